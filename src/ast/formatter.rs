@@ -0,0 +1,249 @@
+use std::collections::{HashSet, VecDeque};
+use std::rc::Rc;
+
+use crate::diagnostics::DiagnosticsColletionCell;
+use crate::terminal::strip_colors;
+
+use super::lexer::{Lexer, Token, TokenKind};
+use super::parser::Parser;
+use super::printer::ASTHiglightPrinter;
+use super::Ast;
+
+// Comment skipped by the parser, the formatter prints it again before the
+// statement that follows it
+pub struct Comment {
+    // as written in the source, with its `//` or `/* */`
+    pub text: String,
+    pub start: usize,
+    // written behind code on the same line
+    pub trailing: bool,
+}
+
+// Layout of the source that is not part of the syntax tree
+#[derive(Default)]
+pub struct Trivia {
+    // in source order
+    pub comments: VecDeque<Comment>,
+    // starts of the tokens and comments preceded by a blank line
+    pub blank_lines: HashSet<usize>,
+}
+
+impl Trivia {
+    pub fn from_tokens(tokens: &[Token]) -> Self {
+        let mut trivia = Trivia::default();
+        // line breaks since the last token that is not whitespace
+        let mut newlines = 0;
+        let mut first = true;
+        for token in tokens {
+            if token.kind == TokenKind::Whitespace {
+                newlines += token.span.literal.matches('\n').count();
+                continue;
+            }
+            if newlines > 1 {
+                trivia.blank_lines.insert(token.span.start);
+            }
+            if let TokenKind::SingleLineComment(_) | TokenKind::MultiLineComment(_) = token.kind {
+                trivia.comments.push_back(Comment {
+                    text: token.span.literal.trim_end().to_string(),
+                    start: token.span.start,
+                    trailing: !first && newlines == 0,
+                });
+            }
+            // a single line comment ends with its line break
+            newlines = usize::from(token.span.literal.ends_with('\n'));
+            first = false;
+        }
+        trivia
+    }
+}
+
+// Canonical uncolored source of the input, fails if the input has syntax errors
+pub fn format(input: &str, diagnostics_colletion: &DiagnosticsColletionCell) -> Result<String, ()> {
+    let mut lexer = Lexer::new(input.to_string(), Rc::clone(diagnostics_colletion));
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token() {
+        tokens.push(token);
+    }
+    let trivia = Trivia::from_tokens(&tokens);
+    let mut parser = Parser::new(tokens, Rc::clone(diagnostics_colletion));
+    let mut ast = Ast::new();
+    while let Some(statement) = parser.next_statement() {
+        ast.add_statement(statement);
+    }
+    if !diagnostics_colletion.borrow().diagnostics.is_empty() {
+        return Err(());
+    }
+
+    let mut printer = ASTHiglightPrinter::with_trivia(trivia);
+    ast.visit(&mut printer);
+    printer.finish();
+    Ok(strip_colors(printer.result()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+    use std::{cell::RefCell, fs, rc::Rc};
+
+    use crate::diagnostics::DiagnosticsColletion;
+
+    fn format(input: &str) -> String {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        super::format(input, &diagnostics_colletion).unwrap()
+    }
+
+    fn examples(directory: &Path, files: &mut Vec<String>) {
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                examples(&path, files);
+            } else if path.extension().is_some_and(|extension| extension == "zn") {
+                files.push(path.display().to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn should_print_canonical_source() {
+        let input = "\
+let   a : i32=1;var b:f64 = 2.0;
+func f(x:i32)->i32{b+=1.5;{ print(x); }
+if x>1{return x;}else{return -x;}}
+struct P{x:i32,func get(self)->i32{return self.x;}}
+let p : P = P{x:1};p.x*=2;
+var o: ( i32? ) = null;let q: *( i32? ) = &o;
+";
+        let expected = "\
+let a: i32 = 1;
+var b: f64 = 2.0;
+func f(x: i32) -> i32 {
+  b += 1.5;
+  {
+    print(x);
+  }
+  if x > 1 {
+    return x;
+  } else {
+    return -x;
+  }
+}
+struct P {
+  x: i32,
+  func get(self) -> i32 {
+    return self.x;
+  }
+}
+let p: P = P { x: 1 };
+p.x *= 2;
+var o: i32? = null;
+let q: *(i32?) = &o;
+";
+        assert_eq!(format(input), expected);
+        assert_eq!(format(expected), expected);
+    }
+
+    #[test]
+    fn should_preserve_comments_and_blank_lines() {
+        let input = "\
+// leading
+module main; // trailing
+
+
+/* block
+   comment */
+func main() -> i32 {   // after the brace
+  let a: i32 = 1;
+
+  // before the return
+  return a; /* behind */ // twice
+  // at the end of the block
+}
+// at the end of the file
+";
+        let expected = "\
+// leading
+module main; // trailing
+
+/* block
+   comment */
+func main() -> i32 { // after the brace
+  let a: i32 = 1;
+
+  // before the return
+  return a; /* behind */ // twice
+  // at the end of the block
+}
+// at the end of the file
+";
+        assert_eq!(format(input), expected);
+        assert_eq!(format(expected), expected);
+    }
+
+    #[test]
+    fn should_keep_the_spelling_of_literals() {
+        let input = "let a: i32 = 0x1F;\nlet b: f64 = 2.50;\nlet c: str = \"\\u{e9}\\n\";\n";
+        assert_eq!(format(input), input);
+    }
+
+    #[test]
+    fn should_keep_comments_inside_statements_in_place() {
+        let input = "\
+func f(x: i32, // the x
+       y: i32 // the y
+) -> i32 {
+  let a: i32 = 1 /* one */ + /* two */ 2;
+  let b: i32 = a * // three
+    3;
+  if x > y {
+    return a;
+  } // after if
+  else {
+    return b;
+  }
+}
+
+func g() -> /* c */ i32 {
+  if true /* always */ {
+    return 1;
+  }
+  return 0;
+}
+";
+        let expected = "\
+func f(
+  x: i32, // the x
+  y: i32 // the y
+) -> i32 {
+  let a: i32 = 1 /* one */ + /* two */ 2;
+  let b: i32 = a * // three
+    3;
+  if x > y {
+    return a;
+  } // after if
+  else {
+    return b;
+  }
+}
+
+func g() -> /* c */ i32 {
+  if true /* always */ {
+    return 1;
+  }
+  return 0;
+}
+";
+        assert_eq!(format(input), expected);
+        assert_eq!(format(expected), expected);
+    }
+
+    #[test]
+    fn should_keep_the_examples_formatted() {
+        let mut files = Vec::new();
+        examples(Path::new("examples"), &mut files);
+        assert!(!files.is_empty());
+        for file in files {
+            let input = fs::read_to_string(&file).unwrap();
+            assert_eq!(format(&input), input, "{} is not formatted", file);
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use core::fmt;
 
+use crate::diagnostics::DiagnosticsColletionCell;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenKind {
     // Litarals
-    Integer(i64),
+    Integer(i128),
     Floating(f64),
+    String(String),
+    Character(char),
     Identifier,
 
     // Keywords
@@ -17,6 +21,10 @@ pub enum TokenKind {
     For,
     In,
     While,
+    Break,
+    Continue,
+    Module,
+    Import,
     I8,
     I16,
     I32,
@@ -31,8 +39,11 @@ pub enum TokenKind {
     Char,
     Str,
     Struct,
+    SelfKeyword,
     Void,
     Null,
+    True,
+    False,
 
     // Arithmetic Operators
     Plus,
@@ -71,7 +82,6 @@ pub enum TokenKind {
     MultiLineComment(String),
     SlashSlash,
     SlashAstrisk,
-    AstriskSlash,
 
     LeftParen,
     RightParen,
@@ -85,6 +95,7 @@ pub enum TokenKind {
     Dot,
     SemiColon,
     Colon,
+    QuestionMark, // optional types
 
     Whitespace,
     Bad,
@@ -94,6 +105,10 @@ pub enum TokenKind {
 impl fmt::Display for TokenKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            TokenKind::Integer(i) => write!(f, "{}", i),
+            TokenKind::Floating(fl) => write!(f, "{}", fl),
+            TokenKind::String(s) => write!(f, "{:?}", s),
+            TokenKind::Character(c) => write!(f, "{:?}", c),
             TokenKind::Identifier => write!(f, "Identifier"),
             TokenKind::Let => write!(f, "Let"),
             TokenKind::Var => write!(f, "Var"),
@@ -104,6 +119,10 @@ impl fmt::Display for TokenKind {
             TokenKind::For => write!(f, "For"),
             TokenKind::In => write!(f, "In"),
             TokenKind::While => write!(f, "While"),
+            TokenKind::Break => write!(f, "Break"),
+            TokenKind::Continue => write!(f, "Continue"),
+            TokenKind::Module => write!(f, "Module"),
+            TokenKind::Import => write!(f, "Import"),
             TokenKind::I8 => write!(f, "I8"),
             TokenKind::I16 => write!(f, "I16"),
             TokenKind::I32 => write!(f, "I32"),
@@ -118,8 +137,11 @@ impl fmt::Display for TokenKind {
             TokenKind::Char => write!(f, "Char"),
             TokenKind::Str => write!(f, "Str"),
             TokenKind::Struct => write!(f, "Struct"),
+            TokenKind::SelfKeyword => write!(f, "Self"),
             TokenKind::Void => write!(f, "Void"),
             TokenKind::Null => write!(f, "Null"),
+            TokenKind::True => write!(f, "True"),
+            TokenKind::False => write!(f, "False"),
             TokenKind::Plus => write!(f, "+"),
             TokenKind::Minus => write!(f, "-"),
             TokenKind::Astrisk => write!(f, "*"),
@@ -141,17 +163,16 @@ impl fmt::Display for TokenKind {
             TokenKind::EqualEqual => write!(f, "=="),
             TokenKind::AmpersandAmpersand => write!(f, "&&"),
             TokenKind::PipePipe => write!(f, "||"),
-            TokenKind::LeftAngleBracket => write!(f, ">"),
+            TokenKind::LeftAngleBracket => write!(f, "<"),
             TokenKind::LeftAngleBracketEqual => write!(f, "<="),
             TokenKind::RightAngleBracket => write!(f, ">"),
             TokenKind::RightAngleBracketEqual => write!(f, ">="),
             TokenKind::SlashSlash => write!(f, "//"),
             TokenKind::SlashAstrisk => write!(f, "/*"),
-            TokenKind::AstriskSlash => write!(f, "*/"),
             TokenKind::LeftParen => write!(f, "("),
             TokenKind::RightParen => write!(f, ")"),
-            TokenKind::LeftBrace => write!(f, "{}", '{'),
-            TokenKind::RightBrace => write!(f, "{}", '}'),
+            TokenKind::LeftBrace => write!(f, "{{"),
+            TokenKind::RightBrace => write!(f, "}}"),
             TokenKind::LeftBracket => write!(f, "["),
             TokenKind::RightBracket => write!(f, "]"),
             TokenKind::MinusRightAngleBracket => write!(f, "->"),
@@ -159,12 +180,12 @@ impl fmt::Display for TokenKind {
             TokenKind::Dot => write!(f, "."),
             TokenKind::SemiColon => write!(f, ";"),
             TokenKind::Colon => write!(f, ":"),
+            TokenKind::QuestionMark => write!(f, "?"),
             TokenKind::Whitespace => write!(f, "Whitespace"),
             TokenKind::Bad => write!(f, "Bad"),
             TokenKind::Eof => write!(f, "Eof"),
             TokenKind::SingleLineComment(_) => write!(f, "//"),
             TokenKind::MultiLineComment(_) => write!(f, "/* */"),
-            _ => todo!(),
         }
     }
 }
@@ -184,6 +205,25 @@ impl TextSpan {
             literal,
         }
     }
+
+    // Merges spans into one covering all of them. The gaps between the spans
+    // are filled with blanks so that the literal keeps the length of the
+    // covered source text.
+    pub fn combine(mut spans: Vec<TextSpan>) -> TextSpan {
+        spans.sort_by_key(|span| span.start);
+        let start = spans.first().map_or(0, |span| span.start);
+        let mut end = start;
+        let mut literal = String::new();
+        for span in spans.iter() {
+            if span.start < end {
+                continue;
+            }
+            literal.push_str(&" ".repeat(span.start - end));
+            literal.push_str(&span.literal);
+            end = span.end;
+        }
+        TextSpan::new(start, end, literal)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -201,11 +241,16 @@ impl Token {
 pub struct Lexer {
     input: String,
     cursor: usize,
+    diagnostics_colletion: DiagnosticsColletionCell,
 }
 
 impl Lexer {
-    pub fn new(input: String) -> Self {
-        Self { input, cursor: 0 }
+    pub fn new(input: String, diagnostics_colletion: DiagnosticsColletionCell) -> Self {
+        Self {
+            input,
+            cursor: 0,
+            diagnostics_colletion,
+        }
     }
 
     pub fn next_token(&mut self) -> Option<Token> {
@@ -221,14 +266,13 @@ impl Lexer {
         }
 
         let start = self.cursor;
-        let mut kind = TokenKind::Bad;
         let c = self.current_char()?;
 
-        if Self::is_number_start(&c) {
-            kind = self.consume_number();
+        let kind = if Self::is_number_start(&c) {
+            self.consume_number()
         } else if Self::is_identifier_start(&c) {
             let identifier = self.consume_identifier();
-            kind = match identifier.as_str() {
+            match identifier.as_str() {
                 "let" => TokenKind::Let,
                 "var" => TokenKind::Var,
                 "func" => TokenKind::Func,
@@ -238,6 +282,10 @@ impl Lexer {
                 "for" => TokenKind::For,
                 "in" => TokenKind::In,
                 "while" => TokenKind::While,
+                "break" => TokenKind::Break,
+                "continue" => TokenKind::Continue,
+                "module" => TokenKind::Module,
+                "import" => TokenKind::Import,
                 "i8" => TokenKind::I8,
                 "i16" => TokenKind::I16,
                 "i32" => TokenKind::I32,
@@ -252,20 +300,27 @@ impl Lexer {
                 "char" => TokenKind::Char,
                 "str" => TokenKind::Str,
                 "struct" => TokenKind::Struct,
+                "self" => TokenKind::SelfKeyword,
                 "void" => TokenKind::Void,
                 "null" => TokenKind::Null,
+                "true" => TokenKind::True,
+                "false" => TokenKind::False,
                 _ => TokenKind::Identifier,
-            };
+            }
+        } else if c == '"' {
+            self.consume_string()
+        } else if c == '\'' {
+            self.consume_character()
         } else if c == '/' && self.peek(1)? == '/' {
-            kind = self.consume_single_line_comment();
+            self.consume_single_line_comment()
         } else if c == '/' && self.peek(1)? == '*' {
-            kind = self.consume_multi_line_comment();
+            self.consume_multi_line_comment()
         } else if Self::is_whitespace(&c) {
             self.consume();
-            kind = TokenKind::Whitespace;
+            TokenKind::Whitespace
         } else {
-            kind = self.consume_punctuation();
-        }
+            self.consume_punctuation()
+        };
 
         let end = self.cursor;
         let literal = self.input[start..end].to_string();
@@ -274,7 +329,7 @@ impl Lexer {
     }
 
     fn is_number_start(c: &char) -> bool {
-        c.is_digit(10)
+        c.is_ascii_digit()
     }
 
     fn is_identifier_start(c: &char) -> bool {
@@ -297,20 +352,15 @@ impl Lexer {
         self.peek(0)
     }
 
+    // The cursor is a byte offset, so spans can slice the input directly
     fn consume(&mut self) -> Option<char> {
-        if self.cursor >= self.input.len() {
-            return None;
-        }
-        let c = self.current_char();
-        self.cursor += 1;
-        c
+        let c = self.current_char()?;
+        self.cursor += c.len_utf8();
+        Some(c)
     }
 
     fn peek(&mut self, offset: usize) -> Option<char> {
-        if self.cursor + offset >= self.input.len() {
-            return None;
-        }
-        self.input.chars().nth(self.cursor + offset)
+        self.input.get(self.cursor..)?.chars().nth(offset)
     }
 
     fn consume_single_line_comment(&mut self) -> TokenKind {
@@ -344,10 +394,149 @@ impl Lexer {
         TokenKind::MultiLineComment(comment)
     }
 
+    // Strings end at the closing quote and may not span multiple lines
+    fn consume_string(&mut self) -> TokenKind {
+        let start = self.cursor;
+        let string = self.consume_quoted('"').unwrap_or_else(|string| {
+            self.diagnostics_colletion
+                .borrow_mut()
+                .report_unterminated_string(TextSpan::new(start, start + 1, "\"".to_string()));
+            string
+        });
+        TokenKind::String(string)
+    }
+
+    fn consume_character(&mut self) -> TokenKind {
+        let start = self.cursor;
+        let content = match self.consume_quoted('\'') {
+            Ok(content) => content,
+            Err(content) => {
+                self.diagnostics_colletion
+                    .borrow_mut()
+                    .report_unterminated_character(TextSpan::new(
+                        start,
+                        start + 1,
+                        "'".to_string(),
+                    ));
+                return TokenKind::Character(content.chars().next().unwrap_or_default());
+            }
+        };
+
+        let span = TextSpan::new(
+            start,
+            self.cursor,
+            self.input[start..self.cursor].to_string(),
+        );
+        let mut chars = content.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => TokenKind::Character(c),
+            (None, _) => {
+                // an invalid escape sequence was already reported
+                if span.literal == "''" {
+                    self.diagnostics_colletion
+                        .borrow_mut()
+                        .report_empty_character(span);
+                }
+                TokenKind::Character(char::default())
+            }
+            (Some(c), Some(_)) => {
+                self.diagnostics_colletion
+                    .borrow_mut()
+                    .report_multiple_characters(span);
+                TokenKind::Character(c)
+            }
+        }
+    }
+
+    // Consumes the text between two quotes and resolves its escape sequences.
+    // Returns the text read so far as error if the line ends before the closing quote.
+    fn consume_quoted(&mut self, quote: char) -> Result<String, String> {
+        self.consume();
+        let mut text = String::new();
+        loop {
+            match self.current_char() {
+                Some(c) if c == quote => {
+                    self.consume();
+                    return Ok(text);
+                }
+                None | Some('\n') => return Err(text),
+                Some('\\') => {
+                    if let Some(c) = self.consume_escape_sequence() {
+                        text.push(c);
+                    }
+                }
+                Some(c) => {
+                    self.consume();
+                    text.push(c);
+                }
+            }
+        }
+    }
+
+    fn consume_escape_sequence(&mut self) -> Option<char> {
+        let start = self.cursor;
+        self.consume();
+        let escaped = match self.current_char() {
+            Some('u') => {
+                self.consume();
+                self.consume_unicode_escape()
+            }
+            Some('\n') | None => None,
+            Some(c) => {
+                self.consume();
+                match c {
+                    'n' => Some('\n'),
+                    't' => Some('\t'),
+                    '\\' => Some('\\'),
+                    '"' => Some('"'),
+                    '\'' => Some('\''),
+                    _ => None,
+                }
+            }
+        };
+        if escaped.is_none() {
+            let end = self.cursor;
+            self.diagnostics_colletion
+                .borrow_mut()
+                .report_invalid_escape_sequence(TextSpan::new(
+                    start,
+                    end,
+                    self.input[start..end].to_string(),
+                ));
+        }
+        escaped
+    }
+
+    // Consumes the `{...}` part of a unicode escape with one to six hex digits
+    fn consume_unicode_escape(&mut self) -> Option<char> {
+        if self.current_char()? != '{' {
+            return None;
+        }
+        self.consume();
+        let mut code = String::new();
+        while let Some(c) = self.current_char().filter(char::is_ascii_hexdigit) {
+            code.push(c);
+            self.consume();
+        }
+        if self.current_char()? != '}' {
+            return None;
+        }
+        self.consume();
+        if code.is_empty() || code.len() > 6 {
+            return None;
+        }
+        char::from_u32(u32::from_str_radix(&code, 16).ok()?)
+    }
+
     fn consume_number(&mut self) -> TokenKind {
-        let mut integer_part: i64 = 0;
-        let mut fractional_part: i64 = 0;
-        let mut divisior_for_fraction: i64 = 1;
+        let start = self.cursor;
+        // None once the literal does not fit into a u64
+        let mut integer_part: Option<u64> = Some(0);
+        let mut push_digit = |radix: u32, c: char| {
+            integer_part = integer_part
+                .and_then(|value| value.checked_mul(radix as u64))
+                .and_then(|value| value.checked_add(c.to_digit(radix).unwrap() as u64));
+        };
 
         let mut oct_format = false;
         let mut hex_format = false;
@@ -355,7 +544,7 @@ impl Lexer {
             self.consume();
             oct_format = true;
 
-            if self.current_char().unwrap() == 'x' {
+            if self.current_char() == Some('x') {
                 self.consume();
                 hex_format = true;
                 oct_format = false;
@@ -367,38 +556,43 @@ impl Lexer {
         while let Some(c) = self.current_char() {
             if !dot_found && oct_format && c.is_digit(8) {
                 self.consume();
-                integer_part = integer_part * 8 + c.to_digit(8).unwrap() as i64;
-            } else if !dot_found && hex_format && c.is_digit(16) {
+                push_digit(8, c);
+            } else if !dot_found && hex_format && c.is_ascii_hexdigit() {
                 self.consume();
-                integer_part = integer_part * 16 + c.to_digit(16).unwrap() as i64;
-            } else if c.is_digit(10) {
+                push_digit(16, c);
+            } else if c.is_ascii_digit() {
                 self.consume();
                 if !dot_found {
-                    integer_part = integer_part * 10 + c.to_digit(10).unwrap() as i64;
-                } else {
-                    fractional_part = fractional_part * 10 + c.to_digit(10).unwrap() as i64;
-                    divisior_for_fraction *= 10;
+                    push_digit(10, c);
                 }
             } else if !oct_format
                 && !hex_format
                 && Self::is_decimal_dot(&c)
-                && self.peek(1).unwrap().is_digit(10)
+                && self.peek(1).is_some_and(|next| next.is_ascii_digit())
             {
-                self.consume();
                 if dot_found {
                     break;
                 }
+                self.consume();
                 dot_found = true;
             } else {
                 break;
             }
         }
+        let literal = &self.input[start..self.cursor];
         if dot_found {
-            return TokenKind::Floating(
-                integer_part as f64 + (fractional_part as f64 / divisior_for_fraction as f64),
-            );
-        } else {
-            return TokenKind::Integer(integer_part);
+            // floats are always decimal, so the literal is valid Rust syntax
+            return TokenKind::Floating(literal.parse().unwrap());
+        }
+        match integer_part {
+            Some(integer) => TokenKind::Integer(integer as i128),
+            None => {
+                let span = TextSpan::new(start, self.cursor, literal.to_string());
+                self.diagnostics_colletion
+                    .borrow_mut()
+                    .report_integer_literal_too_large(span);
+                TokenKind::Integer(0)
+            }
         }
     }
 
@@ -478,7 +672,7 @@ impl Lexer {
             '&' => {
                 if self.current_char().unwrap() == '=' {
                     self.consume();
-                    return TokenKind::AstriskEqual;
+                    return TokenKind::AmpersandEqual;
                 }
                 if self.current_char().unwrap() == '&' {
                     self.consume();
@@ -487,7 +681,7 @@ impl Lexer {
                 TokenKind::Ampersand
             }
             '^' => {
-                if self.current_char().unwrap() == '^' {
+                if self.current_char().unwrap() == '=' {
                     self.consume();
                     return TokenKind::CaretEqual;
                 }
@@ -525,6 +719,7 @@ impl Lexer {
             '.' => TokenKind::Dot,
             ';' => TokenKind::SemiColon,
             ':' => TokenKind::Colon,
+            '?' => TokenKind::QuestionMark,
             _ => TokenKind::Bad,
         }
     }
@@ -532,10 +727,14 @@ impl Lexer {
 
 #[cfg(test)]
 mod test {
+    use std::{cell::RefCell, rc::Rc};
+
     use super::{Lexer, TextSpan, Token, TokenKind};
+    use crate::diagnostics::DiagnosticsColletion;
 
     fn verify(input: &str, expected_tokens: Vec<Token>) {
-        let mut lexer = Lexer::new(input.to_string());
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let mut lexer = Lexer::new(input.to_string(), diagnostics_colletion);
         let mut lexed_tokens = Vec::new();
         while let Some(token) = lexer.next_token() {
             if token.kind == TokenKind::Whitespace {
@@ -561,7 +760,7 @@ mod test {
                 ex.kind, ac.kind
             );
 
-            if ex.span.literal.len() > 0 {
+            if !ex.span.literal.is_empty() {
                 assert_eq!(
                     ac.span.literal, *ex.span.literal,
                     "Tokens do not match. Expected {:?} but found {:?}",
@@ -571,6 +770,19 @@ mod test {
         }
     }
 
+    fn lex_diagnostics(input: &str) -> Vec<(String, TextSpan)> {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let mut lexer = Lexer::new(input.to_string(), Rc::clone(&diagnostics_colletion));
+        while lexer.next_token().is_some() {}
+
+        let diagnostics = diagnostics_colletion.borrow();
+        diagnostics
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.message.clone(), diagnostic.span.clone()))
+            .collect()
+    }
+
     fn token(kind: TokenKind, literal: &str) -> Token {
         Token {
             kind,
@@ -657,4 +869,173 @@ mod test {
 
         verify(input, expected_tokens);
     }
+
+    #[test]
+    fn lex_loop_control_statements() {
+        let input = "while a { break; continue; }";
+        let expected_tokens = vec![
+            token(TokenKind::While, ""),
+            token(TokenKind::Identifier, "a"),
+            token(TokenKind::LeftBrace, ""),
+            token(TokenKind::Break, "break"),
+            token(TokenKind::SemiColon, ""),
+            token(TokenKind::Continue, "continue"),
+            token(TokenKind::SemiColon, ""),
+            token(TokenKind::RightBrace, ""),
+            token(TokenKind::Eof, ""),
+        ];
+
+        verify(input, expected_tokens);
+    }
+
+    #[test]
+    fn lex_module_statements() {
+        let input = "module main; import math;";
+        let expected_tokens = vec![
+            token(TokenKind::Module, "module"),
+            token(TokenKind::Identifier, "main"),
+            token(TokenKind::SemiColon, ""),
+            token(TokenKind::Import, "import"),
+            token(TokenKind::Identifier, "math"),
+            token(TokenKind::SemiColon, ""),
+            token(TokenKind::Eof, ""),
+        ];
+
+        verify(input, expected_tokens);
+    }
+
+    #[test]
+    fn lex_integer_literals_up_to_u64_max() {
+        let input = "9223372036854775807 9223372036854775808 18446744073709551615 0xFFFFFFFFFFFFFFFF 01777777777777777777777";
+        let expected_tokens = vec![
+            token(TokenKind::Integer(i64::MAX as i128), ""),
+            token(TokenKind::Integer(i64::MAX as i128 + 1), ""),
+            token(TokenKind::Integer(u64::MAX as i128), ""),
+            token(TokenKind::Integer(u64::MAX as i128), ""),
+            token(TokenKind::Integer(u64::MAX as i128), ""),
+            token(TokenKind::Eof, ""),
+        ];
+
+        verify(input, expected_tokens);
+        assert_eq!(lex_diagnostics(input), vec![]);
+    }
+
+    #[test]
+    fn report_integer_literals_larger_than_u64_max() {
+        let input = "18446744073709551616 0x10000000000000000 1.5";
+        assert_eq!(
+            lex_diagnostics(input),
+            vec![
+                (
+                    "Integer literal <18446744073709551616> is too large".to_string(),
+                    TextSpan::new(0, 20, "18446744073709551616".to_string())
+                ),
+                (
+                    "Integer literal <0x10000000000000000> is too large".to_string(),
+                    TextSpan::new(21, 40, "0x10000000000000000".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_string_literals() {
+        let input = r#"let s: str = "ä\n\t\\\"\u{1F600}" + "";"#;
+        let expected_tokens = vec![
+            token(TokenKind::Let, ""),
+            token(TokenKind::Identifier, "s"),
+            token(TokenKind::Colon, ""),
+            token(TokenKind::Str, ""),
+            token(TokenKind::Equal, ""),
+            token(
+                TokenKind::String("ä\n\t\\\"😀".to_string()),
+                r#""ä\n\t\\\"\u{1F600}""#,
+            ),
+            token(TokenKind::Plus, ""),
+            token(TokenKind::String(String::new()), r#""""#),
+            token(TokenKind::SemiColon, ""),
+            token(TokenKind::Eof, ""),
+        ];
+
+        verify(input, expected_tokens);
+    }
+
+    #[test]
+    fn report_unterminated_string_at_opening_quote() {
+        let input = "a = \"abc;\nb;";
+        let expected_tokens = vec![
+            token(TokenKind::Identifier, "a"),
+            token(TokenKind::Equal, ""),
+            token(TokenKind::String("abc;".to_string()), "\"abc;"),
+            token(TokenKind::Identifier, "b"),
+            token(TokenKind::SemiColon, ""),
+            token(TokenKind::Eof, ""),
+        ];
+        verify(input, expected_tokens);
+
+        assert_eq!(
+            lex_diagnostics(input),
+            vec![(
+                "Unterminated string literal".to_string(),
+                TextSpan::new(4, 5, "\"".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn report_invalid_escape_sequences() {
+        let input = r#""\q \u{110000} \u12""#;
+        let messages: Vec<String> = lex_diagnostics(input)
+            .into_iter()
+            .map(|(message, _)| message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                r"Invalid escape sequence <\q>",
+                r"Invalid escape sequence <\u{110000}>",
+                r"Invalid escape sequence <\u>",
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_character_literals() {
+        let input = r"'a' 'ß' '\'' '\u{1F600}'";
+        let expected_tokens = vec![
+            token(TokenKind::Character('a'), "'a'"),
+            token(TokenKind::Character('ß'), "'ß'"),
+            token(TokenKind::Character('\''), r"'\''"),
+            token(TokenKind::Character('😀'), r"'\u{1F600}'"),
+            token(TokenKind::Eof, ""),
+        ];
+
+        verify(input, expected_tokens);
+    }
+
+    #[test]
+    fn report_malformed_character_literals() {
+        let input = "'' 'ab' '\\x' 'c";
+        assert_eq!(
+            lex_diagnostics(input),
+            vec![
+                (
+                    "Empty character literal".to_string(),
+                    TextSpan::new(0, 2, "''".to_string())
+                ),
+                (
+                    "Character literal must contain exactly one character".to_string(),
+                    TextSpan::new(3, 7, "'ab'".to_string())
+                ),
+                (
+                    r"Invalid escape sequence <\x>".to_string(),
+                    TextSpan::new(9, 11, r"\x".to_string())
+                ),
+                (
+                    "Unterminated character literal".to_string(),
+                    TextSpan::new(13, 14, "'".to_string())
+                ),
+            ]
+        );
+    }
 }
@@ -0,0 +1,152 @@
+use super::value::Value;
+
+// Location of a value in memory. The generation tells apart the values that
+// lived in the same slot, so a pointer to a freed value never reaches its successor.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Address {
+    index: usize,
+    generation: usize,
+}
+
+// Step from a value to one of its parts, `.x` or `[2]`
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+struct Slot {
+    value: Value,
+    generation: usize,
+}
+
+// Storage of all variables of the interpreter. Every variable gets a slot when
+// it is declared, which is freed again when its scope ends.
+pub struct Memory {
+    slots: Vec<Slot>,
+    free_slots: Vec<usize>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+        }
+    }
+
+    pub fn allocate(&mut self, value: Value) -> Address {
+        match self.free_slots.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.value = value;
+                Address {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    value,
+                    generation: 0,
+                });
+                Address {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    pub fn free(&mut self, address: Address) {
+        if self.get(&address).is_none() {
+            return;
+        }
+        let slot = &mut self.slots[address.index];
+        slot.value = Value::Void;
+        slot.generation += 1;
+        self.free_slots.push(address.index);
+    }
+
+    // None if the value at the address was already freed
+    pub fn get(&self, address: &Address) -> Option<&Value> {
+        self.slots
+            .get(address.index)
+            .filter(|slot| slot.generation == address.generation)
+            .map(|slot| &slot.value)
+    }
+
+    pub fn get_mut(&mut self, address: &Address) -> Option<&mut Value> {
+        self.slots
+            .get_mut(address.index)
+            .filter(|slot| slot.generation == address.generation)
+            .map(|slot| &mut slot.value)
+    }
+}
+
+impl Address {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Address, Memory};
+    use crate::ast::value::Value;
+
+    #[test]
+    fn freed_slots_are_reused_without_reviving_old_addresses() {
+        let mut memory = Memory::new();
+        let first = memory.allocate(Value::I32(1));
+        memory.free(first);
+        let second = memory.allocate(Value::I32(2));
+
+        assert_eq!(first.index(), second.index());
+        assert_eq!(memory.get(&first), None);
+        assert_eq!(memory.get(&second), Some(&Value::I32(2)));
+    }
+
+    #[test]
+    fn freeing_an_address_twice_keeps_the_value_of_its_successor() {
+        let mut memory = Memory::new();
+        let first = memory.allocate(Value::I32(1));
+        memory.free(first);
+        let second = memory.allocate(Value::I32(2));
+        memory.free(first);
+
+        assert_eq!(memory.get(&second), Some(&Value::I32(2)));
+        // the slot was only put back once, so a new value gets a slot of its own
+        let third = memory.allocate(Value::I32(3));
+        assert_ne!(second.index(), third.index());
+        assert_eq!(memory.get(&second), Some(&Value::I32(2)));
+    }
+
+    #[test]
+    fn dangling_addresses_can_neither_be_read_nor_written() {
+        let mut memory = Memory::new();
+        let first = memory.allocate(Value::I32(1));
+        memory.free(first);
+
+        assert_eq!(memory.get_mut(&first), None);
+        let second = memory.allocate(Value::I32(2));
+        assert_eq!(memory.get_mut(&first), None);
+        *memory.get_mut(&second).unwrap() = Value::I32(3);
+        assert_eq!(memory.get(&second), Some(&Value::I32(3)));
+    }
+
+    #[test]
+    fn addresses_outside_of_the_memory_are_not_found() {
+        let mut memory = Memory::new();
+        memory.allocate(Value::I32(1));
+        let outside = Address {
+            index: 1,
+            generation: 0,
+        };
+
+        assert_eq!(memory.get(&outside), None);
+        assert_eq!(memory.get_mut(&outside), None);
+        memory.free(outside);
+        assert_eq!(memory.allocate(Value::I32(2)).index(), 1);
+    }
+}
@@ -1,11 +1,18 @@
+use core::fmt;
+
 use lexer::{TextSpan, Token};
 use printer::ASTTreePrinter;
 
+pub mod formatter;
 pub mod lexer;
+pub mod memory;
 pub mod parser;
 pub mod printer;
 pub mod solver;
 pub mod symbol_checker;
+pub mod symbol_index;
+pub mod type_checker;
+pub mod value;
 
 pub struct Ast {
     statements: Vec<ASTStatement>,
@@ -22,18 +29,64 @@ impl Ast {
         self.statements.push(statement);
     }
 
+    // Name given by the `module` declaration of the file
+    pub fn module_declaration(&self) -> Option<&Token> {
+        self.statements
+            .iter()
+            .find_map(|statement| match &statement.kind {
+                ASTStatementKind::Module(module) => Some(&module.identifier),
+                _ => None,
+            })
+    }
+
+    // Functions declared at the top level of the file
+    pub fn functions(&self) -> Vec<&ASTFunctionStatement> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                ASTStatementKind::FuncDecl(function) => Some(function),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Whether the last statement is a bare expression, like the inputs of the
+    // REPL whose value is printed
+    pub fn ends_with_expression(&self) -> bool {
+        matches!(
+            self.statements.last().map(|statement| &statement.kind),
+            Some(ASTStatementKind::Expr(_))
+        )
+    }
+
+    // The first statements, like the ones of a REPL input that ran before an error
+    pub fn prefix(&self, count: usize) -> Ast {
+        Self {
+            statements: self.statements[..count].to_vec(),
+        }
+    }
+
+    pub fn imports(&self) -> Vec<&Token> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                ASTStatementKind::Import(import) => Some(&import.identifier),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn visit(&self, printer: &mut dyn ASTVisitor) {
         for statement in &self.statements {
             printer.visit_statement(statement);
         }
     }
 
-    pub fn visualize(&self) {
+    // Colored tree of the statements, one node per line
+    pub fn visualize(&self) -> String {
         let mut printer = ASTTreePrinter::new();
-        let decoration = "=".repeat(80);
-        println!("{decoration}\nAST:\n{decoration}");
         self.visit(&mut printer);
-        println!("{decoration}\n");
+        printer.result().to_string()
     }
 }
 
@@ -43,12 +96,17 @@ pub trait ASTVisitor {
             ASTStatementKind::Expr(expr) => self.visit_expression(expr),
             ASTStatementKind::Return(statement) => self.visit_return_statement(statement),
             ASTStatementKind::FuncDecl(statement) => self.visit_funtion_statement(statement),
+            ASTStatementKind::StructDecl(statement) => self.visit_struct_statement(statement),
             ASTStatementKind::Let(statement) => self.visit_let_statement(statement),
             ASTStatementKind::Var(statement) => self.visit_var_statement(statement),
             ASTStatementKind::Compound(statement) => self.visit_compound_statement(statement),
             ASTStatementKind::If(statement) => self.visit_if_statement(statement),
             ASTStatementKind::For(statement) => self.visit_for_loop_statement(statement),
             ASTStatementKind::While(statement) => self.visit_while_loop_statement(statement),
+            ASTStatementKind::Break(statement) => self.visit_break_statement(statement),
+            ASTStatementKind::Continue(statement) => self.visit_continue_statement(statement),
+            ASTStatementKind::Module(statement) => self.visit_module_statement(statement),
+            ASTStatementKind::Import(statement) => self.visit_import_statement(statement),
         }
     }
 
@@ -56,13 +114,32 @@ pub trait ASTVisitor {
         match &expr.kind {
             ASTExpressionKind::IntegerLiteral(i) => self.visit_integer(i),
             ASTExpressionKind::FloatingLiteral(f) => self.visit_float(f),
+            ASTExpressionKind::String(s) => self.visit_string(s),
+            ASTExpressionKind::Character(c) => self.visit_character(c),
+            ASTExpressionKind::Boolean(b) => self.visit_boolean(b),
+            ASTExpressionKind::Null => self.visit_null(),
             ASTExpressionKind::Variable(expr) => self.visit_variable_expression(expr),
-            ASTExpressionKind::StringLiteral(_) => todo!(),
             ASTExpressionKind::Unary(expr) => self.visit_unary_expression(expr),
             ASTExpressionKind::Binary(expr) => self.visit_binary_expression(expr),
             ASTExpressionKind::Parenthesized(expr) => self.visit_parenthesised_expression(expr),
             ASTExpressionKind::FunctionCall(expr) => self.visit_function_call_expression(expr),
+            ASTExpressionKind::MethodCall(expr) => self.visit_method_call_expression(expr),
             ASTExpressionKind::Assignment(expr) => self.visit_assignment_expression(expr),
+            ASTExpressionKind::StructLiteral(expr) => self.visit_struct_literal_expression(expr),
+            ASTExpressionKind::FieldAccess(expr) => self.visit_field_access_expression(expr),
+            ASTExpressionKind::FieldAssignment(expr) => {
+                self.visit_field_assignment_expression(expr)
+            }
+            ASTExpressionKind::ArrayLiteral(expr) => self.visit_array_literal_expression(expr),
+            ASTExpressionKind::Index(expr) => self.visit_index_expression(expr),
+            ASTExpressionKind::IndexAssignment(expr) => {
+                self.visit_index_assignment_expression(expr)
+            }
+            ASTExpressionKind::AddressOf(expr) => self.visit_address_of_expression(expr),
+            ASTExpressionKind::Dereference(expr) => self.visit_dereference_expression(expr),
+            ASTExpressionKind::DereferenceAssignment(expr) => {
+                self.visit_dereference_assignment_expression(expr)
+            }
             ASTExpressionKind::Error(span) => self.visit_error(span),
         }
     }
@@ -84,6 +161,11 @@ pub trait ASTVisitor {
     fn visit_if_statement(&mut self, statement: &ASTIfStatement);
     fn visit_for_loop_statement(&mut self, statement: &ASTForStatement);
     fn visit_while_loop_statement(&mut self, statement: &ASTWhileStatement);
+    fn visit_break_statement(&mut self, _statement: &ASTBreakStatement) {}
+    fn visit_continue_statement(&mut self, _statement: &ASTContinueStatement) {}
+    // modules are resolved by the compilation unit before any pass runs
+    fn visit_module_statement(&mut self, _statement: &ASTModuleStatement) {}
+    fn visit_import_statement(&mut self, _statement: &ASTImportStatement) {}
 
     fn visit_funtion_statement(&mut self, function: &ASTFunctionStatement) {
         if let ASTStatementKind::Compound(statement) = &function.body.kind {
@@ -91,22 +173,40 @@ pub trait ASTVisitor {
         }
     }
 
+    fn visit_struct_statement(&mut self, statement: &ASTStructStatement);
+
     fn visit_expression(&mut self, expr: &ASTExpression) {
         self.do_visit_expression(expr);
     }
 
     fn visit_assignment_expression(&mut self, expr: &ASTAssignmentExpression);
     fn visit_function_call_expression(&mut self, expr: &ASTFunctionCallExpression);
+    fn visit_method_call_expression(&mut self, expr: &ASTMethodCallExpression);
     fn visit_variable_expression(&mut self, expr: &ASTVariableExpression);
+    fn visit_struct_literal_expression(&mut self, expr: &ASTStructLiteralExpression);
+    fn visit_field_access_expression(&mut self, expr: &ASTFieldAccessExpression);
+    fn visit_field_assignment_expression(&mut self, expr: &ASTFieldAssignmentExpression);
+    fn visit_array_literal_expression(&mut self, expr: &ASTArrayLiteralExpression);
+    fn visit_index_expression(&mut self, expr: &ASTIndexExpression);
+    fn visit_index_assignment_expression(&mut self, expr: &ASTIndexAssignmentExpression);
+    fn visit_address_of_expression(&mut self, expr: &ASTAddressOfExpression);
+    fn visit_dereference_expression(&mut self, expr: &ASTDereferenceExpression);
+    fn visit_dereference_assignment_expression(
+        &mut self,
+        expr: &ASTDereferenceAssignmentExpression,
+    );
 
     fn visit_unary_expression(&mut self, expr: &ASTUnaryExpression);
     fn visit_binary_expression(&mut self, expr: &ASTBinaryExpression);
     fn visit_parenthesised_expression(&mut self, expr: &ASTParenthesizedExpression);
-    fn visit_binary_operator(&mut self, op: &ASTBinaryOperator);
 
-    fn visit_error(&mut self, span: &TextSpan) {}
-    fn visit_integer(&mut self, integer: &i64);
+    fn visit_error(&mut self, _span: &TextSpan) {}
+    fn visit_integer(&mut self, integer: &i128);
     fn visit_float(&mut self, float: &f64);
+    fn visit_string(&mut self, string: &str);
+    fn visit_character(&mut self, character: &char);
+    fn visit_boolean(&mut self, boolean: &bool);
+    fn visit_null(&mut self);
 }
 
 #[derive(Clone)]
@@ -117,46 +217,165 @@ enum ASTStatementKind {
     Return(ASTReturnStatement),
     Compound(ASTCompoundStatement),
     FuncDecl(ASTFunctionStatement),
+    StructDecl(ASTStructStatement),
     If(ASTIfStatement),
     While(ASTWhileStatement),
     For(ASTForStatement),
+    Break(ASTBreakStatement),
+    Continue(ASTContinueStatement),
+    Module(ASTModuleStatement),
+    Import(ASTImportStatement),
+}
+
+#[derive(Clone, PartialEq)]
+enum ASTTypeKind {
+    // primitive type or struct name
+    Named(Token),
+    Pointer(Box<ASTType>),
+    Optional(Box<ASTType>),
+    // `[T; N]`
+    Array(Box<ASTType>, usize),
+    Error,
+}
+
+// Type annotation as written in the source, e.g. `*Point?`
+#[derive(Clone, PartialEq)]
+pub struct ASTType {
+    kind: ASTTypeKind,
+    span: TextSpan,
+}
+
+impl ASTType {
+    fn named(token: Token) -> Self {
+        Self {
+            span: token.span.clone(),
+            kind: ASTTypeKind::Named(token),
+        }
+    }
+
+    fn pointer(star: &Token, pointee: ASTType) -> Self {
+        Self {
+            span: TextSpan::combine(vec![star.span.clone(), pointee.span.clone()]),
+            kind: ASTTypeKind::Pointer(Box::new(pointee)),
+        }
+    }
+
+    fn optional(data_type: ASTType, question_mark: &Token) -> Self {
+        Self {
+            span: TextSpan::combine(vec![data_type.span.clone(), question_mark.span.clone()]),
+            kind: ASTTypeKind::Optional(Box::new(data_type)),
+        }
+    }
+
+    fn array(
+        left_bracket: &Token,
+        element_type: ASTType,
+        length: usize,
+        right_bracket: &Token,
+    ) -> Self {
+        Self {
+            span: TextSpan::combine(vec![left_bracket.span.clone(), right_bracket.span.clone()]),
+            kind: ASTTypeKind::Array(Box::new(element_type), length),
+        }
+    }
+
+    // `(T)`, only the span tells it apart from `T`
+    fn grouped(left_paren: &Token, data_type: ASTType, right_paren: &Token) -> Self {
+        Self {
+            span: TextSpan::combine(vec![left_paren.span.clone(), right_paren.span.clone()]),
+            kind: data_type.kind,
+        }
+    }
+
+    fn error(span: TextSpan) -> Self {
+        Self {
+            kind: ASTTypeKind::Error,
+            span,
+        }
+    }
+
+    fn is_optional(&self) -> bool {
+        matches!(self.kind, ASTTypeKind::Optional(_))
+    }
+
+    pub fn is_void(&self) -> bool {
+        matches!(&self.kind, ASTTypeKind::Named(token) if token.kind == lexer::TokenKind::Void)
+    }
+}
+
+impl fmt::Display for ASTType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ASTTypeKind::Named(token) => write!(f, "{}", token.span.literal),
+            // `*T?` is an optional pointer, a pointer to an optional is `*(T?)`
+            ASTTypeKind::Pointer(pointee) if pointee.is_optional() => write!(f, "*({})", pointee),
+            ASTTypeKind::Pointer(pointee) => write!(f, "*{}", pointee),
+            ASTTypeKind::Optional(data_type) if data_type.is_optional() => {
+                write!(f, "({})?", data_type)
+            }
+            ASTTypeKind::Optional(data_type) => write!(f, "{}?", data_type),
+            ASTTypeKind::Array(element_type, length) => write!(f, "[{}; {}]", element_type, length),
+            ASTTypeKind::Error => write!(f, "?"),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct ASTLetStatement {
     identifier: Token,
-    data_type: Token,
+    data_type: ASTType,
     initializer: ASTExpression,
 }
 
 #[derive(Clone)]
 pub struct ASTVarStatement {
     identifier: Token,
-    data_type: Token,
+    data_type: ASTType,
     initializer: ASTExpression,
 }
 
 #[derive(Clone)]
 pub struct ASTReturnStatement {
+    keyword: Token,
     expr: ASTExpression,
 }
 #[derive(Clone)]
 pub struct ASTCompoundStatement {
+    open_brace: Token,
     statements: Vec<ASTStatement>,
+    close_brace: Token,
 }
 
 #[derive(Clone)]
 pub struct FunctionArgumentDeclaration {
     identifier: Token,
-    data_type: Token,
+    data_type: ASTType,
 }
 
 #[derive(Clone)]
 pub struct ASTFunctionStatement {
+    keyword: Token,
     identifier: Token,
+    // the `self` parameter of a method
+    receiver: Option<Token>,
     arguments: Vec<FunctionArgumentDeclaration>,
+    right_paren: Token,
     body: Box<ASTStatement>,
-    return_type: Token,
+    return_type: ASTType,
+}
+
+#[derive(Clone)]
+pub struct StructFieldDeclaration {
+    identifier: Token,
+    data_type: ASTType,
+}
+
+#[derive(Clone)]
+pub struct ASTStructStatement {
+    identifier: Token,
+    fields: Vec<StructFieldDeclaration>,
+    methods: Vec<ASTFunctionStatement>,
+    close_brace: Token,
 }
 
 #[derive(Clone)]
@@ -166,6 +385,7 @@ pub struct ASTElseStatement {
 }
 #[derive(Clone)]
 pub struct ASTIfStatement {
+    #[allow(dead_code)]
     keyword: Token,
     condition: ASTExpression,
     then_branch: Box<ASTStatement>,
@@ -179,59 +399,93 @@ pub struct ASTWhileStatement {
     body: Box<ASTStatement>,
 }
 
+// What a for loop iterates over, `0..10` or the elements of an array
+#[derive(Clone)]
+pub enum ASTForIterable {
+    Range(Box<ASTExpression>, Box<ASTExpression>),
+    Array(Box<ASTExpression>),
+}
+
 #[derive(Clone)]
 pub struct ASTForStatement {
     keyword: Token,
     loop_variable: Token,
-    range: (ASTExpression, ASTExpression),
+    iterable: ASTForIterable,
     body: Box<ASTStatement>,
 }
 
+#[derive(Clone)]
+pub struct ASTBreakStatement {
+    keyword: Token,
+}
+
+#[derive(Clone)]
+pub struct ASTContinueStatement {
+    keyword: Token,
+}
+
+// `module name;`
+#[derive(Clone)]
+pub struct ASTModuleStatement {
+    keyword: Token,
+    identifier: Token,
+}
+
+// `import name;`
+#[derive(Clone)]
+pub struct ASTImportStatement {
+    keyword: Token,
+    identifier: Token,
+}
+
 #[derive(Clone)]
 pub struct ASTStatement {
     kind: ASTStatementKind,
+    // from the first to the last token of the statement
+    span: TextSpan,
 }
 
 impl ASTStatement {
+    // the parser sets the span once the whole statement is consumed
     fn new(kind: ASTStatementKind) -> Self {
-        Self { kind }
+        Self {
+            kind,
+            span: TextSpan::new(0, 0, String::new()),
+        }
     }
 
     fn expression(expr: ASTExpression) -> Self {
-        Self {
-            kind: ASTStatementKind::Expr(expr),
-        }
+        Self::new(ASTStatementKind::Expr(expr))
     }
 
-    fn return_statement(expr: ASTExpression) -> Self {
-        Self {
-            kind: ASTStatementKind::Return(ASTReturnStatement { expr }),
-        }
+    fn return_statement(keyword: Token, expr: ASTExpression) -> Self {
+        Self::new(ASTStatementKind::Return(ASTReturnStatement {
+            keyword,
+            expr,
+        }))
     }
-    fn let_statement(identifier: Token, data_type: Token, initializer: ASTExpression) -> Self {
-        Self {
-            kind: ASTStatementKind::Let(ASTLetStatement {
-                identifier,
-                data_type,
-                initializer,
-            }),
-        }
+    fn let_statement(identifier: Token, data_type: ASTType, initializer: ASTExpression) -> Self {
+        Self::new(ASTStatementKind::Let(ASTLetStatement {
+            identifier,
+            data_type,
+            initializer,
+        }))
     }
 
-    fn var_statement(identifier: Token, data_type: Token, initializer: ASTExpression) -> Self {
-        Self {
-            kind: ASTStatementKind::Var(ASTVarStatement {
-                identifier,
-                data_type,
-                initializer,
-            }),
-        }
+    fn var_statement(identifier: Token, data_type: ASTType, initializer: ASTExpression) -> Self {
+        Self::new(ASTStatementKind::Var(ASTVarStatement {
+            identifier,
+            data_type,
+            initializer,
+        }))
     }
 
-    fn compound(statements: Vec<ASTStatement>) -> Self {
-        Self {
-            kind: ASTStatementKind::Compound(ASTCompoundStatement { statements }),
-        }
+    fn compound(open_brace: Token, statements: Vec<ASTStatement>, close_brace: Token) -> Self {
+        Self::new(ASTStatementKind::Compound(ASTCompoundStatement {
+            open_brace,
+            statements,
+            close_brace,
+        }))
     }
 
     fn conditional(
@@ -240,149 +494,356 @@ impl ASTStatement {
         then_branch: ASTStatement,
         else_branch: Option<ASTElseStatement>,
     ) -> Self {
-        Self {
-            kind: ASTStatementKind::If(ASTIfStatement {
-                keyword,
-                condition,
-                then_branch: Box::new(then_branch),
-                else_branch,
-            }),
-        }
+        Self::new(ASTStatementKind::If(ASTIfStatement {
+            keyword,
+            condition,
+            then_branch: Box::new(then_branch),
+            else_branch,
+        }))
     }
 
     fn while_loop(keyword: Token, condition: ASTExpression, body: ASTStatement) -> Self {
-        Self {
-            kind: ASTStatementKind::While(ASTWhileStatement {
-                keyword,
-                condition,
-                body: Box::new(body),
-            }),
-        }
+        Self::new(ASTStatementKind::While(ASTWhileStatement {
+            keyword,
+            condition,
+            body: Box::new(body),
+        }))
     }
 
     fn for_loop(
         keyword: Token,
         loop_variable: Token,
-        range: (ASTExpression, ASTExpression),
+        iterable: ASTForIterable,
         body: ASTStatement,
     ) -> Self {
-        Self {
-            kind: ASTStatementKind::For(ASTForStatement {
-                keyword,
-                loop_variable,
-                range,
-                body: Box::new(body),
-            }),
-        }
+        Self::new(ASTStatementKind::For(ASTForStatement {
+            keyword,
+            loop_variable,
+            iterable,
+            body: Box::new(body),
+        }))
+    }
+
+    fn break_statement(keyword: Token) -> Self {
+        Self::new(ASTStatementKind::Break(ASTBreakStatement { keyword }))
     }
 
-    fn function(
+    fn continue_statement(keyword: Token) -> Self {
+        Self::new(ASTStatementKind::Continue(ASTContinueStatement { keyword }))
+    }
+
+    fn module_statement(keyword: Token, identifier: Token) -> Self {
+        Self::new(ASTStatementKind::Module(ASTModuleStatement {
+            keyword,
+            identifier,
+        }))
+    }
+
+    fn import_statement(keyword: Token, identifier: Token) -> Self {
+        Self::new(ASTStatementKind::Import(ASTImportStatement {
+            keyword,
+            identifier,
+        }))
+    }
+
+    fn function(function: ASTFunctionStatement) -> Self {
+        Self::new(ASTStatementKind::FuncDecl(function))
+    }
+
+    fn struct_declaration(
         identifier: Token,
-        arguments: Vec<FunctionArgumentDeclaration>,
-        body: ASTStatement,
-        return_type: Token,
+        fields: Vec<StructFieldDeclaration>,
+        methods: Vec<ASTFunctionStatement>,
+        close_brace: Token,
     ) -> Self {
-        Self {
-            kind: ASTStatementKind::FuncDecl(ASTFunctionStatement {
-                identifier,
-                arguments,
-                body: Box::new(body),
-                return_type,
-            }),
-        }
+        Self::new(ASTStatementKind::StructDecl(ASTStructStatement {
+            identifier,
+            fields,
+            methods,
+            close_brace,
+        }))
     }
 }
 
 #[derive(Clone, PartialEq)]
 enum ASTExpressionKind {
-    IntegerLiteral(i64),
+    IntegerLiteral(i128),
     FloatingLiteral(f64),
-    StringLiteral(String),
+    String(String),
+    Character(char),
+    Boolean(bool),
+    Null,
     Unary(ASTUnaryExpression),
     Binary(ASTBinaryExpression),
     Parenthesized(ASTParenthesizedExpression),
     Variable(ASTVariableExpression),
     Assignment(ASTAssignmentExpression),
     FunctionCall(ASTFunctionCallExpression),
+    MethodCall(ASTMethodCallExpression),
+    StructLiteral(ASTStructLiteralExpression),
+    FieldAccess(ASTFieldAccessExpression),
+    FieldAssignment(ASTFieldAssignmentExpression),
+    ArrayLiteral(ASTArrayLiteralExpression),
+    Index(ASTIndexExpression),
+    IndexAssignment(ASTIndexAssignmentExpression),
+    AddressOf(ASTAddressOfExpression),
+    Dereference(ASTDereferenceExpression),
+    DereferenceAssignment(ASTDereferenceAssignmentExpression),
     Error(TextSpan),
 }
 
 #[derive(Clone, PartialEq)]
 pub struct ASTExpression {
     kind: ASTExpressionKind,
+    span: TextSpan,
 }
 
 impl ASTExpression {
-    fn new(kind: ASTExpressionKind) -> Self {
-        Self { kind }
+    fn new(kind: ASTExpressionKind, span: TextSpan) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn span(&self) -> &TextSpan {
+        &self.span
     }
+
     fn error(span: TextSpan) -> Self {
-        Self {
-            kind: ASTExpressionKind::Error(span),
-        }
+        Self::new(ASTExpressionKind::Error(span.clone()), span)
     }
 
-    fn integer(i: i64) -> Self {
-        Self {
-            kind: ASTExpressionKind::IntegerLiteral(i),
-        }
+    fn integer(i: i128, span: TextSpan) -> Self {
+        Self::new(ASTExpressionKind::IntegerLiteral(i), span)
     }
-    fn float(f: f64) -> Self {
-        Self {
-            kind: ASTExpressionKind::FloatingLiteral(f),
-        }
+    fn float(f: f64, span: TextSpan) -> Self {
+        Self::new(ASTExpressionKind::FloatingLiteral(f), span)
+    }
+    fn string(s: String, span: TextSpan) -> Self {
+        Self::new(ASTExpressionKind::String(s), span)
+    }
+    fn character(c: char, span: TextSpan) -> Self {
+        Self::new(ASTExpressionKind::Character(c), span)
+    }
+    fn boolean(b: bool, span: TextSpan) -> Self {
+        Self::new(ASTExpressionKind::Boolean(b), span)
+    }
+    fn null(span: TextSpan) -> Self {
+        Self::new(ASTExpressionKind::Null, span)
     }
 
     fn identifier(token: Token) -> Self {
-        Self {
-            kind: ASTExpressionKind::Variable(ASTVariableExpression { identifier: token }),
-        }
+        let span = token.span.clone();
+        Self::new(
+            ASTExpressionKind::Variable(ASTVariableExpression { identifier: token }),
+            span,
+        )
     }
 
     fn assignment(token: Token, expr: ASTExpression) -> Self {
-        Self {
-            kind: ASTExpressionKind::Assignment(ASTAssignmentExpression {
+        let span = TextSpan::combine(vec![token.span.clone(), expr.span.clone()]);
+        Self::new(
+            ASTExpressionKind::Assignment(ASTAssignmentExpression {
                 identifier: token,
                 expr: Box::new(expr),
             }),
-        }
+            span,
+        )
     }
 
     fn unary(operator: ASTUnaryOperator, expr: ASTExpression) -> Self {
-        Self {
-            kind: ASTExpressionKind::Unary(ASTUnaryExpression {
+        let span = TextSpan::combine(vec![operator.token.span.clone(), expr.span.clone()]);
+        Self::new(
+            ASTExpressionKind::Unary(ASTUnaryExpression {
                 operator,
                 expr: Box::new(expr),
             }),
-        }
+            span,
+        )
     }
 
     fn binary(operator: ASTBinaryOperator, left: ASTExpression, right: ASTExpression) -> Self {
-        Self {
-            kind: ASTExpressionKind::Binary(ASTBinaryExpression {
+        let span = TextSpan::combine(vec![
+            left.span.clone(),
+            operator.token.span.clone(),
+            right.span.clone(),
+        ]);
+        Self::new(
+            ASTExpressionKind::Binary(ASTBinaryExpression {
                 operator,
                 left: Box::new(left),
                 right: Box::new(right),
             }),
-        }
+            span,
+        )
     }
 
-    fn parenthesized(expr: ASTExpression) -> Self {
-        Self {
-            kind: ASTExpressionKind::Parenthesized(ASTParenthesizedExpression {
+    fn parenthesized(left_paren: &Token, expr: ASTExpression, right_paren: &Token) -> Self {
+        let span = TextSpan::combine(vec![
+            left_paren.span.clone(),
+            expr.span.clone(),
+            right_paren.span.clone(),
+        ]);
+        Self::new(
+            ASTExpressionKind::Parenthesized(ASTParenthesizedExpression {
                 expr: Box::new(expr),
             }),
-        }
+            span,
+        )
     }
 
-    fn function_call(identifier: Token, arguments: Vec<ASTExpression>) -> Self {
-        Self {
-            kind: ASTExpressionKind::FunctionCall(ASTFunctionCallExpression {
+    fn function_call(
+        module: Option<Token>,
+        identifier: Token,
+        arguments: Vec<ASTExpression>,
+        right_paren: &Token,
+    ) -> Self {
+        let start = module.as_ref().unwrap_or(&identifier).span.clone();
+        let span = TextSpan::combine(vec![start, right_paren.span.clone()]);
+        Self::new(
+            ASTExpressionKind::FunctionCall(ASTFunctionCallExpression {
+                module,
                 identifier,
                 arguments,
             }),
+            span,
+        )
+    }
+
+    fn method_call(
+        object: ASTExpression,
+        method: Token,
+        arguments: Vec<ASTExpression>,
+        right_paren: &Token,
+    ) -> Self {
+        let span = TextSpan::combine(vec![object.span.clone(), right_paren.span.clone()]);
+        Self::new(
+            ASTExpressionKind::MethodCall(ASTMethodCallExpression {
+                object: Box::new(object),
+                method,
+                arguments,
+            }),
+            span,
+        )
+    }
+
+    fn struct_literal(
+        identifier: Token,
+        fields: Vec<ASTStructLiteralField>,
+        right_brace: &Token,
+    ) -> Self {
+        let span = TextSpan::combine(vec![identifier.span.clone(), right_brace.span.clone()]);
+        Self::new(
+            ASTExpressionKind::StructLiteral(ASTStructLiteralExpression { identifier, fields }),
+            span,
+        )
+    }
+
+    fn field_access(object: ASTExpression, field: Token) -> Self {
+        let span = TextSpan::combine(vec![object.span.clone(), field.span.clone()]);
+        Self::new(
+            ASTExpressionKind::FieldAccess(ASTFieldAccessExpression {
+                object: Box::new(object),
+                field,
+            }),
+            span,
+        )
+    }
+
+    fn address_of(operator: Token, expr: ASTExpression) -> Self {
+        let span = TextSpan::combine(vec![operator.span.clone(), expr.span.clone()]);
+        Self::new(
+            ASTExpressionKind::AddressOf(ASTAddressOfExpression {
+                operator,
+                expr: Box::new(expr),
+            }),
+            span,
+        )
+    }
+
+    fn dereference(operator: Token, expr: ASTExpression) -> Self {
+        let span = TextSpan::combine(vec![operator.span.clone(), expr.span.clone()]);
+        Self::new(
+            ASTExpressionKind::Dereference(ASTDereferenceExpression {
+                operator,
+                expr: Box::new(expr),
+            }),
+            span,
+        )
+    }
+
+    fn dereference_assignment(target: ASTDereferenceExpression, expr: ASTExpression) -> Self {
+        let span = TextSpan::combine(vec![
+            target.operator.span.clone(),
+            target.expr.span.clone(),
+            expr.span.clone(),
+        ]);
+        Self::new(
+            ASTExpressionKind::DereferenceAssignment(ASTDereferenceAssignmentExpression {
+                target,
+                expr: Box::new(expr),
+            }),
+            span,
+        )
+    }
+
+    fn array_literal(
+        left_bracket: &Token,
+        elements: Vec<ASTExpression>,
+        right_bracket: &Token,
+    ) -> Self {
+        let span = TextSpan::combine(vec![left_bracket.span.clone(), right_bracket.span.clone()]);
+        Self::new(
+            ASTExpressionKind::ArrayLiteral(ASTArrayLiteralExpression { elements }),
+            span,
+        )
+    }
+
+    fn index(object: ASTExpression, index: ASTExpression, right_bracket: Token) -> Self {
+        let span = TextSpan::combine(vec![object.span.clone(), right_bracket.span.clone()]);
+        Self::new(
+            ASTExpressionKind::Index(ASTIndexExpression {
+                object: Box::new(object),
+                index: Box::new(index),
+                right_bracket,
+            }),
+            span,
+        )
+    }
+
+    fn index_assignment(target: ASTIndexExpression, expr: ASTExpression) -> Self {
+        let span = TextSpan::combine(vec![target.span(), expr.span.clone()]);
+        Self::new(
+            ASTExpressionKind::IndexAssignment(ASTIndexAssignmentExpression {
+                target,
+                expr: Box::new(expr),
+            }),
+            span,
+        )
+    }
+
+    // Name of the variable a place is stored in, `p` for `p.a[1].b`
+    fn root_variable(&self) -> Option<&Token> {
+        match &self.kind {
+            ASTExpressionKind::Variable(variable) => Some(&variable.identifier),
+            ASTExpressionKind::FieldAccess(access) => access.object.root_variable(),
+            ASTExpressionKind::Index(index) => index.object.root_variable(),
+            _ => None,
         }
     }
+
+    fn field_assignment(target: ASTFieldAccessExpression, expr: ASTExpression) -> Self {
+        let span = TextSpan::combine(vec![
+            target.object.span.clone(),
+            target.field.span.clone(),
+            expr.span.clone(),
+        ]);
+        Self::new(
+            ASTExpressionKind::FieldAssignment(ASTFieldAssignmentExpression {
+                target,
+                expr: Box::new(expr),
+            }),
+            span,
+        )
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -431,21 +892,21 @@ pub struct ASTBinaryOperator {
 impl ASTBinaryOperator {
     fn precedence(&self) -> u8 {
         match self.kind {
-            ASTBinaryOperatorKind::Plus => 5,
-            ASTBinaryOperatorKind::Minus => 5,
-            ASTBinaryOperatorKind::Multiply => 6,
-            ASTBinaryOperatorKind::Divide => 6,
-            ASTBinaryOperatorKind::EqualTo => 1,
-            ASTBinaryOperatorKind::NotEqualTo => 1,
-            ASTBinaryOperatorKind::LogicAND => 1,
+            ASTBinaryOperatorKind::Plus => 7,
+            ASTBinaryOperatorKind::Minus => 7,
+            ASTBinaryOperatorKind::Multiply => 8,
+            ASTBinaryOperatorKind::Divide => 8,
+            ASTBinaryOperatorKind::EqualTo => 3,
+            ASTBinaryOperatorKind::NotEqualTo => 3,
+            ASTBinaryOperatorKind::LogicAND => 2,
             ASTBinaryOperatorKind::LogicOR => 1,
-            ASTBinaryOperatorKind::GreaterThan => 1,
-            ASTBinaryOperatorKind::GreaterThanOrEqual => 1,
-            ASTBinaryOperatorKind::LessThan => 1,
-            ASTBinaryOperatorKind::LessThanOrEqual => 1,
-            ASTBinaryOperatorKind::BitwiseOR => 1,
-            ASTBinaryOperatorKind::BitwiseAND => 1,
-            ASTBinaryOperatorKind::BitwiseXOR => 1,
+            ASTBinaryOperatorKind::GreaterThan => 3,
+            ASTBinaryOperatorKind::GreaterThanOrEqual => 3,
+            ASTBinaryOperatorKind::LessThan => 3,
+            ASTBinaryOperatorKind::LessThanOrEqual => 3,
+            ASTBinaryOperatorKind::BitwiseOR => 4,
+            ASTBinaryOperatorKind::BitwiseAND => 6,
+            ASTBinaryOperatorKind::BitwiseXOR => 5,
         }
     }
 }
@@ -481,6 +942,8 @@ impl ASTVariableExpression {
 
 #[derive(Clone, PartialEq)]
 pub struct ASTFunctionCallExpression {
+    // imported module of a qualified call like `math.max(a, b)`
+    module: Option<Token>,
     identifier: Token,
     arguments: Vec<ASTExpression>,
 }
@@ -489,6 +952,102 @@ impl ASTFunctionCallExpression {
     pub fn identifier(&self) -> &str {
         &self.identifier.span.literal
     }
+
+    pub fn module(&self) -> Option<&str> {
+        self.module
+            .as_ref()
+            .map(|module| module.span.literal.as_str())
+    }
+
+    // Name as written at the call, like `math.max`
+    pub fn qualified_name(&self) -> String {
+        match self.module() {
+            Some(module) => format!("{}.{}", module, self.identifier()),
+            None => self.identifier().to_string(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTMethodCallExpression {
+    object: Box<ASTExpression>,
+    method: Token,
+    arguments: Vec<ASTExpression>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTStructLiteralField {
+    identifier: Token,
+    expr: ASTExpression,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTStructLiteralExpression {
+    identifier: Token,
+    fields: Vec<ASTStructLiteralField>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTFieldAccessExpression {
+    object: Box<ASTExpression>,
+    field: Token,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTFieldAssignmentExpression {
+    target: ASTFieldAccessExpression,
+    expr: Box<ASTExpression>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTArrayLiteralExpression {
+    elements: Vec<ASTExpression>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTIndexExpression {
+    object: Box<ASTExpression>,
+    index: Box<ASTExpression>,
+    right_bracket: Token,
+}
+
+impl ASTIndexExpression {
+    fn span(&self) -> TextSpan {
+        TextSpan::combine(vec![
+            self.object.span.clone(),
+            self.right_bracket.span.clone(),
+        ])
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTIndexAssignmentExpression {
+    target: ASTIndexExpression,
+    expr: Box<ASTExpression>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTAddressOfExpression {
+    operator: Token,
+    expr: Box<ASTExpression>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTDereferenceExpression {
+    operator: Token,
+    expr: Box<ASTExpression>,
+}
+
+impl ASTDereferenceExpression {
+    fn span(&self) -> TextSpan {
+        TextSpan::combine(vec![self.operator.span.clone(), self.expr.span.clone()])
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ASTDereferenceAssignmentExpression {
+    target: ASTDereferenceExpression,
+    expr: Box<ASTExpression>,
 }
 
 #[cfg(test)]
@@ -502,7 +1061,11 @@ mod test {
     #[derive(Debug, PartialEq)]
     enum TestASTNode {
         Floating(f64),
-        Integer(i64),
+        Integer(i128),
+        String(String),
+        Character(char),
+        Boolean(bool),
+        Null,
         Variable(String),
         Let(String, TokenKind),
         Var(String, TokenKind),
@@ -510,12 +1073,25 @@ mod test {
         If,
         While,
         For(String),
+        Break,
+        Continue,
         Return,
         FuncDecl(Vec<(String, TokenKind)>),
         BinaryExpr(TokenKind),
         UnaryExpr(TokenKind),
         ParenExpr,
         FunctionCall(String),
+        MethodCall(String),
+        Struct(Vec<(String, TokenKind)>),
+        StructLiteral(String),
+        FieldAccess(String),
+        FieldAssign(String),
+        AddressOf,
+        Dereference,
+        DereferenceAssign,
+        ArrayLiteral(usize),
+        Index,
+        IndexAssign,
     }
     struct ASTVerifier {
         actual: Vec<TestASTNode>,
@@ -523,6 +1099,17 @@ mod test {
     }
 
     impl ASTVerifier {
+        // kind of the primitive type or struct name an annotation refers to
+        fn type_kind(data_type: &super::ASTType) -> TokenKind {
+            match &data_type.kind {
+                super::ASTTypeKind::Named(token) => token.kind.clone(),
+                super::ASTTypeKind::Pointer(data_type)
+                | super::ASTTypeKind::Optional(data_type)
+                | super::ASTTypeKind::Array(data_type, _) => Self::type_kind(data_type),
+                super::ASTTypeKind::Error => TokenKind::Bad,
+            }
+        }
+
         pub fn new(input: &str, expected_ast: Vec<TestASTNode>) -> Self {
             let compilation_unit = CompilationUnit::compile(input);
             assert!(compilation_unit.is_ok());
@@ -531,10 +1118,9 @@ mod test {
                 expected: expected_ast,
             };
 
-            match compilation_unit {
-                Ok(c) => verifier.flatten_ast(&c.ast),
-                Err(_) => (),
-            };
+            if let Ok(c) = compilation_unit {
+                verifier.flatten_ast(&c.entry_module().ast);
+            }
             verifier
         }
 
@@ -570,7 +1156,7 @@ mod test {
         fn visit_let_statement(&mut self, statement: &super::ASTLetStatement) {
             self.actual.push(TestASTNode::Let(
                 statement.identifier.span.literal.clone(),
-                statement.data_type.kind.clone(),
+                Self::type_kind(&statement.data_type),
             ));
             self.visit_expression(&statement.initializer);
         }
@@ -578,7 +1164,7 @@ mod test {
         fn visit_var_statement(&mut self, statement: &super::ASTVarStatement) {
             self.actual.push(TestASTNode::Var(
                 statement.identifier.span.literal.clone(),
-                statement.data_type.kind.clone(),
+                Self::type_kind(&statement.data_type),
             ));
             self.visit_expression(&statement.initializer);
         }
@@ -589,7 +1175,7 @@ mod test {
             if let super::ASTStatementKind::Compound(body) = &statement.then_branch.kind {
                 self.visit_compound_statement(body);
             }
-            if let Some(else_branch) = statement.else_branch {
+            if let Some(else_branch) = &statement.else_branch {
                 self.visit_statement(&else_branch.else_branch);
             }
         }
@@ -598,8 +1184,13 @@ mod test {
             self.actual.push(TestASTNode::For(
                 statement.loop_variable.span.literal.clone(),
             ));
-            self.visit_expression(&statement.range.0);
-            self.visit_expression(&statement.range.1);
+            match &statement.iterable {
+                super::ASTForIterable::Range(start, end) => {
+                    self.visit_expression(start);
+                    self.visit_expression(end);
+                }
+                super::ASTForIterable::Array(array) => self.visit_expression(array),
+            }
             if let super::ASTStatementKind::Compound(body) = &statement.body.kind {
                 self.visit_compound_statement(body);
             }
@@ -613,16 +1204,24 @@ mod test {
             }
         }
 
+        fn visit_break_statement(&mut self, _statement: &super::ASTBreakStatement) {
+            self.actual.push(TestASTNode::Break);
+        }
+
+        fn visit_continue_statement(&mut self, _statement: &super::ASTContinueStatement) {
+            self.actual.push(TestASTNode::Continue);
+        }
+
         fn visit_funtion_statement(&mut self, function: &super::ASTFunctionStatement) {
             let mut args: Vec<(String, TokenKind)> = Vec::new();
             args.push((
                 function.identifier.span.literal.clone(),
-                function.return_type.kind.clone(),
+                Self::type_kind(&function.return_type),
             ));
             for arg in function.arguments.iter() {
                 args.push((
                     arg.identifier.span.literal.clone(),
-                    arg.data_type.kind.clone(),
+                    Self::type_kind(&arg.data_type),
                 ));
             }
 
@@ -633,6 +1232,25 @@ mod test {
             }
         }
 
+        fn visit_struct_statement(&mut self, statement: &super::ASTStructStatement) {
+            let mut fields: Vec<(String, TokenKind)> = Vec::new();
+            fields.push((
+                statement.identifier.span.literal.clone(),
+                statement.identifier.kind.clone(),
+            ));
+            for field in statement.fields.iter() {
+                fields.push((
+                    field.identifier.span.literal.clone(),
+                    Self::type_kind(&field.data_type),
+                ));
+            }
+
+            self.actual.push(TestASTNode::Struct(fields));
+            for method in statement.methods.iter() {
+                self.visit_funtion_statement(method);
+            }
+        }
+
         fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {
             self.actual
                 .push(TestASTNode::Assign(expr.identifier.span.literal.clone()));
@@ -640,9 +1258,17 @@ mod test {
         }
 
         fn visit_function_call_expression(&mut self, expr: &super::ASTFunctionCallExpression) {
-            self.actual.push(TestASTNode::FunctionCall(
-                expr.identifier.span.literal.clone(),
-            ));
+            self.actual
+                .push(TestASTNode::FunctionCall(expr.qualified_name()));
+            for arg in expr.arguments.iter() {
+                self.visit_expression(arg);
+            }
+        }
+
+        fn visit_method_call_expression(&mut self, expr: &super::ASTMethodCallExpression) {
+            self.actual
+                .push(TestASTNode::MethodCall(expr.method.span.literal.clone()));
+            self.visit_expression(&expr.object);
             for arg in expr.arguments.iter() {
                 self.visit_expression(arg);
             }
@@ -653,6 +1279,75 @@ mod test {
                 .push(TestASTNode::Variable(expr.identifier.span.literal.clone()));
         }
 
+        fn visit_struct_literal_expression(&mut self, expr: &super::ASTStructLiteralExpression) {
+            self.actual.push(TestASTNode::StructLiteral(
+                expr.identifier.span.literal.clone(),
+            ));
+            for field in expr.fields.iter() {
+                self.visit_expression(&field.expr);
+            }
+        }
+
+        fn visit_field_access_expression(&mut self, expr: &super::ASTFieldAccessExpression) {
+            self.actual
+                .push(TestASTNode::FieldAccess(expr.field.span.literal.clone()));
+            self.visit_expression(&expr.object);
+        }
+
+        fn visit_field_assignment_expression(
+            &mut self,
+            expr: &super::ASTFieldAssignmentExpression,
+        ) {
+            self.actual.push(TestASTNode::FieldAssign(
+                expr.target.field.span.literal.clone(),
+            ));
+            self.visit_expression(&expr.target.object);
+            self.visit_expression(&expr.expr);
+        }
+
+        fn visit_address_of_expression(&mut self, expr: &super::ASTAddressOfExpression) {
+            self.actual.push(TestASTNode::AddressOf);
+            self.visit_expression(&expr.expr);
+        }
+
+        fn visit_dereference_expression(&mut self, expr: &super::ASTDereferenceExpression) {
+            self.actual.push(TestASTNode::Dereference);
+            self.visit_expression(&expr.expr);
+        }
+
+        fn visit_dereference_assignment_expression(
+            &mut self,
+            expr: &super::ASTDereferenceAssignmentExpression,
+        ) {
+            self.actual.push(TestASTNode::DereferenceAssign);
+            self.visit_expression(&expr.target.expr);
+            self.visit_expression(&expr.expr);
+        }
+
+        fn visit_array_literal_expression(&mut self, expr: &super::ASTArrayLiteralExpression) {
+            self.actual
+                .push(TestASTNode::ArrayLiteral(expr.elements.len()));
+            for element in expr.elements.iter() {
+                self.visit_expression(element);
+            }
+        }
+
+        fn visit_index_expression(&mut self, expr: &super::ASTIndexExpression) {
+            self.actual.push(TestASTNode::Index);
+            self.visit_expression(&expr.object);
+            self.visit_expression(&expr.index);
+        }
+
+        fn visit_index_assignment_expression(
+            &mut self,
+            expr: &super::ASTIndexAssignmentExpression,
+        ) {
+            self.actual.push(TestASTNode::IndexAssign);
+            self.visit_expression(&expr.target.object);
+            self.visit_expression(&expr.target.index);
+            self.visit_expression(&expr.expr);
+        }
+
         fn visit_unary_expression(&mut self, expr: &super::ASTUnaryExpression) {
             self.actual
                 .push(TestASTNode::UnaryExpr(expr.operator.token.kind.clone()));
@@ -670,14 +1365,28 @@ mod test {
             self.visit_expression(&expr.expr);
         }
 
-        fn visit_binary_operator(&mut self, op: &super::ASTBinaryOperator) {}
-
-        fn visit_integer(&mut self, integer: &i64) {
-            self.actual.push(TestASTNode::Integer(integer.clone()));
+        fn visit_integer(&mut self, integer: &i128) {
+            self.actual.push(TestASTNode::Integer(*integer));
         }
 
         fn visit_float(&mut self, float: &f64) {
-            self.actual.push(TestASTNode::Floating(float.clone()));
+            self.actual.push(TestASTNode::Floating(*float));
+        }
+
+        fn visit_string(&mut self, string: &str) {
+            self.actual.push(TestASTNode::String(string.to_string()));
+        }
+
+        fn visit_character(&mut self, character: &char) {
+            self.actual.push(TestASTNode::Character(*character));
+        }
+
+        fn visit_boolean(&mut self, boolean: &bool) {
+            self.actual.push(TestASTNode::Boolean(*boolean));
+        }
+
+        fn visit_null(&mut self) {
+            self.actual.push(TestASTNode::Null);
         }
     }
 
@@ -712,6 +1421,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn should_parse_simple_binary_addition_statement() {
         let input = "10 + 3.1415;";
         let expected_ast = vec![
@@ -725,6 +1435,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn should_parse_complex_binary_statement() {
         let input = "let a: f64 = (7.2 - 10) / 2 + 3.1415 * 8;";
         let expected_ast = vec![
@@ -747,12 +1458,12 @@ mod test {
 
     #[test]
     fn should_parse_function_declaration() {
-        let input = "func f(a: u8, b: i16, c: u64) -> u64 { return a + b + c; }";
+        let input = "func f(a: u8, b: u16, c: u64) -> u64 { return a + b + c; }";
         let expected_ast = vec![
             TestASTNode::FuncDecl(vec![
                 ("f".to_string(), TokenKind::U64), // function name
                 ("a".to_string(), TokenKind::U8),
-                ("b".to_string(), TokenKind::I16),
+                ("b".to_string(), TokenKind::U16),
                 ("c".to_string(), TokenKind::U64),
             ]),
             TestASTNode::Return,
@@ -770,14 +1481,14 @@ mod test {
     #[test]
     fn should_parse_function_call() {
         let input = "\
-        func f(a: u8, b: i16, c: u64) -> u64 { return a + b + c; }
+        func f(a: u8, b: u16, c: u64) -> u64 { return a + b + c; }
         f(1, 2, 6);
         ";
         let expected_ast = vec![
             TestASTNode::FuncDecl(vec![
                 ("f".to_string(), TokenKind::U64), // function name
                 ("a".to_string(), TokenKind::U8),
-                ("b".to_string(), TokenKind::I16),
+                ("b".to_string(), TokenKind::U16),
                 ("c".to_string(), TokenKind::U64),
             ]),
             TestASTNode::Return,
@@ -799,13 +1510,13 @@ mod test {
     #[test]
     fn should_parse_for_loop() {
         let input = "\
-        var a: u32 = 0;
+        var a: i64 = 0;
         for i in 0..10 {
             a += i;
         }
         ";
         let expected_ast = vec![
-            TestASTNode::Var("a".to_string(), TokenKind::U32),
+            TestASTNode::Var("a".to_string(), TokenKind::I64),
             TestASTNode::Integer(0),
             TestASTNode::For("i".to_string()),
             TestASTNode::Integer(0),
@@ -844,4 +1555,174 @@ mod test {
         let verifier = ASTVerifier::new(input, expected_ast);
         verifier.verify();
     }
+
+    #[test]
+    fn should_parse_break_and_continue() {
+        let input = "\
+        var a: i32 = 5;
+        while a > 0 {
+            a -= 1;
+            if a == 3 {
+                continue;
+            }
+            break;
+        }
+        ";
+        let expected_ast = vec![
+            TestASTNode::Var("a".to_string(), TokenKind::I32),
+            TestASTNode::Integer(5),
+            TestASTNode::While,
+            TestASTNode::BinaryExpr(TokenKind::RightAngleBracket),
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Integer(0),
+            TestASTNode::Assign("a".to_string()),
+            TestASTNode::BinaryExpr(TokenKind::MinusEqual),
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Integer(1),
+            TestASTNode::If,
+            TestASTNode::BinaryExpr(TokenKind::EqualEqual),
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Integer(3),
+            TestASTNode::Continue,
+            TestASTNode::Break,
+        ];
+
+        let verifier = ASTVerifier::new(input, expected_ast);
+        verifier.verify();
+    }
+
+    #[test]
+    fn should_parse_boolean_expression_with_precedence() {
+        let input = "let a: bool = 1 + 2 < 4 && true || false;";
+        let expected_ast = vec![
+            TestASTNode::Let("a".to_string(), TokenKind::Bool),
+            TestASTNode::BinaryExpr(TokenKind::PipePipe),
+            TestASTNode::BinaryExpr(TokenKind::AmpersandAmpersand),
+            TestASTNode::BinaryExpr(TokenKind::LeftAngleBracket),
+            TestASTNode::BinaryExpr(TokenKind::Plus),
+            TestASTNode::Integer(1),
+            TestASTNode::Integer(2),
+            TestASTNode::Integer(4),
+            TestASTNode::Boolean(true),
+            TestASTNode::Boolean(false),
+        ];
+
+        let verifier = ASTVerifier::new(input, expected_ast);
+        verifier.verify();
+    }
+
+    #[test]
+    fn should_parse_struct_declaration_literal_and_field_access() {
+        let input = "\
+        struct Point { x: i32, y: i32, }
+        var p: Point = Point { x: 1, y: 2 };
+        p.x = p.y;";
+        let expected_ast = vec![
+            TestASTNode::Struct(vec![
+                ("Point".to_string(), TokenKind::Identifier),
+                ("x".to_string(), TokenKind::I32),
+                ("y".to_string(), TokenKind::I32),
+            ]),
+            TestASTNode::Var("p".to_string(), TokenKind::Identifier),
+            TestASTNode::StructLiteral("Point".to_string()),
+            TestASTNode::Integer(1),
+            TestASTNode::Integer(2),
+            TestASTNode::FieldAssign("x".to_string()),
+            TestASTNode::Variable("p".to_string()),
+            TestASTNode::FieldAccess("y".to_string()),
+            TestASTNode::Variable("p".to_string()),
+        ];
+
+        let verifier = ASTVerifier::new(input, expected_ast);
+        verifier.verify();
+    }
+
+    #[test]
+    fn should_parse_methods_and_method_calls() {
+        let input = "\
+        struct Point {
+            x: i32,
+            func scaled(self, factor: i32) -> i32 { return self.x * factor; }
+        }
+        Point { x: 2 }.scaled(3);";
+        let expected_ast = vec![
+            TestASTNode::Struct(vec![
+                ("Point".to_string(), TokenKind::Identifier),
+                ("x".to_string(), TokenKind::I32),
+            ]),
+            TestASTNode::FuncDecl(vec![
+                ("scaled".to_string(), TokenKind::I32),
+                ("factor".to_string(), TokenKind::I32),
+            ]),
+            TestASTNode::Return,
+            TestASTNode::BinaryExpr(TokenKind::Astrisk),
+            TestASTNode::FieldAccess("x".to_string()),
+            TestASTNode::Variable("self".to_string()),
+            TestASTNode::Variable("factor".to_string()),
+            TestASTNode::MethodCall("scaled".to_string()),
+            TestASTNode::StructLiteral("Point".to_string()),
+            TestASTNode::Integer(2),
+            TestASTNode::Integer(3),
+        ];
+
+        let verifier = ASTVerifier::new(input, expected_ast);
+        verifier.verify();
+    }
+
+    #[test]
+    fn should_parse_pointer_expressions() {
+        let input = "\
+        var a: i32 = 1;
+        let p: *i32 = &a;
+        *p = 2 * *p;
+        p != null;";
+        let expected_ast = vec![
+            TestASTNode::Var("a".to_string(), TokenKind::I32),
+            TestASTNode::Integer(1),
+            TestASTNode::Let("p".to_string(), TokenKind::I32),
+            TestASTNode::AddressOf,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::DereferenceAssign,
+            TestASTNode::Variable("p".to_string()),
+            TestASTNode::BinaryExpr(TokenKind::Astrisk),
+            TestASTNode::Integer(2),
+            TestASTNode::Dereference,
+            TestASTNode::Variable("p".to_string()),
+            TestASTNode::BinaryExpr(TokenKind::ExclemationMarkEqual),
+            TestASTNode::Variable("p".to_string()),
+            TestASTNode::Null,
+        ];
+
+        let verifier = ASTVerifier::new(input, expected_ast);
+        verifier.verify();
+    }
+
+    #[test]
+    fn should_parse_arrays_and_indexing() {
+        let input = "\
+        var a: [i32; 3] = [1, 2, 3,];
+        a[0] = a[a[1]];
+        for x in a { x; }";
+        let expected_ast = vec![
+            TestASTNode::Var("a".to_string(), TokenKind::I32),
+            TestASTNode::ArrayLiteral(3),
+            TestASTNode::Integer(1),
+            TestASTNode::Integer(2),
+            TestASTNode::Integer(3),
+            TestASTNode::IndexAssign,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Integer(0),
+            TestASTNode::Index,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Index,
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Integer(1),
+            TestASTNode::For("x".to_string()),
+            TestASTNode::Variable("a".to_string()),
+            TestASTNode::Variable("x".to_string()),
+        ];
+
+        let verifier = ASTVerifier::new(input, expected_ast);
+        verifier.verify();
+    }
 }
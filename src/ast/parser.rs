@@ -9,8 +9,9 @@ use std::{
 
 use super::lexer::TextSpan;
 use super::{
-    ASTBinaryOperator, ASTBinaryOperatorKind, ASTElseStatement, ASTUnaryOperator,
-    ASTUnaryOperatorKind, FunctionArgumentDeclaration,
+    ASTBinaryOperator, ASTBinaryOperatorKind, ASTElseStatement, ASTExpressionKind, ASTForIterable,
+    ASTFunctionStatement, ASTStructLiteralField, ASTType, ASTUnaryOperator, ASTUnaryOperatorKind,
+    FunctionArgumentDeclaration, StructFieldDeclaration,
 };
 
 struct Cursor {
@@ -38,6 +39,15 @@ pub struct Parser {
     tokens: Vec<Token>,
     cursor: Cursor,
     diagnostics_colletion: DiagnosticsColletionCell,
+    // set after a syntax error until the parser synchronized at the next statement
+    panic_mode: Cell<bool>,
+    last_error_position: Cell<Option<usize>>,
+    // `Name {` starts a struct literal except where a block follows the expression
+    struct_literals_allowed: Cell<bool>,
+    // `module` and `import` statements are only allowed before any other statement
+    module_header: bool,
+    // modules imported so far, `name.func()` calls a function of one of them
+    imports: Vec<String>,
 }
 
 impl Parser {
@@ -48,35 +58,33 @@ impl Parser {
         Self {
             tokens: tokens
                 .iter()
-                .filter(|token| match token.kind {
-                    TokenKind::Whitespace => false,
-                    TokenKind::SingleLineComment(_) => false,
-                    TokenKind::MultiLineComment(_) => false,
-                    _ => true,
+                .filter(|token| {
+                    !matches!(
+                        token.kind,
+                        TokenKind::Whitespace
+                            | TokenKind::SingleLineComment(_)
+                            | TokenKind::MultiLineComment(_)
+                    )
                 })
-                .map(|token| token.clone())
+                .cloned()
                 .collect(),
             cursor: Cursor::new(),
             diagnostics_colletion,
+            panic_mode: Cell::new(false),
+            last_error_position: Cell::new(None),
+            struct_literals_allowed: Cell::new(true),
+            module_header: true,
+            imports: Vec::new(),
         }
     }
 
     pub fn from_input(input: String, diagnostics_colletion: DiagnosticsColletionCell) -> Self {
-        let mut lexer = Lexer::new(input);
+        let mut lexer = Lexer::new(input, Rc::clone(&diagnostics_colletion));
         let mut tokens = Vec::new();
         while let Some(token) = lexer.next_token() {
-            match token.kind {
-                TokenKind::Whitespace => (),
-                TokenKind::SingleLineComment(_) => (),
-                TokenKind::MultiLineComment(_) => (),
-                _ => tokens.push(token),
-            };
-        }
-        Self {
-            tokens,
-            cursor: Cursor::new(),
-            diagnostics_colletion,
+            tokens.push(token);
         }
+        Self::new(tokens, diagnostics_colletion)
     }
 
     pub fn next_statement(&mut self) -> Option<ASTStatement> {
@@ -87,21 +95,93 @@ impl Parser {
     }
 
     fn parse_statement(&mut self) -> ASTStatement {
-        match self.current_token().kind {
+        let start = self.cursor.get_value();
+        let first_token = self.current_token().span.clone();
+        if !matches!(
+            self.current_token().kind,
+            TokenKind::Module | TokenKind::Import
+        ) {
+            self.module_header = false;
+        }
+        let mut statement = match self.current_token().kind {
+            TokenKind::Module => self.parse_module_statement(),
+            TokenKind::Import => self.parse_import_statement(),
             TokenKind::Let => self.parse_let_statement(),
             TokenKind::Var => self.parse_var_statement(),
             TokenKind::Return => self.parse_return_statement(),
             TokenKind::Func => self.parse_function_statement(),
+            TokenKind::Struct => self.parse_struct_statement(),
             TokenKind::If => self.parse_if_statement(),
             TokenKind::While => self.parse_while_loop_statement(),
             TokenKind::For => self.parse_for_loop_statement(),
+            TokenKind::Break => self.parse_break_statement(),
+            TokenKind::Continue => self.parse_continue_statement(),
             TokenKind::LeftBrace => self.parse_compound_statement(),
             TokenKind::SingleLineComment(_) => todo!("Decide if comments need to be in AST"),
             TokenKind::MultiLineComment(_) => todo!("Decide if comments need to be in AST"),
             _ => self.parse_expression_statement(),
+        };
+        if self.panic_mode.get() {
+            self.synchronize();
+        }
+        // a stray `}` would be found again by the next statement
+        if self.cursor.get_value() == start {
+            self.consume();
+        }
+        statement.span = TextSpan::combine(vec![first_token, self.peek(-1).span.clone()]);
+        statement
+    }
+
+    // Skips the rest of a statement that contains a syntax error. Stops after a `;`
+    // or in front of a `}` or a keyword that starts the next statement.
+    fn synchronize(&self) {
+        self.panic_mode.set(false);
+        // the broken statement already ended at its `;` or `}`
+        if matches!(
+            self.peek(-1).kind,
+            TokenKind::SemiColon | TokenKind::RightBrace
+        ) {
+            return;
+        }
+        loop {
+            match self.current_token().kind {
+                TokenKind::Eof
+                | TokenKind::RightBrace
+                | TokenKind::Let
+                | TokenKind::Var
+                | TokenKind::Func
+                | TokenKind::Struct
+                | TokenKind::If
+                | TokenKind::While
+                | TokenKind::For
+                | TokenKind::Return
+                | TokenKind::Break
+                | TokenKind::Continue
+                | TokenKind::Module
+                | TokenKind::Import => break,
+                TokenKind::SemiColon => {
+                    self.consume();
+                    break;
+                }
+                _ => {
+                    self.consume();
+                }
+            }
         }
     }
 
+    // Only the first error of a statement is reported, the following ones are
+    // most likely caused by it
+    fn report_error(&self, report: impl FnOnce(&mut DiagnosticsColletion)) {
+        let position = self.cursor.get_value();
+        if self.panic_mode.get() || self.last_error_position.get() == Some(position) {
+            return;
+        }
+        self.panic_mode.set(true);
+        self.last_error_position.set(Some(position));
+        report(&mut self.diagnostics_colletion.borrow_mut());
+    }
+
     fn current_token(&self) -> &Token {
         self.peek(0)
     }
@@ -119,28 +199,105 @@ impl Parser {
         self.peek(-1)
     }
 
+    // A missing token is not consumed, so the found token can still start the
+    // next statement
     fn consume_expected(&self, expected: TokenKind) -> &Token {
-        let token = self.consume();
+        let token = self.current_token();
         if token.kind != expected {
-            self.diagnostics_colletion
-                .borrow_mut()
-                .report_unexpected_token(&expected, token);
+            self.report_error(|diagnostics| diagnostics.report_unexpected_token(&expected, token));
+            return token;
+        }
+        self.consume()
+    }
+
+    // Types are a primitive type, struct name or array type `[T; N]`, optionally
+    // behind pointers and followed by `?`, e.g. `*Point?`. Parentheses group a
+    // type, `*(Point?)` points to an optional.
+    fn parse_type(&self) -> ASTType {
+        let data_type = self.parse_pointer_type();
+        if self.current_token().kind != TokenKind::QuestionMark {
+            return data_type;
+        }
+        ASTType::optional(data_type, self.consume())
+    }
+
+    fn parse_pointer_type(&self) -> ASTType {
+        let token = self.current_token();
+        match token.kind {
+            TokenKind::Astrisk => {
+                let star = self.consume();
+                ASTType::pointer(star, self.parse_pointer_type())
+            }
+            TokenKind::LeftBracket => {
+                let left_bracket = self.consume();
+                let element_type = self.parse_type();
+                self.consume_expected(TokenKind::SemiColon);
+                let length = match self.current_token().kind {
+                    TokenKind::Integer(length) if length >= 0 => {
+                        self.consume();
+                        length as usize
+                    }
+                    _ => {
+                        self.report_error(|diagnostics| {
+                            diagnostics.report_invalid_array_length(self.current_token())
+                        });
+                        0
+                    }
+                };
+                let right_bracket = self.consume_expected(TokenKind::RightBracket);
+                ASTType::array(left_bracket, element_type, length, right_bracket)
+            }
+            TokenKind::LeftParen => {
+                let left_paren = self.consume();
+                let data_type = self.parse_type();
+                let right_paren = self.consume_expected(TokenKind::RightParen);
+                ASTType::grouped(left_paren, data_type, right_paren)
+            }
+            TokenKind::I8
+            | TokenKind::I16
+            | TokenKind::I32
+            | TokenKind::I64
+            | TokenKind::U8
+            | TokenKind::U16
+            | TokenKind::U32
+            | TokenKind::U64
+            | TokenKind::F32
+            | TokenKind::F64
+            | TokenKind::Bool
+            | TokenKind::Char
+            | TokenKind::Str
+            | TokenKind::Void
+            | TokenKind::Identifier => ASTType::named(self.consume().clone()),
+            // tokens that follow a type are left for the rest of the statement
+            TokenKind::Comma
+            | TokenKind::RightParen
+            | TokenKind::Equal
+            | TokenKind::LeftBrace
+            | TokenKind::RightBrace
+            | TokenKind::SemiColon
+            | TokenKind::Eof => {
+                self.report_error(|diagnostics| diagnostics.report_unknown_type(token));
+                ASTType::error(token.span.clone())
+            }
+            _ => {
+                self.report_error(|diagnostics| diagnostics.report_unknown_type(token));
+                ASTType::error(self.consume().span.clone())
+            }
         }
-        token
     }
 
     fn parse_return_statement(&mut self) -> ASTStatement {
-        self.consume_expected(TokenKind::Return);
+        let keyword = self.consume_expected(TokenKind::Return).clone();
         let expr = self.parse_expression();
         self.consume_expected(TokenKind::SemiColon);
-        ASTStatement::return_statement(expr)
+        ASTStatement::return_statement(keyword, expr)
     }
 
     fn parse_let_statement(&mut self) -> ASTStatement {
         self.consume_expected(TokenKind::Let);
         let identifier = self.consume_expected(TokenKind::Identifier).clone();
         self.consume_expected(TokenKind::Colon);
-        let data_type = self.consume().clone();
+        let data_type = self.parse_type();
         self.consume_expected(TokenKind::Equal);
         let expr = self.parse_expression();
         self.consume_expected(TokenKind::SemiColon);
@@ -151,7 +308,7 @@ impl Parser {
         self.consume_expected(TokenKind::Var);
         let identifier = self.consume_expected(TokenKind::Identifier).clone();
         self.consume_expected(TokenKind::Colon);
-        let data_type = self.consume().clone();
+        let data_type = self.parse_type();
         self.consume_expected(TokenKind::Equal);
         let expr = self.parse_expression();
         self.consume_expected(TokenKind::SemiColon);
@@ -159,27 +316,46 @@ impl Parser {
     }
 
     fn parse_compound_statement(&mut self) -> ASTStatement {
-        self.consume_expected(TokenKind::LeftBrace);
+        // an opening brace is a safe point to report errors of the block again
+        if self.current_token().kind == TokenKind::LeftBrace {
+            self.panic_mode.set(false);
+        }
+        let open_brace = self.consume_expected(TokenKind::LeftBrace).clone();
         let mut statements: Vec<ASTStatement> = Vec::new();
         while self.current_token().kind != TokenKind::RightBrace
             && self.current_token().kind != TokenKind::Eof
         {
-            println!("Help {:?}", self.current_token());
             statements.push(self.parse_statement());
         }
-        self.consume_expected(TokenKind::RightBrace);
-        ASTStatement::compound(statements)
+        let close_brace = self.consume_expected(TokenKind::RightBrace).clone();
+        ASTStatement::compound(open_brace, statements, close_brace)
     }
 
     fn parse_function_statement(&mut self) -> ASTStatement {
-        self.consume_expected(TokenKind::Func);
+        let function = self.parse_function_declaration(false);
+        ASTStatement::function(function)
+    }
+
+    // Methods are declared inside of a struct and take `self` as first parameter
+    fn parse_function_declaration(&mut self, is_method: bool) -> ASTFunctionStatement {
+        let keyword = self.consume_expected(TokenKind::Func).clone();
         let identifier = self.consume_expected(TokenKind::Identifier).clone();
         self.consume_expected(TokenKind::LeftParen);
 
+        let receiver = if is_method {
+            let receiver = self.consume_expected(TokenKind::SelfKeyword).clone();
+            if self.current_token().kind == TokenKind::Comma {
+                self.consume();
+            }
+            Some(receiver)
+        } else {
+            None
+        };
+
         if self.current_token().kind == TokenKind::Comma {
-            self.diagnostics_colletion
-                .borrow_mut()
-                .report_unexpected_token(&TokenKind::Identifier, self.peek(1));
+            self.report_error(|diagnostics| {
+                diagnostics.report_unexpected_token(&TokenKind::Identifier, self.peek(1))
+            });
             self.consume();
         }
 
@@ -188,9 +364,10 @@ impl Parser {
             && self.current_token().kind != TokenKind::Eof
         {
             if self.current_token().kind == TokenKind::Comma {
-                self.diagnostics_colletion
-                    .borrow_mut()
-                    .report_unexpected_token(&TokenKind::Identifier, self.current_token());
+                self.report_error(|diagnostics| {
+                    diagnostics
+                        .report_unexpected_token(&TokenKind::Identifier, self.current_token())
+                });
                 self.consume();
             }
 
@@ -199,44 +376,89 @@ impl Parser {
                 self.consume_expected(TokenKind::Colon);
                 arguments.push(FunctionArgumentDeclaration {
                     identifier,
-                    data_type: self.consume().clone(),
+                    data_type: self.parse_type(),
                 });
             } else {
-                self.diagnostics_colletion
-                    .borrow_mut()
-                    .report_unexpected_token(&TokenKind::Identifier, self.current_token());
+                self.report_error(|diagnostics| {
+                    diagnostics
+                        .report_unexpected_token(&TokenKind::Identifier, self.current_token())
+                });
+                self.consume();
             }
 
             if self.current_token().kind == TokenKind::Comma
                 && self.peek(1).kind == TokenKind::RightParen
             {
+                // trailing comma
                 self.consume_expected(TokenKind::RightParen);
+                self.consume();
                 break;
             } else if self.current_token().kind == TokenKind::Comma {
                 self.consume(); // Consume comma if present
             }
         }
 
-        let right_paren = self.consume_expected(TokenKind::RightParen);
+        let right_paren = self.consume_expected(TokenKind::RightParen).clone();
 
         // Return type is declared like `func foo() -> i32 {...}`
         let return_type = if self.current_token().kind == TokenKind::MinusRightAngleBracket {
             self.consume_expected(TokenKind::MinusRightAngleBracket);
-            self.consume().clone()
+            self.parse_type()
         } else {
-            Token {
+            ASTType::named(Token {
                 kind: TokenKind::Void,
                 span: TextSpan::new(
                     right_paren.span.start,
                     right_paren.span.end,
                     "void".to_string(),
                 ),
-            }
+            })
         };
 
         let body = self.parse_compound_statement();
 
-        ASTStatement::function(identifier, arguments, body, return_type)
+        ASTFunctionStatement {
+            keyword,
+            identifier,
+            receiver,
+            arguments,
+            right_paren,
+            body: Box::new(body),
+            return_type,
+        }
+    }
+
+    fn parse_struct_statement(&mut self) -> ASTStatement {
+        self.consume_expected(TokenKind::Struct);
+        let identifier = self.consume_expected(TokenKind::Identifier).clone();
+        self.consume_expected(TokenKind::LeftBrace);
+
+        // fields are separated by commas, the last one may have a trailing comma.
+        // Methods need no separator.
+        let mut fields: Vec<StructFieldDeclaration> = Vec::new();
+        let mut methods: Vec<ASTFunctionStatement> = Vec::new();
+        while self.current_token().kind != TokenKind::RightBrace
+            && self.current_token().kind != TokenKind::Eof
+        {
+            if self.current_token().kind == TokenKind::Func {
+                methods.push(self.parse_function_declaration(true));
+                continue;
+            }
+            let identifier = self.consume_expected(TokenKind::Identifier).clone();
+            self.consume_expected(TokenKind::Colon);
+            fields.push(StructFieldDeclaration {
+                identifier,
+                data_type: self.parse_type(),
+            });
+            if self.current_token().kind == TokenKind::Comma {
+                self.consume();
+            } else if self.current_token().kind != TokenKind::Func {
+                break;
+            }
+        }
+        let close_brace = self.consume_expected(TokenKind::RightBrace).clone();
+
+        ASTStatement::struct_declaration(identifier, fields, methods, close_brace)
     }
 
     fn consume_optional_else_statement(&mut self) -> Option<ASTElseStatement> {
@@ -246,7 +468,7 @@ impl Parser {
         let else_keyword = self.consume_expected(TokenKind::Else).clone();
         let else_branch = self.parse_compound_statement();
         Some(ASTElseStatement {
-            else_keyword: else_keyword,
+            else_keyword,
             else_branch: Box::new(else_branch),
         })
     }
@@ -268,7 +490,7 @@ impl Parser {
 
     fn parse_if_statement(&mut self) -> ASTStatement {
         let keyword = self.consume_expected(TokenKind::If).clone();
-        let condition = self.parse_expression();
+        let condition = self.parse_expression_allowing_struct_literals(false);
         let then_branch = self.parse_compound_statement();
         let else_branch = self.consume_optional_else_statement();
 
@@ -277,7 +499,7 @@ impl Parser {
 
     fn parse_while_loop_statement(&mut self) -> ASTStatement {
         let keyword = self.consume_expected(TokenKind::While).clone();
-        let condition = self.parse_expression();
+        let condition = self.parse_expression_allowing_struct_literals(false);
         let body = self.parse_compound_statement();
 
         ASTStatement::while_loop(keyword, condition, body)
@@ -287,14 +509,60 @@ impl Parser {
         let keyword = self.consume_expected(TokenKind::For).clone();
         let loop_variable = self.consume_expected(TokenKind::Identifier).clone();
         self.consume_expected(TokenKind::In);
-        let range_start = self.parse_expression();
-        self.consume_expected(TokenKind::Dot);
-        self.consume_expected(TokenKind::Dot);
-        let range_end = self.parse_expression();
+        let iterated = self.parse_expression_allowing_struct_literals(false);
+        // `for x in array` iterates over the elements, `for i in a..b` over a range
+        let iterable = if self.current_token().kind == TokenKind::Dot {
+            self.consume_expected(TokenKind::Dot);
+            self.consume_expected(TokenKind::Dot);
+            let range_end = self.parse_expression_allowing_struct_literals(false);
+            ASTForIterable::Range(Box::new(iterated), Box::new(range_end))
+        } else {
+            ASTForIterable::Array(Box::new(iterated))
+        };
 
         let body = self.parse_compound_statement();
 
-        ASTStatement::for_loop(keyword, loop_variable, (range_start, range_end), body)
+        ASTStatement::for_loop(keyword, loop_variable, iterable, body)
+    }
+
+    fn parse_break_statement(&mut self) -> ASTStatement {
+        let keyword = self.consume_expected(TokenKind::Break).clone();
+        self.consume_expected(TokenKind::SemiColon);
+        ASTStatement::break_statement(keyword)
+    }
+
+    // The module declaration has to be the first statement of a file
+    fn parse_module_statement(&mut self) -> ASTStatement {
+        let keyword = self.consume_expected(TokenKind::Module).clone();
+        if self.cursor.get_value() != 1 {
+            self.report_error(|diagnostics| {
+                diagnostics.report_misplaced_module_statement(&keyword)
+            });
+        }
+        let identifier = self.consume_expected(TokenKind::Identifier).clone();
+        self.consume_expected(TokenKind::SemiColon);
+        ASTStatement::module_statement(keyword, identifier)
+    }
+
+    fn parse_import_statement(&mut self) -> ASTStatement {
+        let keyword = self.consume_expected(TokenKind::Import).clone();
+        if !self.module_header {
+            self.report_error(|diagnostics| {
+                diagnostics.report_misplaced_module_statement(&keyword)
+            });
+        }
+        let identifier = self.consume_expected(TokenKind::Identifier).clone();
+        self.consume_expected(TokenKind::SemiColon);
+        if identifier.kind == TokenKind::Identifier {
+            self.imports.push(identifier.span.literal.clone());
+        }
+        ASTStatement::import_statement(keyword, identifier)
+    }
+
+    fn parse_continue_statement(&mut self) -> ASTStatement {
+        let keyword = self.consume_expected(TokenKind::Continue).clone();
+        self.consume_expected(TokenKind::SemiColon);
+        ASTStatement::continue_statement(keyword)
     }
 
     fn parse_expression_statement(&mut self) -> ASTStatement {
@@ -311,11 +579,7 @@ impl Parser {
                 let assignment = self.parse_binary_expression(0);
                 return ASTExpression::assignment(var, assignment);
             }
-            if self.peek(1).kind == TokenKind::PlusEqual
-                || self.peek(1).kind == TokenKind::MinusEqual
-                || self.peek(1).kind == TokenKind::AstriskEqual
-                || self.peek(1).kind == TokenKind::Slash
-            {
+            if Self::is_compound_assignment_operator(&self.peek(1).kind) {
                 let var = self.consume().clone();
                 let op = self.consume_assignment_operator();
                 let assignment = self.parse_binary_expression(0);
@@ -325,18 +589,70 @@ impl Parser {
                 );
             }
         }
-        self.parse_binary_expression(0)
+
+        // fields, array elements and dereferenced pointers can be assigned as well
+        let expr = self.parse_binary_expression(0);
+        if !matches!(
+            expr.kind,
+            ASTExpressionKind::FieldAccess(_)
+                | ASTExpressionKind::Index(_)
+                | ASTExpressionKind::Dereference(_)
+        ) {
+            return expr;
+        }
+        let assignment = if self.current_token().kind == TokenKind::Equal {
+            self.consume();
+            self.parse_binary_expression(0)
+        } else if Self::is_compound_assignment_operator(&self.current_token().kind) {
+            let op = self.consume_assignment_operator();
+            let assignment = self.parse_binary_expression(0);
+            ASTExpression::binary(op, expr.clone(), assignment)
+        } else {
+            return expr;
+        };
+        match expr.kind {
+            ASTExpressionKind::FieldAccess(target) => {
+                ASTExpression::field_assignment(target, assignment)
+            }
+            ASTExpressionKind::Index(target) => ASTExpression::index_assignment(target, assignment),
+            ASTExpressionKind::Dereference(target) => {
+                ASTExpression::dereference_assignment(target, assignment)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn is_compound_assignment_operator(kind: &TokenKind) -> bool {
+        matches!(
+            kind,
+            TokenKind::PlusEqual
+                | TokenKind::MinusEqual
+                | TokenKind::AstriskEqual
+                | TokenKind::SlashEqual
+                | TokenKind::PipeEqual
+                | TokenKind::AmpersandEqual
+                | TokenKind::CaretEqual
+        )
     }
 
     fn parse_expression(&mut self) -> ASTExpression {
         self.parse_assignment_expression()
     }
 
+    // Conditions and ranges are followed by a block, `if a {` must not start a
+    // struct literal there. Nested parentheses, arguments and fields allow them again.
+    fn parse_expression_allowing_struct_literals(&mut self, allowed: bool) -> ASTExpression {
+        let previous = self.struct_literals_allowed.replace(allowed);
+        let expr = self.parse_expression();
+        self.struct_literals_allowed.set(previous);
+        expr
+    }
+
     fn parse_arguments_list(&mut self) -> Vec<ASTExpression> {
         if self.current_token().kind == TokenKind::Comma {
-            self.diagnostics_colletion
-                .borrow_mut()
-                .report_unexpected_token(&TokenKind::Identifier, self.peek(1));
+            self.report_error(|diagnostics| {
+                diagnostics.report_unexpected_token(&TokenKind::Identifier, self.peek(1))
+            });
             self.consume();
         }
 
@@ -345,16 +661,22 @@ impl Parser {
             && self.current_token().kind != TokenKind::Eof
         {
             if self.current_token().kind == TokenKind::Comma {
-                self.diagnostics_colletion
-                    .borrow_mut()
-                    .report_unexpected_token(&TokenKind::Identifier, self.current_token());
+                self.report_error(|diagnostics| {
+                    diagnostics
+                        .report_unexpected_token(&TokenKind::Identifier, self.current_token())
+                });
                 self.consume();
             }
-            arguments.push(self.parse_expression());
+            arguments.push(self.parse_expression_allowing_struct_literals(true));
+            if self.panic_mode.get() {
+                break;
+            }
             if self.current_token().kind == TokenKind::Comma
                 && self.peek(1).kind == TokenKind::RightParen
             {
+                // trailing comma
                 self.consume_expected(TokenKind::RightParen);
+                self.consume();
                 break;
             } else if self.current_token().kind == TokenKind::Comma {
                 self.consume(); // Consume comma if present
@@ -363,52 +685,158 @@ impl Parser {
         arguments
     }
 
-    fn parse_function_call_expression(&mut self) -> ASTExpression {
+    fn parse_function_call_expression(&mut self, module: Option<Token>) -> ASTExpression {
         let identifier = self.peek(-1).clone();
         self.consume();
         let arguments = self.parse_arguments_list();
-        self.consume_expected(TokenKind::RightParen);
-        ASTExpression::function_call(identifier.clone(), arguments)
+        let right_paren = self.consume_expected(TokenKind::RightParen).clone();
+        ASTExpression::function_call(module, identifier, arguments, &right_paren)
+    }
+
+    // `math.max(a, b)` calls a function of the imported module `math`
+    fn is_qualified_function_call(&self, module: &Token) -> bool {
+        self.imports.contains(&module.span.literal)
+            && self.current_token().kind == TokenKind::Dot
+            && self.peek(1).kind == TokenKind::Identifier
+            && self.peek(2).kind == TokenKind::LeftParen
     }
 
     fn parse_primary_expression(&mut self) -> ASTExpression {
+        // keep the tokens the recovery synchronizes on
+        if matches!(
+            self.current_token().kind,
+            TokenKind::SemiColon | TokenKind::RightBrace | TokenKind::Eof
+        ) {
+            let token = self.current_token();
+            self.report_error(|diagnostics| diagnostics.report_expected_expression(token));
+            return ASTExpression::error(token.span.clone());
+        }
+
         let token = self.consume().clone();
 
-        return match token.kind {
-            TokenKind::Integer(i) => ASTExpression::integer(i),
-            TokenKind::Floating(i) => ASTExpression::float(i),
+        match token.kind {
+            TokenKind::Integer(i) => ASTExpression::integer(i, token.span),
+            TokenKind::Floating(i) => ASTExpression::float(i, token.span),
+            TokenKind::String(s) => ASTExpression::string(s, token.span),
+            TokenKind::Character(c) => ASTExpression::character(c, token.span),
+            TokenKind::True => ASTExpression::boolean(true, token.span),
+            TokenKind::False => ASTExpression::boolean(false, token.span),
+            TokenKind::SelfKeyword => ASTExpression::identifier(token),
+            TokenKind::Null => ASTExpression::null(token.span),
             TokenKind::Identifier => {
                 if self.current_token().kind == TokenKind::LeftParen {
-                    self.parse_function_call_expression()
+                    self.parse_function_call_expression(None)
+                } else if self.is_qualified_function_call(&token) {
+                    self.consume();
+                    self.consume();
+                    self.parse_function_call_expression(Some(token))
+                } else if self.current_token().kind == TokenKind::LeftBrace
+                    && self.struct_literals_allowed.get()
+                {
+                    self.parse_struct_literal_expression(token)
                 } else {
                     ASTExpression::identifier(token.clone())
                 }
             }
 
             TokenKind::LeftParen => {
-                let expr = self.parse_binary_expression(0);
-                let _found_token = self.consume_expected(TokenKind::RightParen);
-                ASTExpression::parenthesized(expr)
+                let expr = self.parse_expression_allowing_struct_literals(true);
+                let right_paren = self.consume_expected(TokenKind::RightParen).clone();
+                ASTExpression::parenthesized(&token, expr, &right_paren)
             }
+            TokenKind::LeftBracket => self.parse_array_literal_expression(&token),
             TokenKind::Tilde | TokenKind::Minus | TokenKind::ExclemationMark => {
-                self.parse_unary_expression()
+                self.parse_unary_expression(&token)
+            }
+            // `&p.x` and `*p.x` apply to the field like the unary operators
+            TokenKind::Ampersand => {
+                let expr = self.parse_postfix_expression();
+                ASTExpression::address_of(token, expr)
+            }
+            TokenKind::Astrisk => {
+                let expr = self.parse_postfix_expression();
+                ASTExpression::dereference(token, expr)
             }
             _ => {
-                self.diagnostics_colletion
-                    .borrow_mut()
-                    .report_expected_expression(&token);
+                self.report_error(|diagnostics| diagnostics.report_expected_expression(&token));
                 ASTExpression::error(token.span)
             }
-        };
+        }
     }
 
-    fn parse_unary_expression(&mut self) -> ASTExpression {
-        let operator = self.parse_unary_operator().unwrap();
-        let expr = self.parse_primary_expression();
+    fn parse_struct_literal_expression(&mut self, identifier: Token) -> ASTExpression {
+        self.consume_expected(TokenKind::LeftBrace);
+        let mut fields: Vec<ASTStructLiteralField> = Vec::new();
+        while self.current_token().kind != TokenKind::RightBrace
+            && self.current_token().kind != TokenKind::Eof
+        {
+            let identifier = self.consume_expected(TokenKind::Identifier).clone();
+            self.consume_expected(TokenKind::Colon);
+            fields.push(ASTStructLiteralField {
+                identifier,
+                expr: self.parse_expression_allowing_struct_literals(true),
+            });
+            if self.current_token().kind != TokenKind::Comma {
+                break;
+            }
+            self.consume();
+        }
+        let right_brace = self.consume_expected(TokenKind::RightBrace).clone();
+        ASTExpression::struct_literal(identifier, fields, &right_brace)
+    }
+
+    // Field accesses and method calls bind tighter than any operator, `-p.x`
+    // negates the field
+    fn parse_postfix_expression(&mut self) -> ASTExpression {
+        let mut expr = self.parse_primary_expression();
+        loop {
+            match self.current_token().kind {
+                TokenKind::Dot if self.peek(1).kind == TokenKind::Identifier => {
+                    self.consume();
+                    let member = self.consume().clone();
+                    if self.current_token().kind == TokenKind::LeftParen {
+                        self.consume();
+                        let arguments = self.parse_arguments_list();
+                        let right_paren = self.consume_expected(TokenKind::RightParen).clone();
+                        expr = ASTExpression::method_call(expr, member, arguments, &right_paren);
+                    } else {
+                        expr = ASTExpression::field_access(expr, member);
+                    }
+                }
+                TokenKind::LeftBracket => {
+                    self.consume();
+                    let index = self.parse_expression_allowing_struct_literals(true);
+                    let right_bracket = self.consume_expected(TokenKind::RightBracket).clone();
+                    expr = ASTExpression::index(expr, index, right_bracket);
+                }
+                _ => return expr,
+            }
+        }
+    }
+
+    // Elements are separated by commas, the last one may have a trailing comma
+    fn parse_array_literal_expression(&mut self, left_bracket: &Token) -> ASTExpression {
+        let mut elements: Vec<ASTExpression> = Vec::new();
+        while self.current_token().kind != TokenKind::RightBracket
+            && self.current_token().kind != TokenKind::Eof
+        {
+            elements.push(self.parse_expression_allowing_struct_literals(true));
+            if self.panic_mode.get() || self.current_token().kind != TokenKind::Comma {
+                break;
+            }
+            self.consume();
+        }
+        let right_bracket = self.consume_expected(TokenKind::RightBracket).clone();
+        ASTExpression::array_literal(left_bracket, elements, &right_bracket)
+    }
+
+    fn parse_unary_expression(&mut self, operator_token: &Token) -> ASTExpression {
+        let operator = self.parse_unary_operator(operator_token).unwrap();
+        let expr = self.parse_postfix_expression();
         ASTExpression::unary(operator, expr)
     }
     fn parse_binary_expression(&mut self, precedence: u8) -> ASTExpression {
-        let mut left = self.parse_primary_expression();
+        let mut left = self.parse_postfix_expression();
 
         while let Some(operator) = self.parse_binary_operator() {
             let operator_precedence = operator.precedence();
@@ -444,27 +872,217 @@ impl Parser {
             TokenKind::LeftAngleBracketEqual => Some(ASTBinaryOperatorKind::LessThanOrEqual),
             _ => None,
         };
-        kind.map(|kind| {
-            return ASTBinaryOperator {
-                kind,
-                token: token.clone(),
-            };
+        kind.map(|kind| ASTBinaryOperator {
+            kind,
+            token: token.clone(),
         })
     }
 
-    fn parse_unary_operator(&mut self) -> Option<ASTUnaryOperator> {
-        let token = self.current_token();
+    fn parse_unary_operator(&mut self, token: &Token) -> Option<ASTUnaryOperator> {
         let kind = match token.kind {
             TokenKind::Tilde => Some(ASTUnaryOperatorKind::BitwiseNOT),
             TokenKind::ExclemationMark => Some(ASTUnaryOperatorKind::LogicNot),
             TokenKind::Minus => Some(ASTUnaryOperatorKind::Minus),
             _ => None,
         };
-        kind.map(|kind| {
-            return ASTUnaryOperator {
-                kind,
-                token: token.clone(),
-            };
+        kind.map(|kind| ASTUnaryOperator {
+            kind,
+            token: token.clone(),
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::ast::symbol_checker::SymbolChecker;
+    use crate::ast::type_checker::TypeChecker;
+    use crate::ast::{ASTExpressionKind, ASTStatementKind, Ast};
+    use crate::diagnostics::DiagnosticsColletion;
+
+    use super::Parser;
+
+    fn parse(input: &str) -> (Ast, Vec<String>) {
+        parse_with_passes(input, false)
+    }
+
+    // Parses the input and optionally runs the semantic passes over the recovered AST
+    fn parse_with_passes(input: &str, run_passes: bool) -> (Ast, Vec<String>) {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let mut parser = Parser::from_input(input.to_string(), Rc::clone(&diagnostics_colletion));
+        let mut ast = Ast::new();
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        if run_passes {
+            ast.visit(&mut SymbolChecker::new(Rc::clone(&diagnostics_colletion)));
+            ast.visit(&mut TypeChecker::new(Rc::clone(&diagnostics_colletion)));
+        }
+
+        let diagnostics = diagnostics_colletion.borrow();
+        let messages = diagnostics
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect();
+        (ast, messages)
+    }
+
+    #[test]
+    fn should_report_missing_semicolon_once() {
+        let input = "\
+        let a: i32 = 1
+        let b: i32 = a + 2;
+        b;
+        ";
+        let (ast, diagnostics) = parse_with_passes(input, true);
+        assert_eq!(diagnostics, vec!["Expected <;>, but found <Let>"]);
+        assert_eq!(ast.statements.len(), 3);
+    }
+
+    #[test]
+    fn should_recover_at_next_statement() {
+        let input = "\
+        func a(arg1, arg2) {
+            let c = arg2 / 071
+            return arg1 * c
+        }
+
+        return a(0x02, 7.67)
+        ";
+        let (ast, diagnostics) = parse(input);
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Expected <:>, but found <,>",
+                "Expected <:>, but found <=>",
+                "Expected <;>, but found <}>",
+                "Expected <;>, but found <Eof>",
+            ]
+        );
+        assert_eq!(ast.statements.len(), 2);
+    }
+
+    #[test]
+    fn should_skip_to_end_of_broken_statement() {
+        let input = "\
+        var a: i32 = ;
+        a = / 2;
+        a += 3;
+        ";
+        let (ast, diagnostics) = parse(input);
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Expected expression, but found <;>",
+                "Expected expression, but found </>",
+            ]
+        );
+        assert_eq!(ast.statements.len(), 3);
+    }
+
+    #[test]
+    fn should_make_progress_on_stray_closing_brace() {
+        let input = "\
+        f(1, }
+        let a: i32 = 1;
+        ";
+        let (ast, diagnostics) = parse(input);
+        assert_eq!(diagnostics, vec!["Expected expression, but found <}>"]);
+        // the stray brace is skipped as an erroneous statement of its own
+        assert_eq!(ast.statements.len(), 3);
+    }
+
+    #[test]
+    fn should_parse_type_annotations() {
+        let input = "\
+        let a: *Point? = null;
+        func f(x: * * i32) -> bool? { return null; }
+        var b: 5 = 1;
+        let c: = 2;
+        ";
+        let (ast, diagnostics) = parse(input);
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Expected type, but found <5>",
+                "Expected type, but found <=>"
+            ]
+        );
+
+        let mut types = Vec::new();
+        for statement in ast.statements.iter() {
+            match &statement.kind {
+                ASTStatementKind::Let(statement) => types.push(&statement.data_type),
+                ASTStatementKind::Var(statement) => types.push(&statement.data_type),
+                ASTStatementKind::FuncDecl(function) => {
+                    types.push(&function.arguments[0].data_type);
+                    types.push(&function.return_type);
+                }
+                _ => {}
+            }
+        }
+        let types: Vec<(String, String)> = types
+            .iter()
+            .map(|data_type| (data_type.to_string(), data_type.span.literal.clone()))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("*Point?".to_string(), "*Point?".to_string()),
+                ("**i32".to_string(), "* * i32".to_string()),
+                ("bool?".to_string(), "bool?".to_string()),
+                ("?".to_string(), "5".to_string()),
+                ("?".to_string(), "=".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_module_header_and_qualified_calls() {
+        let input = "\
+        module main;
+        import math;
+        let a: i32 = math.max(1, 2);
+        let b: i32 = main.max(1);
+        import late;
+        module other;
+        func f() { import nested; }
+        ";
+        let (ast, messages) = parse(input);
+        assert_eq!(
+            messages,
+            vec![
+                "<Import> is only allowed at the start of a file, before any other statement",
+                "<Module> is only allowed at the start of a file, before any other statement",
+                "<Import> is only allowed at the start of a file, before any other statement",
+            ]
+        );
+        assert_eq!(ast.module_declaration().unwrap().span.literal, "main");
+        let imports: Vec<&str> = ast
+            .imports()
+            .iter()
+            .map(|import| import.span.literal.as_str())
+            .collect();
+        assert_eq!(imports, vec!["math", "late"]);
+
+        // only imported modules qualify a function call
+        let calls: Vec<String> = ast
+            .statements
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                ASTStatementKind::Let(statement) => Some(&statement.initializer.kind),
+                _ => None,
+            })
+            .map(|initializer| match initializer {
+                ASTExpressionKind::FunctionCall(call) => call.qualified_name(),
+                ASTExpressionKind::MethodCall(call) => {
+                    format!("method {}", call.method.span.literal)
+                }
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(calls, vec!["math.max", "method max"]);
+    }
+}
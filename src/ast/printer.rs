@@ -1,10 +1,14 @@
-use super::ASTVisitor;
+use super::formatter::Trivia;
+use super::lexer::TokenKind;
+use super::{ASTExpression, ASTExpressionKind, ASTStatementKind, ASTVisitor};
+use crate::terminal::strip_colors;
 
+use termion::color;
 use termion::color::Fg;
-use termion::color::{self, White};
 
 pub struct ASTTreePrinter {
     indentation: usize,
+    result: String,
 }
 
 impl ASTTreePrinter {
@@ -13,7 +17,6 @@ impl ASTTreePrinter {
     const TEXT_COLOR: color::White = color::White;
     const STATEMENT_COLOR: color::Yellow = color::Yellow;
     const LET_STATEMENT_COLOR: color::Green = color::Green;
-    const EXPR_COLOR: color::Green = color::Green;
     const BIN_EXPR_COLOR: color::LightBlue = color::LightBlue;
     const OPERATOR_COLOR: color::LightYellow = color::LightYellow;
 
@@ -21,12 +24,20 @@ impl ASTTreePrinter {
     const LET_STATEMENT_ICON: &str = nerd_font_symbols::md::MD_EQUAL;
     const FUNC_STATEMENT_ICON: &str = nerd_font_symbols::md::MD_FUNCTION_VARIANT;
     const FUNC_CALL_STATEMENT_ICON: &str = nerd_font_symbols::md::MD_FUNCTION;
-    const EXPR_ICON: &str = nerd_font_symbols::md::MD_FUNCTION_VARIANT;
     const BIN_EXPR_ICON: &str = nerd_font_symbols::cod::COD_SYMBOL_OPERATOR;
     const VARIABLE_ICON: &str = nerd_font_symbols::md::MD_VARIABLE;
+    const STRUCT_ICON: &str = nerd_font_symbols::cod::COD_SYMBOL_STRUCTURE;
+    const FIELD_ICON: &str = nerd_font_symbols::cod::COD_SYMBOL_FIELD;
 
     pub fn new() -> Self {
-        Self { indentation: 0 }
+        Self {
+            indentation: 0,
+            result: String::new(),
+        }
+    }
+
+    pub fn result(&self) -> &str {
+        &self.result
     }
 
     fn increase_indentation(&mut self) {
@@ -36,15 +47,14 @@ impl ASTTreePrinter {
         self.indentation -= Self::INDENATION;
     }
 
-    fn print(&self, text: &str, text_color: &dyn color::Color) {
-        // println!("{}├─ {}", "│ ".repeat(self.indentation), text);
-        println!(
-            "│{}└─ {}{}{}",
+    fn print(&mut self, text: &str, text_color: &dyn color::Color) {
+        self.result.push_str(&format!(
+            "│{}└─ {}{}{}\n",
             " ".repeat(self.indentation),
             color::Fg(text_color),
             text,
             color::Fg(color::Reset)
-        );
+        ));
     }
 }
 
@@ -81,11 +91,7 @@ impl ASTVisitor for ASTTreePrinter {
         );
         self.increase_indentation();
         self.print(
-            &format!(
-                "DataType: {}{}",
-                Fg(Self::TEXT_COLOR),
-                statement.data_type.span.literal
-            ),
+            &format!("DataType: {}{}", Fg(Self::TEXT_COLOR), statement.data_type),
             &Self::TEXT_COLOR,
         );
         ASTVisitor::do_visit_expression(self, &statement.initializer);
@@ -104,11 +110,7 @@ impl ASTVisitor for ASTTreePrinter {
         );
         self.increase_indentation();
         self.print(
-            &format!(
-                "DataType: {}{}",
-                Fg(Self::TEXT_COLOR),
-                statement.data_type.span.literal
-            ),
+            &format!("DataType: {}{}", Fg(Self::TEXT_COLOR), statement.data_type),
             &Self::TEXT_COLOR,
         );
         ASTVisitor::do_visit_expression(self, &statement.initializer);
@@ -137,14 +139,24 @@ impl ASTVisitor for ASTTreePrinter {
             &color::Blue,
         );
         self.increase_indentation();
-        self.print("From:", &Self::TEXT_COLOR);
-        self.increase_indentation();
-        self.visit_expression(&statement.range.0);
-        self.decrease_indentation();
-        self.print("To:", &Self::TEXT_COLOR);
-        self.increase_indentation();
-        self.visit_expression(&statement.range.1);
-        self.decrease_indentation();
+        match &statement.iterable {
+            super::ASTForIterable::Range(start, end) => {
+                self.print("From:", &Self::TEXT_COLOR);
+                self.increase_indentation();
+                self.visit_expression(start);
+                self.decrease_indentation();
+                self.print("To:", &Self::TEXT_COLOR);
+                self.increase_indentation();
+                self.visit_expression(end);
+                self.decrease_indentation();
+            }
+            super::ASTForIterable::Array(array) => {
+                self.print("Elements of:", &Self::TEXT_COLOR);
+                self.increase_indentation();
+                self.visit_expression(array);
+                self.decrease_indentation();
+            }
+        }
         self.print("Body:", &Self::TEXT_COLOR);
         self.increase_indentation();
         self.visit_statement(&statement.body);
@@ -163,6 +175,28 @@ impl ASTVisitor for ASTTreePrinter {
         self.decrease_indentation();
     }
 
+    fn visit_break_statement(&mut self, _statement: &super::ASTBreakStatement) {
+        self.print("Break", &color::Blue);
+    }
+
+    fn visit_continue_statement(&mut self, _statement: &super::ASTContinueStatement) {
+        self.print("Continue", &color::Blue);
+    }
+
+    fn visit_module_statement(&mut self, statement: &super::ASTModuleStatement) {
+        self.print(
+            &format!("Module: {}", statement.identifier.span.literal),
+            &color::Blue,
+        );
+    }
+
+    fn visit_import_statement(&mut self, statement: &super::ASTImportStatement) {
+        self.print(
+            &format!("Import: {}", statement.identifier.span.literal),
+            &color::Blue,
+        );
+    }
+
     fn visit_funtion_statement(&mut self, function: &super::ASTFunctionStatement) {
         self.print(
             &format!(
@@ -175,8 +209,19 @@ impl ASTVisitor for ASTTreePrinter {
         );
 
         self.increase_indentation();
-        self.print(&format!("Arguments:"), &Self::TEXT_COLOR);
+        self.print("Arguments:", &Self::TEXT_COLOR);
         self.increase_indentation();
+        if let Some(receiver) = &function.receiver {
+            self.print(
+                &format!(
+                    "{}  Receiver: {}{}",
+                    Self::FUNC_STATEMENT_ICON,
+                    color::Fg(Self::TEXT_COLOR),
+                    &receiver.span.literal
+                ),
+                &Self::TEXT_COLOR,
+            );
+        }
         for arg in function.arguments.iter() {
             self.print(
                 &format!(
@@ -184,14 +229,22 @@ impl ASTVisitor for ASTTreePrinter {
                     Self::FUNC_STATEMENT_ICON,
                     color::Fg(Self::TEXT_COLOR),
                     &arg.identifier.span.literal,
-                    &arg.data_type.span.literal
+                    &arg.data_type
                 ),
                 &Self::TEXT_COLOR,
             );
         }
         self.decrease_indentation();
 
-        self.print(&format!("Body:"), &Self::TEXT_COLOR);
+        self.print(
+            &format!(
+                "Return type: {}{}",
+                Fg(Self::TEXT_COLOR),
+                function.return_type
+            ),
+            &Self::TEXT_COLOR,
+        );
+        self.print("Body:", &Self::TEXT_COLOR);
         self.increase_indentation();
 
         if let super::ASTStatementKind::Compound(statement) = &function.body.kind {
@@ -200,13 +253,42 @@ impl ASTVisitor for ASTTreePrinter {
         self.decrease_indentation();
     }
 
+    fn visit_struct_statement(&mut self, statement: &super::ASTStructStatement) {
+        self.print(
+            &format!(
+                "{}  Struct: {}{}",
+                Self::STRUCT_ICON,
+                color::Fg(Self::TEXT_COLOR),
+                &statement.identifier.span.literal
+            ),
+            &Self::TEXT_COLOR,
+        );
+        self.increase_indentation();
+        for field in statement.fields.iter() {
+            self.print(
+                &format!(
+                    "{}  Field: {}{} ({})",
+                    Self::FIELD_ICON,
+                    color::Fg(Self::TEXT_COLOR),
+                    &field.identifier.span.literal,
+                    &field.data_type
+                ),
+                &Self::TEXT_COLOR,
+            );
+        }
+        for method in statement.methods.iter() {
+            self.visit_funtion_statement(method);
+        }
+        self.decrease_indentation();
+    }
+
     fn visit_expression(&mut self, expr: &super::ASTExpression) {
         // self.print(
         //     &format!("{}  Expression:", Self::EXPR_ICON),
         //     &Self::EXPR_COLOR,
         // );
         // self.increase_indentation();
-        ASTVisitor::do_visit_expression(self, &expr);
+        ASTVisitor::do_visit_expression(self, expr);
         // self.decrease_indentation();
     }
 
@@ -231,13 +313,31 @@ impl ASTVisitor for ASTTreePrinter {
                 "{}  FunctionCall: {}{}",
                 Self::FUNC_CALL_STATEMENT_ICON,
                 color::Fg(Self::TEXT_COLOR),
-                &expr.identifier.span.literal
+                expr.qualified_name()
             ),
             &Self::TEXT_COLOR,
         );
         self.increase_indentation();
         for expr in expr.arguments.iter() {
-            ASTVisitor::do_visit_expression(self, &expr);
+            ASTVisitor::do_visit_expression(self, expr);
+        }
+        self.decrease_indentation();
+    }
+
+    fn visit_method_call_expression(&mut self, expr: &super::ASTMethodCallExpression) {
+        self.print(
+            &format!(
+                "{}  MethodCall: {}{}",
+                Self::FUNC_CALL_STATEMENT_ICON,
+                color::Fg(Self::TEXT_COLOR),
+                &expr.method.span.literal
+            ),
+            &Self::TEXT_COLOR,
+        );
+        self.increase_indentation();
+        self.visit_expression(&expr.object);
+        for expr in expr.arguments.iter() {
+            ASTVisitor::do_visit_expression(self, expr);
         }
         self.decrease_indentation();
     }
@@ -249,6 +349,92 @@ impl ASTVisitor for ASTTreePrinter {
         );
     }
 
+    fn visit_struct_literal_expression(&mut self, expr: &super::ASTStructLiteralExpression) {
+        self.print(
+            &format!(
+                "{}  StructLiteral: {}{}",
+                Self::STRUCT_ICON,
+                color::Fg(Self::TEXT_COLOR),
+                &expr.identifier.span.literal
+            ),
+            &Self::TEXT_COLOR,
+        );
+        self.increase_indentation();
+        for field in expr.fields.iter() {
+            self.print(
+                &format!(
+                    "{}  Field: {}",
+                    Self::FIELD_ICON,
+                    &field.identifier.span.literal
+                ),
+                &Self::TEXT_COLOR,
+            );
+            self.increase_indentation();
+            self.visit_expression(&field.expr);
+            self.decrease_indentation();
+        }
+        self.decrease_indentation();
+    }
+
+    fn visit_field_access_expression(&mut self, expr: &super::ASTFieldAccessExpression) {
+        self.print(
+            &format!(
+                "{}  FieldAccess: {}",
+                Self::FIELD_ICON,
+                &expr.field.span.literal
+            ),
+            &Self::TEXT_COLOR,
+        );
+        self.increase_indentation();
+        self.visit_expression(&expr.object);
+        self.decrease_indentation();
+    }
+
+    fn visit_field_assignment_expression(&mut self, expr: &super::ASTFieldAssignmentExpression) {
+        self.print(
+            &format!(
+                "{}  FieldAssignment: {}{}",
+                nerd_font_symbols::md::MD_EQUAL,
+                color::Fg(Self::OPERATOR_COLOR),
+                expr.target.field.span.literal
+            ),
+            &Self::TEXT_COLOR,
+        );
+        self.increase_indentation();
+        self.visit_expression(&expr.target.object);
+        self.visit_expression(&expr.expr);
+        self.decrease_indentation();
+    }
+
+    fn visit_array_literal_expression(&mut self, expr: &super::ASTArrayLiteralExpression) {
+        self.print("ArrayLiteral:", &Self::TEXT_COLOR);
+        self.increase_indentation();
+        for element in expr.elements.iter() {
+            self.visit_expression(element);
+        }
+        self.decrease_indentation();
+    }
+
+    fn visit_index_expression(&mut self, expr: &super::ASTIndexExpression) {
+        self.print("Index:", &Self::TEXT_COLOR);
+        self.increase_indentation();
+        self.visit_expression(&expr.object);
+        self.visit_expression(&expr.index);
+        self.decrease_indentation();
+    }
+
+    fn visit_index_assignment_expression(&mut self, expr: &super::ASTIndexAssignmentExpression) {
+        self.print(
+            &format!("{}  IndexAssignment:", nerd_font_symbols::md::MD_EQUAL,),
+            &Self::TEXT_COLOR,
+        );
+        self.increase_indentation();
+        self.visit_expression(&expr.target.object);
+        self.visit_expression(&expr.target.index);
+        self.visit_expression(&expr.expr);
+        self.decrease_indentation();
+    }
+
     fn visit_unary_expression(&mut self, expr: &super::ASTUnaryExpression) {
         self.print(
             &format!(
@@ -264,6 +450,55 @@ impl ASTVisitor for ASTTreePrinter {
         self.decrease_indentation();
     }
 
+    fn visit_address_of_expression(&mut self, expr: &super::ASTAddressOfExpression) {
+        self.print(
+            &format!(
+                "{}  AddressOf: {}{}",
+                Self::BIN_EXPR_ICON,
+                color::Fg(Self::OPERATOR_COLOR),
+                expr.operator.span.literal
+            ),
+            &Self::BIN_EXPR_COLOR,
+        );
+        self.increase_indentation();
+        self.visit_expression(&expr.expr);
+        self.decrease_indentation();
+    }
+
+    fn visit_dereference_expression(&mut self, expr: &super::ASTDereferenceExpression) {
+        self.print(
+            &format!(
+                "{}  Dereference: {}{}",
+                Self::BIN_EXPR_ICON,
+                color::Fg(Self::OPERATOR_COLOR),
+                expr.operator.span.literal
+            ),
+            &Self::BIN_EXPR_COLOR,
+        );
+        self.increase_indentation();
+        self.visit_expression(&expr.expr);
+        self.decrease_indentation();
+    }
+
+    fn visit_dereference_assignment_expression(
+        &mut self,
+        expr: &super::ASTDereferenceAssignmentExpression,
+    ) {
+        self.print(
+            &format!(
+                "{}  DereferenceAssignment: {}{}",
+                nerd_font_symbols::md::MD_EQUAL,
+                color::Fg(Self::OPERATOR_COLOR),
+                expr.target.operator.span.literal
+            ),
+            &Self::TEXT_COLOR,
+        );
+        self.increase_indentation();
+        self.visit_expression(&expr.target.expr);
+        self.visit_expression(&expr.expr);
+        self.decrease_indentation();
+    }
+
     fn visit_binary_expression(&mut self, expr: &super::ASTBinaryExpression) {
         self.print(
             &format!(
@@ -294,91 +529,148 @@ impl ASTVisitor for ASTTreePrinter {
         self.visit_expression(&expr.expr);
     }
 
-    fn visit_binary_operator(&mut self, op: &super::ASTBinaryOperator) {
-        let var_name = format!(
-            "Operator: {}",
-            match op.kind {
-                super::ASTBinaryOperatorKind::Plus => "+",
-                super::ASTBinaryOperatorKind::Minus => "-",
-                super::ASTBinaryOperatorKind::Multiply => "*",
-                super::ASTBinaryOperatorKind::Divide => "/",
-                super::ASTBinaryOperatorKind::EqualTo => "==",
-                super::ASTBinaryOperatorKind::NotEqualTo => "!=",
-                super::ASTBinaryOperatorKind::LogicAND => "&&",
-                super::ASTBinaryOperatorKind::LogicOR => "||",
-                super::ASTBinaryOperatorKind::GreaterThan => ">",
-                super::ASTBinaryOperatorKind::GreaterThanOrEqual => ">=",
-                super::ASTBinaryOperatorKind::LessThan => "<",
-                super::ASTBinaryOperatorKind::LessThanOrEqual => "<=",
-                super::ASTBinaryOperatorKind::BitwiseOR => "|",
-                super::ASTBinaryOperatorKind::BitwiseAND => "&",
-                super::ASTBinaryOperatorKind::BitwiseXOR => "^",
-            }
-        );
-        self.print(&var_name, &color::Yellow);
-    }
-
     fn visit_error(&mut self, span: &super::TextSpan) {
         self.print(&format!("Error: {:?}", span), &color::Red);
     }
 
-    fn visit_integer(&mut self, integer: &i64) {
+    fn visit_integer(&mut self, integer: &i128) {
         self.print(&format!("Integer: {}", integer), &Self::TEXT_COLOR);
     }
 
     fn visit_float(&mut self, float: &f64) {
         self.print(&format!("Float: {}", float), &Self::TEXT_COLOR);
     }
+
+    fn visit_string(&mut self, string: &str) {
+        self.print(&format!("String: {:?}", string), &Self::TEXT_COLOR);
+    }
+
+    fn visit_character(&mut self, character: &char) {
+        self.print(&format!("Character: {:?}", character), &Self::TEXT_COLOR);
+    }
+
+    fn visit_boolean(&mut self, boolean: &bool) {
+        self.print(&format!("Boolean: {}", boolean), &Self::TEXT_COLOR);
+    }
+
+    fn visit_null(&mut self) {
+        self.print("Null", &Self::TEXT_COLOR);
+    }
+}
+
+// Kind of source text, picks the color of the highlight printer and the
+// semantic token type of the language server
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Highlight {
+    Keyword,
+    Type,
+    Function,
+    Variable,
+    Number,
+    String,
+    // `true`, `false` and `null`
+    Constant,
+    Comment,
+    // operators and punctuation
+    Text,
+}
+
+impl Highlight {
+    // Kind of a token that is told by the token alone, identifiers are
+    // classified by the declaration they refer to
+    pub fn of_token(kind: &TokenKind) -> Option<Highlight> {
+        let highlight = match kind {
+            TokenKind::Let
+            | TokenKind::Var
+            | TokenKind::Func
+            | TokenKind::Return
+            | TokenKind::If
+            | TokenKind::Else
+            | TokenKind::While
+            | TokenKind::For
+            | TokenKind::In
+            | TokenKind::Break
+            | TokenKind::Continue
+            | TokenKind::Struct
+            | TokenKind::Module
+            | TokenKind::Import
+            | TokenKind::SelfKeyword => Highlight::Keyword,
+            TokenKind::I8
+            | TokenKind::I16
+            | TokenKind::I32
+            | TokenKind::I64
+            | TokenKind::U8
+            | TokenKind::U16
+            | TokenKind::U32
+            | TokenKind::U64
+            | TokenKind::F32
+            | TokenKind::F64
+            | TokenKind::Bool
+            | TokenKind::Char
+            | TokenKind::Str
+            | TokenKind::Void => Highlight::Type,
+            TokenKind::Integer(_) | TokenKind::Floating(_) => Highlight::Number,
+            TokenKind::String(_) | TokenKind::Character(_) => Highlight::String,
+            TokenKind::True | TokenKind::False | TokenKind::Null => Highlight::Constant,
+            TokenKind::SingleLineComment(_) | TokenKind::MultiLineComment(_) => Highlight::Comment,
+            TokenKind::Identifier | TokenKind::Whitespace | TokenKind::Bad | TokenKind::Eof => {
+                return None
+            }
+            _ => Highlight::Text,
+        };
+        Some(highlight)
+    }
+
+    fn color(self) -> String {
+        match self {
+            Highlight::Keyword => Fg(color::Green).to_string(),
+            Highlight::Type | Highlight::Number | Highlight::Constant => {
+                Fg(color::Cyan).to_string()
+            }
+            Highlight::Function => Fg(color::Yellow).to_string(),
+            Highlight::Variable => Fg(color::LightWhite).to_string(),
+            Highlight::String => Fg(color::LightGreen).to_string(),
+            Highlight::Comment => Fg(color::LightBlack).to_string(),
+            Highlight::Text => Fg(color::White).to_string(),
+        }
+    }
 }
 
 pub struct ASTHiglightPrinter {
     indent: usize,
     result: String,
+    // comments and blank lines printed between the statements
+    trivia: Trivia,
 }
 
 impl ASTHiglightPrinter {
     const INDENATION: usize = 2;
 
-    const KEYWORD_COLOR: color::Green = color::Green;
-    const TYPE_COLOR: color::Cyan = color::Cyan;
-    const OPERATOR_COLOR: color::Green = color::Green;
-    const TEXT_COLOR: color::White = color::White;
-
-    const INTEGER_COLOR: color::Cyan = color::Cyan;
-    const FLOAT_COLOR: color::Cyan = color::Cyan;
-    const LET_COLOR: color::Green = color::Green;
-    const FUNC_COLOR: color::Green = color::Green;
-    const FUNC_CALL_COLOR: color::Yellow = color::Yellow;
-    const FUNC_NAME_COLOR: color::Yellow = color::Yellow;
-    const VARIABLE_COLOR: color::LightWhite = color::LightWhite;
-
-    pub fn new() -> Self {
+    pub fn with_trivia(trivia: Trivia) -> Self {
         Self {
             indent: 0,
             result: "".to_string(),
+            trivia,
         }
     }
 
-    pub fn print_result(&self) {
-        let decoration = "=".repeat(80) + "\n";
-        println!(
-            "{decoration}Highlighted Source:\n{decoration}{}\n{}{decoration}",
-            self.result
-                .lines()
-                .enumerate()
-                .map(|(i, line)| format!("{:3} │ {}", i, line))
-                .collect::<Vec<String>>()
-                .join("\n"),
-            Fg(White)
-        );
+    // Prints the comments after the last statement
+    pub fn finish(&mut self) {
+        self.print_comments_before(usize::MAX);
+    }
+
+    // Regenerated source, colored like the printer was constructed
+    pub fn result(&self) -> &str {
+        &self.result
     }
 
     fn add_whitespace(&mut self) {
         self.print(" ");
     }
 
+    // Line breaks are left uncolored, so the printer can tell where a line starts
     fn add_newline(&mut self) {
-        self.print("\n");
+        self.result.push('\n');
     }
 
     fn add_semicolon(&mut self) {
@@ -392,13 +684,6 @@ impl ASTHiglightPrinter {
         self.indent -= Self::INDENATION;
     }
 
-    fn print_indent(&mut self) {
-        self.result.push_str(&format!(
-            "{}{}",
-            " ".repeat(self.indent),
-            color::Fg(color::Reset)
-        ));
-    }
     fn print_with_indent(&mut self, text: &str) {
         self.result.push_str(&format!(
             "{}{}{}",
@@ -413,17 +698,142 @@ impl ASTHiglightPrinter {
     }
 
     fn visit_idenifier(&mut self, identifier: &String) {
-        self.print(&format!("{}{}", Fg(Self::TEXT_COLOR), identifier));
+        self.print(&format!("{}{}", Highlight::Text.color(), identifier));
+    }
+
+    fn previous_line(&self) -> String {
+        let result = self.result.strip_suffix('\n').unwrap_or(&self.result);
+        strip_colors(result.rsplit('\n').next().unwrap_or_default())
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.result.is_empty() || self.result.ends_with('\n')
+    }
+
+    // Comments between statements are printed at the start of a line, the
+    // ones behind code are appended to the previous line
+    fn print_comments_before(&mut self, position: usize) {
+        if !self.at_line_start() {
+            return;
+        }
+        while let Some(comment) = self.trivia.comments.front() {
+            if comment.start >= position {
+                break;
+            }
+            let comment = self.trivia.comments.pop_front().unwrap();
+            if comment.trailing && !self.result.is_empty() {
+                self.result.pop();
+                self.print(&format!(" {}{}", Highlight::Comment.color(), comment.text));
+            } else {
+                self.print_blank_line(comment.start);
+                self.print_with_indent(&format!("{}{}", Highlight::Comment.color(), comment.text));
+            }
+            self.add_newline();
+        }
+    }
+
+    // Comments in the middle of a line stay where they were written, a `//`
+    // comment ends the line and the code continues on the next one at `indent`
+    fn print_inline_comments_before(&mut self, position: usize, indent: usize) {
+        while let Some(comment) = self.trivia.comments.front() {
+            if comment.start >= position {
+                break;
+            }
+            let comment = self.trivia.comments.pop_front().unwrap();
+            if !self.previous_line().ends_with(' ') {
+                self.add_whitespace();
+            }
+            self.print(&format!("{}{}", Highlight::Comment.color(), comment.text));
+            if comment.text.starts_with("//") {
+                self.add_newline();
+                self.print(&" ".repeat(indent));
+            } else {
+                self.add_whitespace();
+            }
+        }
+    }
+
+    // Keeps a blank line of the source, except at the start of a block
+    fn print_blank_line(&mut self, position: usize) {
+        if self.trivia.blank_lines.contains(&position)
+            && !self.result.is_empty()
+            && !self.previous_line().ends_with('{')
+        {
+            self.add_newline();
+        }
+    }
+
+    // `a += 1` is parsed as `a = a + 1`, where the operator keeps the `+=` token
+    fn compound_assignment(expr: &ASTExpression) -> Option<(&str, &ASTExpression)> {
+        let ASTExpressionKind::Binary(binary) = &expr.kind else {
+            return None;
+        };
+        let operator = &binary.operator.token.span.literal;
+        let is_compound =
+            operator.ends_with('=') && !matches!(operator.as_str(), "==" | "!=" | "<=" | ">=");
+        is_compound.then_some((operator, &binary.right))
+    }
+
+    fn visit_assigned_expression(&mut self, expr: &ASTExpression) {
+        match Self::compound_assignment(expr) {
+            Some((operator, right)) => {
+                self.print(&format!(" {}{} ", Highlight::Text.color(), operator));
+                self.visit_expression(right);
+            }
+            None => {
+                self.print(&format!("{} = ", Highlight::Text.color()));
+                self.visit_expression(expr);
+            }
+        }
     }
 }
 
 impl ASTVisitor for ASTHiglightPrinter {
+    fn visit_expression(&mut self, expr: &ASTExpression) {
+        self.print_inline_comments_before(expr.span.start, self.indent + Self::INDENATION);
+        let literal = &expr.span.literal;
+        // literals keep their spelling, `0x1F` is not printed as `31`
+        match &expr.kind {
+            ASTExpressionKind::IntegerLiteral(_) | ASTExpressionKind::FloatingLiteral(_)
+                if !literal.is_empty() =>
+            {
+                self.print(&format!("{}{}", Highlight::Number.color(), literal));
+            }
+            ASTExpressionKind::String(_) | ASTExpressionKind::Character(_)
+                if !literal.is_empty() =>
+            {
+                self.print(&format!("{}{}", Highlight::String.color(), literal));
+            }
+            _ => self.do_visit_expression(expr),
+        }
+    }
+
     fn visit_statement(&mut self, statement: &super::ASTStatement) {
-        self.do_visit_statement(statement);
+        self.print_comments_before(statement.span.start);
+        if !self.at_line_start() {
+            // the block of an if, else or loop continues the line
+            self.do_visit_statement(statement);
+            return;
+        }
+        self.print_blank_line(statement.span.start);
+        match &statement.kind {
+            ASTStatementKind::Expr(expr) => {
+                self.print_with_indent("");
+                self.visit_expression(expr);
+                self.add_semicolon();
+                self.add_newline();
+            }
+            ASTStatementKind::Compound(compound) => {
+                self.print_with_indent("");
+                self.visit_compound_statement(compound);
+                self.add_newline();
+            }
+            _ => self.do_visit_statement(statement),
+        }
     }
 
     fn visit_return_statement(&mut self, statement: &super::ASTReturnStatement) {
-        self.print_with_indent(&format!("{}return", Fg(Self::LET_COLOR)));
+        self.print_with_indent(&format!("{}return", Highlight::Keyword.color()));
         self.add_whitespace();
         self.visit_expression(&statement.expr);
         self.add_semicolon();
@@ -431,16 +841,16 @@ impl ASTVisitor for ASTHiglightPrinter {
     }
 
     fn visit_let_statement(&mut self, statement: &super::ASTLetStatement) {
-        self.print_with_indent(&format!("{}let", Fg(Self::LET_COLOR)));
+        self.print_with_indent(&format!("{}let", Highlight::Keyword.color()));
         self.add_whitespace();
         self.visit_idenifier(&statement.identifier.span.literal);
         self.print(&format!(
             ": {}{}",
-            Fg(Self::TYPE_COLOR),
-            statement.data_type.span.literal
+            Highlight::Type.color(),
+            statement.data_type
         ));
         self.add_whitespace();
-        self.print(&format!("{}=", Fg(Self::TEXT_COLOR)));
+        self.print(&format!("{}=", Highlight::Text.color()));
         self.add_whitespace();
         self.visit_expression(&statement.initializer);
         self.add_semicolon();
@@ -448,16 +858,16 @@ impl ASTVisitor for ASTHiglightPrinter {
     }
 
     fn visit_var_statement(&mut self, statement: &super::ASTVarStatement) {
-        self.print_with_indent(&format!("{}var", Fg(Self::LET_COLOR)));
+        self.print_with_indent(&format!("{}var", Highlight::Keyword.color()));
         self.add_whitespace();
         self.visit_idenifier(&statement.identifier.span.literal);
         self.print(&format!(
             ": {}{}",
-            Fg(Self::TYPE_COLOR),
-            statement.data_type.span.literal
+            Highlight::Type.color(),
+            statement.data_type
         ));
         self.add_whitespace();
-        self.print(&format!("{}=", Fg(Self::TEXT_COLOR)));
+        self.print(&format!("{}=", Highlight::Text.color()));
         self.add_whitespace();
         self.visit_expression(&statement.initializer);
         self.add_semicolon();
@@ -465,21 +875,24 @@ impl ASTVisitor for ASTHiglightPrinter {
     }
 
     fn visit_compound_statement(&mut self, statement: &super::ASTCompoundStatement) {
-        self.print(&format!("{}{}", Fg(Self::TEXT_COLOR), '{'));
+        // comments in front of the `{`, e.g. behind an `if` condition
+        self.print_inline_comments_before(statement.open_brace.span.start, self.indent);
+        self.print(&format!("{}{}", Highlight::Text.color(), '{'));
         self.add_newline();
         self.increase_indentation();
         for statement in statement.statements.iter() {
             self.visit_statement(statement);
         }
+        self.print_comments_before(statement.close_brace.span.start);
         self.decrease_indentation();
-        self.print_with_indent(&format!("{}{}", Fg(Self::TEXT_COLOR), '}'));
+        self.print_with_indent(&format!("{}{}", Highlight::Text.color(), '}'));
     }
 
     fn visit_if_statement(&mut self, statement: &super::ASTIfStatement) {
         self.print_with_indent(&format!(
             "{}if{} ",
-            Fg(Self::KEYWORD_COLOR),
-            Fg(Self::TEXT_COLOR),
+            Highlight::Keyword.color(),
+            Highlight::Text.color(),
         ));
         self.visit_expression(&statement.condition);
         self.add_whitespace();
@@ -487,11 +900,21 @@ impl ASTVisitor for ASTHiglightPrinter {
         // self.increase_indentation();
         self.visit_statement(&statement.then_branch);
         if let Some(else_branch) = &statement.else_branch {
-            self.print(&format!(
-                "{} else{} ",
-                Fg(Self::KEYWORD_COLOR),
-                Fg(Self::TEXT_COLOR),
-            ));
+            self.print_inline_comments_before(else_branch.else_keyword.span.start, self.indent);
+            // a `//` comment behind the then branch moves the else to its own line
+            if self.previous_line().trim().is_empty() {
+                self.print(&format!(
+                    "{}else{} ",
+                    Highlight::Keyword.color(),
+                    Highlight::Text.color()
+                ));
+            } else {
+                self.print(&format!(
+                    "{} else{} ",
+                    Highlight::Keyword.color(),
+                    Highlight::Text.color(),
+                ));
+            }
             self.visit_statement(&else_branch.else_branch);
         }
         self.add_newline();
@@ -500,14 +923,19 @@ impl ASTVisitor for ASTHiglightPrinter {
     fn visit_for_loop_statement(&mut self, statement: &super::ASTForStatement) {
         self.print_with_indent(&format!(
             "{}for{} {} in",
-            Fg(Self::KEYWORD_COLOR),
-            Fg(Self::TEXT_COLOR),
+            Highlight::Keyword.color(),
+            Highlight::Text.color(),
             statement.loop_variable.span.literal
         ));
         self.add_whitespace();
-        self.visit_expression(&statement.range.0);
-        self.print("..");
-        self.visit_expression(&statement.range.1);
+        match &statement.iterable {
+            super::ASTForIterable::Range(start, end) => {
+                self.visit_expression(start);
+                self.print("..");
+                self.visit_expression(end);
+            }
+            super::ASTForIterable::Array(array) => self.visit_expression(array),
+        }
         self.add_whitespace();
         self.visit_statement(&statement.body);
         self.add_newline();
@@ -516,8 +944,8 @@ impl ASTVisitor for ASTHiglightPrinter {
     fn visit_while_loop_statement(&mut self, statement: &super::ASTWhileStatement) {
         self.print_with_indent(&format!(
             "{}while{}",
-            Fg(Self::KEYWORD_COLOR),
-            Fg(Self::TEXT_COLOR),
+            Highlight::Keyword.color(),
+            Highlight::Text.color(),
         ));
         self.add_whitespace();
         self.visit_expression(&statement.condition);
@@ -526,88 +954,317 @@ impl ASTVisitor for ASTHiglightPrinter {
         self.add_newline();
     }
 
+    fn visit_break_statement(&mut self, _statement: &super::ASTBreakStatement) {
+        self.print_with_indent(&format!(
+            "{}break{}",
+            Highlight::Keyword.color(),
+            Highlight::Text.color(),
+        ));
+        self.add_semicolon();
+        self.add_newline();
+    }
+
+    fn visit_continue_statement(&mut self, _statement: &super::ASTContinueStatement) {
+        self.print_with_indent(&format!(
+            "{}continue{}",
+            Highlight::Keyword.color(),
+            Highlight::Text.color(),
+        ));
+        self.add_semicolon();
+        self.add_newline();
+    }
+
+    fn visit_module_statement(&mut self, statement: &super::ASTModuleStatement) {
+        self.print_with_indent(&format!(
+            "{}{} {}{}",
+            Highlight::Keyword.color(),
+            statement.keyword.span.literal,
+            Highlight::Text.color(),
+            statement.identifier.span.literal,
+        ));
+        self.add_semicolon();
+        self.add_newline();
+    }
+
+    fn visit_import_statement(&mut self, statement: &super::ASTImportStatement) {
+        self.print_with_indent(&format!(
+            "{}{} {}{}",
+            Highlight::Keyword.color(),
+            statement.keyword.span.literal,
+            Highlight::Text.color(),
+            statement.identifier.span.literal,
+        ));
+        self.add_semicolon();
+        self.add_newline();
+    }
+
     fn visit_funtion_statement(&mut self, function: &super::ASTFunctionStatement) {
         self.print_with_indent(&format!(
             "{}func {}{}{}(",
-            Fg(Self::FUNC_COLOR),
-            Fg(Self::FUNC_NAME_COLOR),
+            Highlight::Keyword.color(),
+            Highlight::Function.color(),
             function.identifier.span.literal,
-            Fg(Self::TEXT_COLOR),
+            Highlight::Text.color(),
         ));
-        for (i, arg) in function.arguments.iter().enumerate() {
-            if i != 0 {
-                self.print(&format!("{},", Fg(Self::TEXT_COLOR)));
-                self.add_whitespace();
+        let mut parameters = Vec::new();
+        if let Some(receiver) = &function.receiver {
+            parameters.push((
+                receiver.span.start,
+                format!("{}{}", Highlight::Keyword.color(), receiver.span.literal),
+            ));
+        }
+        for arg in function.arguments.iter() {
+            parameters.push((
+                arg.identifier.span.start,
+                format!(
+                    "{}{}: {}{}",
+                    Highlight::Text.color(),
+                    arg.identifier.span.literal,
+                    Highlight::Type.color(),
+                    arg.data_type,
+                ),
+            ));
+        }
+        let end = function.right_paren.span.start;
+        if self
+            .trivia
+            .comments
+            .front()
+            .is_some_and(|comment| comment.start < end)
+        {
+            // one parameter per line, so the comments stay behind their parameters
+            self.add_newline();
+            self.increase_indentation();
+            let count = parameters.len();
+            for (i, (start, parameter)) in parameters.into_iter().enumerate() {
+                self.print_comments_before(start);
+                self.print_with_indent(&parameter);
+                if i + 1 != count {
+                    self.print(&format!("{},", Highlight::Text.color()));
+                }
+                self.add_newline();
             }
+            self.print_comments_before(end);
+            self.decrease_indentation();
+            self.print_with_indent("");
+        } else {
+            let parameters: Vec<String> = parameters.into_iter().map(|(_, p)| p).collect();
+            self.print(&parameters.join(&format!("{}, ", Highlight::Text.color())));
+        }
+
+        self.print(&format!("{}) ", Highlight::Text.color()));
+        if !function.return_type.is_void() {
+            self.print(&format!("{}-> ", Highlight::Text.color()));
+            self.print_inline_comments_before(function.return_type.span.start, self.indent);
             self.print(&format!(
-                "{}{}: {}{}",
-                Fg(Self::TEXT_COLOR),
-                arg.identifier.span.literal,
-                Fg(Self::TYPE_COLOR),
-                arg.data_type.span.literal,
+                "{}{} ",
+                Highlight::Type.color(),
+                function.return_type
             ));
         }
-
-        self.print(&format!("{}) ", Fg(Self::TEXT_COLOR)));
         if let super::ASTStatementKind::Compound(statement) = &function.body.kind {
             self.visit_compound_statement(statement);
         }
         self.add_newline();
     }
 
-    fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {
+    fn visit_struct_statement(&mut self, statement: &super::ASTStructStatement) {
         self.print_with_indent(&format!(
-            "{}{}{} = ",
-            Fg(Self::VARIABLE_COLOR),
-            expr.identifier.span.literal,
-            Fg(Self::TEXT_COLOR)
+            "{}struct {}{}{} ",
+            Highlight::Keyword.color(),
+            Highlight::Type.color(),
+            statement.identifier.span.literal,
+            Highlight::Text.color(),
         ));
-        self.visit_expression(&expr.expr);
-        self.add_semicolon();
+        self.print(&format!("{}{}", Highlight::Text.color(), '{'));
         self.add_newline();
+        self.increase_indentation();
+        for field in statement.fields.iter() {
+            self.print_comments_before(field.identifier.span.start);
+            self.print_blank_line(field.identifier.span.start);
+            self.print_with_indent(&format!(
+                "{}{}: {}{}{},",
+                Highlight::Text.color(),
+                field.identifier.span.literal,
+                Highlight::Type.color(),
+                field.data_type,
+                Highlight::Text.color(),
+            ));
+            self.add_newline();
+        }
+        for method in statement.methods.iter() {
+            self.print_comments_before(method.keyword.span.start);
+            self.print_blank_line(method.keyword.span.start);
+            self.visit_funtion_statement(method);
+        }
+        self.print_comments_before(statement.close_brace.span.start);
+        self.decrease_indentation();
+        self.print_with_indent(&format!("{}{}", Highlight::Text.color(), '}'));
+        self.add_newline();
+    }
+
+    fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {
+        self.print(&format!(
+            "{}{}",
+            Highlight::Variable.color(),
+            expr.identifier.span.literal,
+        ));
+        self.visit_assigned_expression(&expr.expr);
     }
 
     fn visit_function_call_expression(&mut self, expr: &super::ASTFunctionCallExpression) {
         self.print(&format!(
             "{}{}{}(",
-            Fg(Self::FUNC_CALL_COLOR),
-            expr.identifier(),
-            Fg(Self::TEXT_COLOR)
+            Highlight::Function.color(),
+            expr.qualified_name(),
+            Highlight::Text.color()
         ));
 
         for (i, arg) in expr.arguments.iter().enumerate() {
             if i != 0 {
-                self.print(&format!("{},", Fg(Self::TEXT_COLOR)));
+                self.print(&format!("{},", Highlight::Text.color()));
                 self.add_whitespace();
             }
             self.visit_expression(arg);
         }
-        self.print(&format!("{})", Fg(Self::TEXT_COLOR)));
+        self.print(&format!("{})", Highlight::Text.color()));
+    }
+
+    fn visit_method_call_expression(&mut self, expr: &super::ASTMethodCallExpression) {
+        self.visit_expression(&expr.object);
+        self.print(&format!(
+            "{}.{}{}{}(",
+            Highlight::Text.color(),
+            Highlight::Function.color(),
+            expr.method.span.literal,
+            Highlight::Text.color()
+        ));
+
+        for (i, arg) in expr.arguments.iter().enumerate() {
+            if i != 0 {
+                self.print(&format!("{},", Highlight::Text.color()));
+                self.add_whitespace();
+            }
+            self.visit_expression(arg);
+        }
+        self.print(&format!("{})", Highlight::Text.color()));
     }
 
     fn visit_variable_expression(&mut self, expr: &super::ASTVariableExpression) {
         self.print(&format!(
             "{}{}",
-            Fg(Self::VARIABLE_COLOR),
+            Highlight::Variable.color(),
             expr.identifier()
         ));
     }
 
+    fn visit_struct_literal_expression(&mut self, expr: &super::ASTStructLiteralExpression) {
+        self.print(&format!(
+            "{}{}{} {} ",
+            Highlight::Type.color(),
+            expr.identifier.span.literal,
+            Highlight::Text.color(),
+            '{'
+        ));
+        for (i, field) in expr.fields.iter().enumerate() {
+            if i != 0 {
+                self.print(&format!("{},", Highlight::Text.color()));
+                self.add_whitespace();
+            }
+            self.print(&format!(
+                "{}{}: ",
+                Highlight::Text.color(),
+                field.identifier.span.literal
+            ));
+            self.visit_expression(&field.expr);
+        }
+        self.print(&format!("{} {}", Highlight::Text.color(), '}'));
+    }
+
+    fn visit_field_access_expression(&mut self, expr: &super::ASTFieldAccessExpression) {
+        self.visit_expression(&expr.object);
+        self.print(&format!(
+            "{}.{}{}",
+            Highlight::Text.color(),
+            Highlight::Variable.color(),
+            expr.field.span.literal
+        ));
+    }
+
+    fn visit_field_assignment_expression(&mut self, expr: &super::ASTFieldAssignmentExpression) {
+        self.visit_field_access_expression(&expr.target);
+        self.visit_assigned_expression(&expr.expr);
+    }
+
+    fn visit_array_literal_expression(&mut self, expr: &super::ASTArrayLiteralExpression) {
+        self.print(&format!("{}[", Highlight::Text.color()));
+        for (i, element) in expr.elements.iter().enumerate() {
+            if i != 0 {
+                self.print(&format!("{},", Highlight::Text.color()));
+                self.add_whitespace();
+            }
+            self.visit_expression(element);
+        }
+        self.print(&format!("{}]", Highlight::Text.color()));
+    }
+
+    fn visit_index_expression(&mut self, expr: &super::ASTIndexExpression) {
+        self.visit_expression(&expr.object);
+        self.print(&format!("{}[", Highlight::Text.color()));
+        self.visit_expression(&expr.index);
+        self.print(&format!("{}]", Highlight::Text.color()));
+    }
+
+    fn visit_index_assignment_expression(&mut self, expr: &super::ASTIndexAssignmentExpression) {
+        self.visit_index_expression(&expr.target);
+        self.visit_assigned_expression(&expr.expr);
+    }
+
     fn visit_unary_expression(&mut self, expr: &super::ASTUnaryExpression) {
         self.print(&format!(
             "{}{}",
-            Fg(Self::TEXT_COLOR),
+            Highlight::Text.color(),
             expr.operator.token.span.literal
         ));
         self.visit_expression(&expr.expr);
     }
 
+    fn visit_address_of_expression(&mut self, expr: &super::ASTAddressOfExpression) {
+        self.print(&format!(
+            "{}{}",
+            Highlight::Text.color(),
+            expr.operator.span.literal
+        ));
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_dereference_expression(&mut self, expr: &super::ASTDereferenceExpression) {
+        self.print(&format!(
+            "{}{}",
+            Highlight::Text.color(),
+            expr.operator.span.literal
+        ));
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_dereference_assignment_expression(
+        &mut self,
+        expr: &super::ASTDereferenceAssignmentExpression,
+    ) {
+        self.visit_dereference_expression(&expr.target);
+        self.visit_assigned_expression(&expr.expr);
+    }
+
     fn visit_binary_expression(&mut self, expr: &super::ASTBinaryExpression) {
         self.visit_expression(&expr.left);
         self.add_whitespace();
+        self.print_inline_comments_before(
+            expr.operator.token.span.start,
+            self.indent + Self::INDENATION,
+        );
         self.print(&format!(
             "{}{}",
-            Fg(Self::TEXT_COLOR),
+            Highlight::Text.color(),
             expr.operator.token.span.literal
         ));
         self.add_whitespace();
@@ -615,39 +1272,30 @@ impl ASTVisitor for ASTHiglightPrinter {
     }
 
     fn visit_parenthesised_expression(&mut self, expr: &super::ASTParenthesizedExpression) {
-        self.print(&format!("{}(", Fg(Self::TEXT_COLOR)));
+        self.print(&format!("{}(", Highlight::Text.color()));
         self.visit_expression(&expr.expr);
-        self.print(&format!("{})", Fg(Self::TEXT_COLOR)));
+        self.print(&format!("{})", Highlight::Text.color()));
     }
 
-    fn visit_binary_operator(&mut self, op: &super::ASTBinaryOperator) {
-        self.print(&format!(
-            "{}{}",
-            Fg(Self::TEXT_COLOR),
-            match op.kind {
-                super::ASTBinaryOperatorKind::Plus => "+",
-                super::ASTBinaryOperatorKind::Minus => "-",
-                super::ASTBinaryOperatorKind::Multiply => "*",
-                super::ASTBinaryOperatorKind::Divide => "/",
-                super::ASTBinaryOperatorKind::EqualTo => "==",
-                super::ASTBinaryOperatorKind::NotEqualTo => "!=",
-                super::ASTBinaryOperatorKind::LogicAND => "&&",
-                super::ASTBinaryOperatorKind::LogicOR => "||",
-                super::ASTBinaryOperatorKind::GreaterThan => ">",
-                super::ASTBinaryOperatorKind::GreaterThanOrEqual => ">=",
-                super::ASTBinaryOperatorKind::LessThan => "<",
-                super::ASTBinaryOperatorKind::LessThanOrEqual => "<=",
-                super::ASTBinaryOperatorKind::BitwiseOR => "|",
-                super::ASTBinaryOperatorKind::BitwiseAND => "&",
-                super::ASTBinaryOperatorKind::BitwiseXOR => "^",
-            }
-        ));
-    }
-
-    fn visit_integer(&mut self, integer: &i64) {
-        self.print(&format!("{}{}", Fg(Self::INTEGER_COLOR), integer));
+    fn visit_integer(&mut self, integer: &i128) {
+        self.print(&format!("{}{}", Highlight::Number.color(), integer));
     }
+    // `{:?}` keeps the fraction of `2.0`, which would be read back as an integer
     fn visit_float(&mut self, float: &f64) {
-        self.print(&format!("{}{}", Fg(Self::FLOAT_COLOR), float));
+        self.print(&format!("{}{:?}", Highlight::Number.color(), float));
+    }
+    // escapes are printed the way they are written in the source
+    fn visit_string(&mut self, string: &str) {
+        self.print(&format!("{}{:?}", Highlight::String.color(), string));
+    }
+    fn visit_character(&mut self, character: &char) {
+        self.print(&format!("{}{:?}", Highlight::String.color(), character));
+    }
+    fn visit_boolean(&mut self, boolean: &bool) {
+        self.print(&format!("{}{}", Highlight::Constant.color(), boolean));
+    }
+
+    fn visit_null(&mut self) {
+        self.print(&format!("{}null", Highlight::Constant.color()));
     }
 }
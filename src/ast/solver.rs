@@ -1,187 +1,1363 @@
-use std::{collections::HashMap, ops::Not};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
+use crate::diagnostics::DiagnosticsColletionCell;
+
+use super::lexer::TextSpan;
+use super::memory::{Address, Memory, PathSegment};
+use super::type_checker::Type;
+use super::value::Value;
 use super::{
-    ASTBinaryOperator, ASTBinaryOperatorKind, ASTFunctionStatement, ASTReturnStatement, ASTVisitor,
+    ASTBinaryOperatorKind, ASTExpression, ASTExpressionKind, ASTForIterable, ASTForStatement,
+    ASTFunctionStatement, ASTIndexExpression, ASTReturnStatement, ASTStructStatement,
+    ASTUnaryOperatorKind, ASTVisitor, Ast,
 };
 
-type Scope = HashMap<String, f64>;
+// Pending jump that unwinds the statements currently executed
+#[derive(Debug, PartialEq)]
+enum ControlFlow {
+    // left at the enclosing function call or ends the program at top-level
+    Return,
+    // runtime error, stops the execution of all remaining statements
+    Error,
+    // left at the innermost loop, which stops or starts its next iteration
+    Break,
+    Continue,
+}
+
+// Variables of a scope in declaration order, shadowed ones stay alive until
+// the scope ends
+type Scope = Vec<(String, Address)>;
+type Variables = HashMap<String, Value>;
+
+// Functions and global variables of a module that is not executed at the moment
+struct ModuleState {
+    functions: HashMap<String, ASTFunctionStatement>,
+    globals: Scope,
+}
+
 pub struct ASTSolver {
-    result: Option<f64>,
+    result: Value,
     scopes: Vec<Scope>,
+    memory: Memory,
     functions: HashMap<String, ASTFunctionStatement>,
+    structs: HashMap<String, ASTStructStatement>,
+    // module that declares a struct, its methods are executed in there
+    struct_modules: HashMap<String, String>,
+    // module that is executed at the moment, its state lives in the fields above
+    module: String,
+    modules: HashMap<String, ModuleState>,
+    diagnostics: DiagnosticsColletionCell,
+    control_flow: Option<ControlFlow>,
+    max_loop_iterations: usize,
+    // calls that have not returned yet
+    call_depth: usize,
+    max_call_depth: usize,
 }
 
 impl ASTSolver {
-    pub fn new() -> Self {
+    pub const DEFAULT_MAX_LOOP_ITERATIONS: usize = 1_000_000;
+    // every call recurses on the native stack, `STACK_SIZE` in main.rs fits this many
+    pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+    pub fn new(diagnostics: DiagnosticsColletionCell) -> Self {
         Self {
             scopes: vec![Scope::new()],
-            result: None,
+            memory: Memory::new(),
+            result: Value::Void,
             functions: HashMap::new(),
+            structs: HashMap::new(),
+            struct_modules: HashMap::new(),
+            module: String::new(),
+            modules: HashMap::new(),
+            diagnostics,
+            control_flow: None,
+            max_loop_iterations: Self::DEFAULT_MAX_LOOP_ITERATIONS,
+            call_depth: 0,
+            max_call_depth: Self::DEFAULT_MAX_CALL_DEPTH,
         }
     }
 
-    pub fn print_result(&self) {
-        println!("Solver result: {}", self.result.unwrap());
+    pub fn set_max_loop_iterations(&mut self, max_loop_iterations: usize) {
+        self.max_loop_iterations = max_loop_iterations;
     }
 
-    fn enter_scope(&mut self, scope_variables: Scope) {
-        self.scopes.push(scope_variables);
-        // self.active_scope += 1;
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
     }
 
+    // Runs the top-level statements of a module in its own global scope
+    pub fn run_module(&mut self, module: &str, ast: &Ast) {
+        self.module = module.to_string();
+        self.diagnostics.borrow_mut().set_module(module);
+        self.scopes = vec![Scope::new()];
+        ast.visit(self);
+        let state = ModuleState {
+            functions: std::mem::take(&mut self.functions),
+            globals: self.scopes.swap_remove(0),
+        };
+        self.modules.insert(module.to_string(), state);
+        // no module is executed until the next one runs
+        self.module.clear();
+    }
+
+    // Runs a snippet in the global scope left by the previous ones, a runtime
+    // error or a top-level return only ends the snippet itself. Returns the
+    // number of statements that ran to their end if the snippet was ended early.
+    pub fn run_snippet(&mut self, module: &str, ast: &Ast) -> Option<usize> {
+        self.module = module.to_string();
+        self.diagnostics.borrow_mut().set_module(module);
+        for (index, statement) in ast.statements.iter().enumerate() {
+            self.visit_statement(statement);
+            if self.control_flow.take().is_some() {
+                return Some(index);
+            }
+        }
+        None
+    }
+
+    // Calls `main` of the entry module after all modules ran, its return value
+    // is the result of the program
+    pub fn run_entry_point(&mut self, module: &str) {
+        let main = self.modules[module].functions["main"].clone();
+        self.call_function_in_module(module, &main, Variables::new(), &main.identifier.span);
+    }
+
+    pub fn result(&self) -> &Value {
+        &self.result
+    }
+
+    fn enter_scope(&mut self, scope_variables: Variables) {
+        let scope = scope_variables
+            .into_iter()
+            .map(|(identifier, value)| (identifier, self.memory.allocate(value)))
+            .collect();
+        self.scopes.push(scope);
+    }
+
+    // Frees the variables of the scope, pointers to them are dangling afterwards
     fn leave_scope(&mut self) {
-        self.scopes.pop();
-        // self.active_scope -= 1;
+        for (_, address) in self.scopes.pop().unwrap() {
+            self.memory.free(address);
+        }
     }
 
-    fn add_identifier_to_scope(&mut self, identifier: &String, value: f64) {
+    fn add_identifier_to_scope(&mut self, identifier: &str, value: Value) {
+        let address = self.memory.allocate(value);
         self.scopes
             .last_mut()
             .unwrap()
-            .insert(identifier.clone(), value);
+            .push((identifier.to_string(), address));
+    }
+
+    fn get_identifier_address(&self, identifier: &str) -> Option<Address> {
+        self.scopes.iter().rev().find_map(|scope| {
+            scope
+                .iter()
+                .rev()
+                .find(|(name, _)| name == identifier)
+                .map(|(_, address)| *address)
+        })
+    }
+
+    fn get_identifier_in_scope(&self, identifier: &str) -> Value {
+        self.get_identifier_address(identifier)
+            .and_then(|address| self.memory.get(&address))
+            .cloned()
+            .unwrap_or(Value::Void)
+    }
+
+    // Value stored at a field or element of the variable at the given address
+    fn get_place(&self, address: &Address, path: &[PathSegment]) -> Option<&Value> {
+        let mut value = self.memory.get(address)?;
+        for segment in path {
+            value = value.part(segment)?;
+        }
+        Some(value)
+    }
+
+    fn get_place_mut(&mut self, address: &Address, path: &[PathSegment]) -> Option<&mut Value> {
+        let mut value = self.memory.get_mut(address)?;
+        for segment in path {
+            value = value.part_mut(segment)?;
+        }
+        Some(value)
+    }
+
+    // Address and path of a variable, its fields and elements, like `p.a[1]`.
+    // Returns None if evaluating an index was interrupted.
+    fn evaluate_place(&mut self, expr: &ASTExpression) -> Option<(Address, Vec<PathSegment>)> {
+        match &expr.kind {
+            ASTExpressionKind::Variable(variable) => {
+                let address = self
+                    .get_identifier_address(variable.identifier())
+                    .expect("variables are checked by the symbol checker");
+                Some((address, Vec::new()))
+            }
+            ASTExpressionKind::FieldAccess(access) => {
+                let (address, mut path) = self.evaluate_place(&access.object)?;
+                path.push(PathSegment::Field(access.field.span.literal.clone()));
+                Some((address, path))
+            }
+            ASTExpressionKind::Index(index) => self.evaluate_element_place(index),
+            _ => unreachable!("places are checked by the symbol checker"),
+        }
     }
 
-    fn check_identifier_in_scope(&self, identifier: &String) -> bool {
-        for scope in self.scopes.iter().rev() {
-            if scope.contains_key(identifier) {
-                return true;
+    fn evaluate_element_place(
+        &mut self,
+        expr: &ASTIndexExpression,
+    ) -> Option<(Address, Vec<PathSegment>)> {
+        let (address, mut path) = self.evaluate_place(&expr.object)?;
+        self.visit_expression(&expr.index);
+        if self.is_unwinding() {
+            return None;
+        }
+        let index = self.result.clone();
+        let length = match self.get_place(&address, &path) {
+            Some(Value::Array(elements)) => elements.len(),
+            _ => unreachable!("indexed values are checked by the type checker"),
+        };
+        let element = self.element_index(&index, length, expr.index.span())?;
+        path.push(PathSegment::Index(element));
+        Some((address, path))
+    }
+
+    // Position of an element if the index is within the bounds of the array,
+    // otherwise the error is reported and stops the execution
+    fn element_index(&mut self, index: &Value, length: usize, span: &TextSpan) -> Option<usize> {
+        let index = index
+            .as_integer()
+            .expect("indices are checked by the type checker");
+        if index < 0 || index as usize >= length {
+            self.diagnostics
+                .borrow_mut()
+                .report_index_out_of_bounds(span.clone(), index, length);
+            self.abort();
+            return None;
+        }
+        Some(index as usize)
+    }
+
+    // Runs the loop body once with the given loop variable, false once the loop ends
+    fn run_loop_iteration(
+        &mut self,
+        statement: &ASTForStatement,
+        loop_variable: Value,
+        iterations: &mut usize,
+    ) -> bool {
+        if !self.count_loop_iteration(iterations, &statement.keyword) {
+            return false;
+        }
+
+        let mut scope = Variables::new();
+        scope.insert(statement.loop_variable.span.literal.clone(), loop_variable);
+        self.enter_scope(scope);
+        self.visit_statement(&statement.body);
+        self.leave_scope();
+        self.finish_loop_iteration()
+    }
+
+    // Address and path a pointer points to. Null and dangling pointers are
+    // reported and stop the execution. A pointer to a field dangles as well
+    // once the optional holding the struct is set to null.
+    fn resolve_pointer(
+        &mut self,
+        pointer: &Value,
+        span: &TextSpan,
+    ) -> Option<(Address, Vec<PathSegment>)> {
+        match pointer {
+            Value::Pointer { address, path, .. } if self.get_place(address, path).is_some() => {
+                Some((*address, path.clone()))
+            }
+            Value::Pointer { .. } => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_dangling_pointer(span.clone());
+                self.abort();
+                None
+            }
+            _ => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_null_dereference(span.clone());
+                self.abort();
+                None
             }
         }
-        return false;
     }
 
-    fn get_identifier_in_scope(&self, identifier: &String) -> Option<f64> {
-        for scope in self.scopes.iter().rev() {
-            if scope.contains_key(identifier) {
-                return scope.get(identifier).copied();
+    // Evaluates an initializer and converts it to the declared type
+    fn evaluate_declaration(&mut self, identifier: &str, data_type: &super::ASTType) {
+        let value = match Type::from_ast(data_type) {
+            Some(data_type) => self.result.cast(&data_type),
+            None => self.result.clone(),
+        };
+        self.add_identifier_to_scope(identifier, value);
+    }
+
+    fn abort(&mut self) {
+        self.control_flow = Some(ControlFlow::Error);
+        self.result = Value::Void;
+    }
+
+    fn is_unwinding(&self) -> bool {
+        self.control_flow.is_some()
+    }
+
+    // Consumes a break or continue at the end of a loop body and tells whether
+    // the loop runs its next iteration
+    fn finish_loop_iteration(&mut self) -> bool {
+        match self.control_flow {
+            None => true,
+            Some(ControlFlow::Continue) => {
+                self.control_flow = None;
+                true
+            }
+            Some(ControlFlow::Break) => {
+                self.control_flow = None;
+                false
+            }
+            Some(ControlFlow::Return | ControlFlow::Error) => false,
+        }
+    }
+
+    // Evaluates the arguments of a call into the scope of the called function.
+    // Returns None if the evaluation was interrupted.
+    fn evaluate_arguments(
+        &mut self,
+        arguments: &[super::ASTExpression],
+        function: &ASTFunctionStatement,
+    ) -> Option<Variables> {
+        let mut scope = Variables::new();
+        for (arg_expr, func_arg) in arguments.iter().zip(function.arguments.iter()) {
+            self.visit_expression(arg_expr);
+            if self.is_unwinding() {
+                return None;
+            }
+            let value = match Type::from_ast(&func_arg.data_type) {
+                Some(data_type) => self.result.cast(&data_type),
+                None => self.result.clone(),
+            };
+            scope.insert(func_arg.identifier.span.literal.clone(), value);
+        }
+        Some(scope)
+    }
+
+    // Calls a function declared in another module, which sees the globals and
+    // functions of that module. Modules never import each other in a cycle, so
+    // the called module is not executed at the moment.
+    fn call_function_in_module(
+        &mut self,
+        module: &str,
+        function: &ASTFunctionStatement,
+        arguments: Variables,
+        call: &TextSpan,
+    ) {
+        if module == self.module {
+            self.call_function(function, arguments, call);
+            return;
+        }
+        let callee = self.modules.remove(module).unwrap();
+        let caller = ModuleState {
+            functions: std::mem::replace(&mut self.functions, callee.functions),
+            globals: Scope::new(),
+        };
+        let caller_scopes = std::mem::replace(&mut self.scopes, vec![callee.globals]);
+        let caller_module = std::mem::replace(&mut self.module, module.to_string());
+        self.diagnostics.borrow_mut().set_module(module);
+
+        self.call_function(function, arguments, call);
+
+        self.diagnostics.borrow_mut().set_module(&caller_module);
+        self.module = caller_module;
+        let callee = ModuleState {
+            functions: std::mem::replace(&mut self.functions, caller.functions),
+            globals: std::mem::replace(&mut self.scopes, caller_scopes).swap_remove(0),
+        };
+        self.modules.insert(module.to_string(), callee);
+    }
+
+    // Runs the body of a function, unless the call nests deeper than the limit.
+    // The body sees its arguments and the globals, not the locals of the caller.
+    fn call_function(
+        &mut self,
+        function: &ASTFunctionStatement,
+        arguments: Variables,
+        call: &TextSpan,
+    ) {
+        if self.call_depth >= self.max_call_depth {
+            self.diagnostics
+                .borrow_mut()
+                .report_call_depth_exceeded(call.clone(), self.max_call_depth);
+            self.abort();
+            return;
+        }
+        self.call_depth += 1;
+        let caller_scopes = self.scopes.split_off(1);
+        self.enter_scope(arguments);
+
+        // todo: that check should be done before
+        if let super::ASTStatementKind::Compound(statement) = &function.body.kind {
+            for statement in statement.statements.iter() {
+                self.visit_statement(statement);
+            }
+        }
+        self.leave_scope();
+        self.scopes.extend(caller_scopes);
+        self.call_depth -= 1;
+
+        match self.control_flow {
+            Some(ControlFlow::Return) => self.control_flow = None,
+            Some(ControlFlow::Error) => return,
+            Some(ControlFlow::Break | ControlFlow::Continue) => {
+                unreachable!("symbol checker rejects break and continue outside of loops")
             }
+            // reaching the end of the body returns nothing
+            None => self.result = Value::Void,
+        }
+        if let Some(return_type) = Type::from_ast(&function.return_type) {
+            self.result = self.result.cast(&return_type);
         }
-        return None;
+    }
+
+    // Counts an iteration of a loop and aborts once the limit is exceeded
+    fn count_loop_iteration(&mut self, iterations: &mut usize, keyword: &super::Token) -> bool {
+        *iterations += 1;
+        if *iterations > self.max_loop_iterations {
+            self.diagnostics
+                .borrow_mut()
+                .report_loop_limit_exceeded(keyword.span.clone(), self.max_loop_iterations);
+            self.abort();
+            return false;
+        }
+        true
     }
 }
 
 impl ASTVisitor for ASTSolver {
+    fn visit_statement(&mut self, statement: &super::ASTStatement) {
+        if self.is_unwinding() {
+            return;
+        }
+        self.do_visit_statement(statement);
+    }
+
+    fn visit_compound_statement(&mut self, statement: &super::ASTCompoundStatement) {
+        self.enter_scope(Variables::new());
+        for statement in statement.statements.iter() {
+            self.visit_statement(statement);
+        }
+        self.leave_scope();
+    }
+
     fn visit_return_statement(&mut self, statement: &ASTReturnStatement) {
         self.visit_expression(&statement.expr);
+        if !self.is_unwinding() {
+            self.control_flow = Some(ControlFlow::Return);
+        }
     }
+
     fn visit_let_statement(&mut self, statement: &super::ASTLetStatement) {
         self.visit_expression(&statement.initializer);
-        self.add_identifier_to_scope(&statement.identifier.span.literal, self.result.unwrap());
+        if !self.is_unwinding() {
+            self.evaluate_declaration(&statement.identifier.span.literal, &statement.data_type);
+        }
     }
 
     fn visit_var_statement(&mut self, statement: &super::ASTVarStatement) {
         self.visit_expression(&statement.initializer);
-        self.add_identifier_to_scope(&statement.identifier.span.literal, self.result.unwrap());
+        if !self.is_unwinding() {
+            self.evaluate_declaration(&statement.identifier.span.literal, &statement.data_type);
+        }
     }
 
     fn visit_if_statement(&mut self, statement: &super::ASTIfStatement) {
         self.visit_expression(&statement.condition);
-        let condition = self.result.unwrap();
+        if self.is_unwinding() {
+            return;
+        }
 
-        if condition != 0.0 {
+        if self.result.is_true() {
             self.visit_statement(&statement.then_branch);
         } else if let Some(else_branch) = &statement.else_branch {
             self.visit_statement(&else_branch.else_branch);
         }
     }
 
-    fn visit_for_loop_statement(&mut self, statement: &super::ASTForStatement) {}
+    fn visit_for_loop_statement(&mut self, statement: &super::ASTForStatement) {
+        let (start, end) = match &statement.iterable {
+            ASTForIterable::Range(start, end) => (start, end),
+            ASTForIterable::Array(array) => {
+                self.visit_expression(array);
+                if self.is_unwinding() {
+                    return;
+                }
+                // the loop works on a copy of the array
+                let Value::Array(elements) = self.result.clone() else {
+                    unreachable!("iterated values are checked by the type checker")
+                };
+                let mut iterations = 0;
+                for element in elements {
+                    let element = element.cast(&element.get_type().concrete());
+                    if !self.run_loop_iteration(statement, element, &mut iterations) {
+                        return;
+                    }
+                }
+                return;
+            }
+        };
+        self.visit_expression(start);
+        let start = self.result.clone();
+        self.visit_expression(end);
+        let end = self.result.clone();
+        if self.is_unwinding() {
+            return;
+        }
+
+        // the loop variable gets the type the type checker derived for the range
+        let loop_variable_type = Type::arithmetic_result(&start.get_type(), &end.get_type())
+            .unwrap_or(Type::IntegerLiteral)
+            .concrete();
+        let mut loop_variable = start.cast(&loop_variable_type);
+        let end = end.cast(&loop_variable_type);
+
+        let mut iterations = 0;
+        while loop_variable.compare(&end) == Some(Ordering::Less) {
+            if !self.run_loop_iteration(statement, loop_variable.clone(), &mut iterations) {
+                return;
+            }
+
+            loop_variable = loop_variable
+                .add(&Value::IntegerLiteral(1))
+                .expect("loop variable is an integer");
+        }
+    }
+
+    fn visit_while_loop_statement(&mut self, statement: &super::ASTWhileStatement) {
+        let mut iterations = 0;
+        loop {
+            self.visit_expression(&statement.condition);
+            if self.is_unwinding() || !self.result.is_true() {
+                return;
+            }
+            if !self.count_loop_iteration(&mut iterations, &statement.keyword) {
+                return;
+            }
 
-    fn visit_while_loop_statement(&mut self, statement: &super::ASTWhileStatement) {}
+            self.enter_scope(Variables::new());
+            self.visit_statement(&statement.body);
+            self.leave_scope();
+            if !self.finish_loop_iteration() {
+                return;
+            }
+        }
+    }
+
+    fn visit_break_statement(&mut self, _statement: &super::ASTBreakStatement) {
+        self.control_flow = Some(ControlFlow::Break);
+    }
+
+    fn visit_continue_statement(&mut self, _statement: &super::ASTContinueStatement) {
+        self.control_flow = Some(ControlFlow::Continue);
+    }
 
     fn visit_funtion_statement(&mut self, function: &super::ASTFunctionStatement) {
         self.functions
             .insert(function.identifier.span.literal.clone(), function.clone());
 
-        self.add_identifier_to_scope(&function.identifier.span.literal, 0.0);
+        self.add_identifier_to_scope(&function.identifier.span.literal, Value::Void);
     }
 
     fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {
         self.visit_expression(&expr.expr);
-        for scope in self.scopes.iter_mut().rev() {
-            if let Some(value) = scope.get_mut(&expr.identifier.span.literal) {
-                *value = self.result.unwrap();
-            }
+        if self.is_unwinding() {
+            return;
+        }
+        let Some(address) = self.get_identifier_address(&expr.identifier.span.literal) else {
+            return;
+        };
+        let result = self.result.clone();
+        if let Some(value) = self.memory.get_mut(&address) {
+            // the variable keeps the type it was declared with
+            *value = result.cast(&value.get_type());
+            self.result = value.clone();
         }
     }
 
-    fn visit_function_call_expression(&mut self, expr: &super::ASTFunctionCallExpression) {
-        if !self.check_identifier_in_scope(&expr.identifier.span.literal) {}
+    fn visit_struct_statement(&mut self, statement: &ASTStructStatement) {
+        self.structs
+            .insert(statement.identifier.span.literal.clone(), statement.clone());
+        self.struct_modules.insert(
+            statement.identifier.span.literal.clone(),
+            self.module.clone(),
+        );
+    }
 
-        let func = self
-            .functions
+    fn visit_struct_literal_expression(&mut self, expr: &super::ASTStructLiteralExpression) {
+        let declaration = self
+            .structs
             .get(&expr.identifier.span.literal)
             .unwrap()
             .clone();
-        let mut arguments: Scope = Scope::new();
 
-        // evaluate arguments and add them to scope
-        // arguments.push(expr.identifier.span.literal.clone());
-        for (arg_expr, func_arg) in expr.arguments.iter().zip(func.arguments.iter()) {
-            self.visit_expression(&arg_expr);
-            let arg_name = func_arg.identifier.span.literal.clone();
+        // initializers run in source order, the fields are stored in declaration order
+        let mut values: HashMap<&str, Value> = HashMap::new();
+        for field in expr.fields.iter() {
+            self.visit_expression(&field.expr);
+            if self.is_unwinding() {
+                return;
+            }
+            values.insert(&field.identifier.span.literal, self.result.clone());
+        }
+        let fields = declaration
+            .fields
+            .iter()
+            .map(|field| {
+                let value = values
+                    .remove(field.identifier.span.literal.as_str())
+                    .expect("fields are checked by the symbol checker");
+                let value = match Type::from_ast(&field.data_type) {
+                    Some(data_type) => value.cast(&data_type),
+                    None => value,
+                };
+                (field.identifier.span.literal.clone(), value)
+            })
+            .collect();
+        self.result = Value::Struct {
+            name: declaration.identifier.span.literal.clone(),
+            fields,
+        };
+    }
 
-            arguments.insert(arg_name, self.result.unwrap());
+    fn visit_field_access_expression(&mut self, expr: &super::ASTFieldAccessExpression) {
+        self.visit_expression(&expr.object);
+        if self.is_unwinding() {
+            return;
         }
-        self.enter_scope(arguments);
+        self.result = self
+            .result
+            .field(&expr.field.span.literal)
+            .expect("fields are checked by the type checker")
+            .clone();
+    }
 
-        // todo: that check should be done before
-        if let super::ASTStatementKind::Compound(statement) = &func.body.kind {
-            for statement in statement.statements.iter() {
-                self.visit_statement(statement);
+    fn visit_field_assignment_expression(&mut self, expr: &super::ASTFieldAssignmentExpression) {
+        self.visit_expression(&expr.expr);
+        if self.is_unwinding() {
+            return;
+        }
+        let result = self.result.clone();
+        let Some((address, mut path)) = self.evaluate_place(&expr.target.object) else {
+            return;
+        };
+        path.push(PathSegment::Field(expr.target.field.span.literal.clone()));
+        let value = self
+            .get_place_mut(&address, &path)
+            .expect("fields are checked by the type checker");
+        // the field keeps the type it was declared with
+        *value = result.cast(&value.get_type());
+        self.result = value.clone();
+    }
+
+    fn visit_array_literal_expression(&mut self, expr: &super::ASTArrayLiteralExpression) {
+        let mut elements = Vec::new();
+        for element in expr.elements.iter() {
+            self.visit_expression(element);
+            if self.is_unwinding() {
+                return;
+            }
+            elements.push(self.result.clone());
+        }
+        self.result = Value::Array(elements);
+    }
+
+    fn visit_index_expression(&mut self, expr: &super::ASTIndexExpression) {
+        self.visit_expression(&expr.object);
+        let Value::Array(elements) = self.result.clone() else {
+            if !self.is_unwinding() {
+                unreachable!("indexed values are checked by the type checker")
             }
+            return;
+        };
+        self.visit_expression(&expr.index);
+        if self.is_unwinding() {
+            return;
         }
+        let index = self.result.clone();
+        if let Some(index) = self.element_index(&index, elements.len(), expr.index.span()) {
+            self.result = elements[index].clone();
+        }
+    }
 
-        self.leave_scope();
+    fn visit_index_assignment_expression(&mut self, expr: &super::ASTIndexAssignmentExpression) {
+        self.visit_expression(&expr.expr);
+        if self.is_unwinding() {
+            return;
+        }
+        let result = self.result.clone();
+        let Some((address, path)) = self.evaluate_element_place(&expr.target) else {
+            return;
+        };
+        let value = self
+            .get_place_mut(&address, &path)
+            .expect("elements are checked by the type checker");
+        // the element keeps the type it was declared with
+        *value = result.cast(&value.get_type());
+        self.result = value.clone();
+    }
+
+    fn visit_address_of_expression(&mut self, expr: &super::ASTAddressOfExpression) {
+        let Some((address, path)) = self.evaluate_place(&expr.expr) else {
+            return;
+        };
+        let pointee = self
+            .get_place(&address, &path)
+            .expect("places are checked by the type checker")
+            .get_type();
+        self.result = Value::Pointer {
+            address,
+            path,
+            pointee,
+        };
+    }
+
+    fn visit_dereference_expression(&mut self, expr: &super::ASTDereferenceExpression) {
+        self.visit_expression(&expr.expr);
+        if self.is_unwinding() {
+            return;
+        }
+        let pointer = self.result.clone();
+        let Some((address, path)) = self.resolve_pointer(&pointer, &expr.span()) else {
+            return;
+        };
+        self.result = self
+            .get_place(&address, &path)
+            .expect("fields are checked by the type checker")
+            .clone();
+    }
+
+    fn visit_dereference_assignment_expression(
+        &mut self,
+        expr: &super::ASTDereferenceAssignmentExpression,
+    ) {
+        self.visit_expression(&expr.expr);
+        let result = self.result.clone();
+        self.visit_expression(&expr.target.expr);
+        if self.is_unwinding() {
+            return;
+        }
+        let pointer = self.result.clone();
+        let Some((address, path)) = self.resolve_pointer(&pointer, &expr.target.span()) else {
+            return;
+        };
+        let value = self
+            .get_place_mut(&address, &path)
+            .expect("fields are checked by the type checker");
+        // the value keeps the type it was declared with
+        *value = result.cast(&value.get_type());
+        self.result = value.clone();
+    }
+
+    fn visit_function_call_expression(&mut self, expr: &super::ASTFunctionCallExpression) {
+        let functions = match expr.module() {
+            Some(module) => &self.modules[module].functions,
+            None => &self.functions,
+        };
+        let func = functions.get(expr.identifier()).unwrap().clone();
+        let Some(arguments) = self.evaluate_arguments(&expr.arguments, &func) else {
+            return;
+        };
+        let module = expr.module().unwrap_or(&self.module).to_string();
+        self.call_function_in_module(&module, &func, arguments, &expr.identifier.span);
+    }
+
+    fn visit_method_call_expression(&mut self, expr: &super::ASTMethodCallExpression) {
+        self.visit_expression(&expr.object);
+        if self.is_unwinding() {
+            return;
+        }
+        // the method works on a copy of the receiver
+        let receiver = self.result.clone();
+        let Type::Struct(struct_name) = receiver.get_type() else {
+            unreachable!("receiver is checked by the type checker")
+        };
+        let method = self.structs[&struct_name]
+            .methods
+            .iter()
+            .find(|method| method.identifier.span.literal == expr.method.span.literal)
+            .expect("methods are checked by the symbol checker")
+            .clone();
+
+        let Some(mut arguments) = self.evaluate_arguments(&expr.arguments, &method) else {
+            return;
+        };
+        if let Some(self_parameter) = &method.receiver {
+            arguments.insert(self_parameter.span.literal.clone(), receiver);
+        }
+        let module = self.struct_modules[&struct_name].clone();
+        self.call_function_in_module(&module, &method, arguments, &expr.method.span);
     }
 
     fn visit_variable_expression(&mut self, expr: &super::ASTVariableExpression) {
         self.result = self.get_identifier_in_scope(&expr.identifier.span.literal);
-        // self.result = Some(*self.variables.get(expr.identifier()).unwrap());
     }
 
     fn visit_unary_expression(&mut self, expr: &super::ASTUnaryExpression) {
         self.visit_expression(&expr.expr);
-        self.result = Some(match expr.operator.kind {
-            super::ASTUnaryOperatorKind::BitwiseNOT => (self.result.unwrap() as i64).not() as f64,
-            super::ASTUnaryOperatorKind::LogicNot => ((self.result.unwrap() == 0.0) as i64) as f64,
-            super::ASTUnaryOperatorKind::Minus => self.result.unwrap() * -1.0,
-        });
+        if self.is_unwinding() {
+            return;
+        }
+        let result = match expr.operator.kind {
+            ASTUnaryOperatorKind::BitwiseNOT => self.result.bitwise_not(),
+            ASTUnaryOperatorKind::LogicNot => Some(Value::Bool(!self.result.is_true())),
+            ASTUnaryOperatorKind::Minus => self.result.negate(),
+        };
+        self.result = result.expect("operand is checked by the type checker");
     }
+
     fn visit_binary_expression(&mut self, expr: &super::ASTBinaryExpression) {
         self.visit_expression(&expr.left);
-        let left = self.result.unwrap();
+        if self.is_unwinding() {
+            return;
+        }
+        let left = self.result.clone();
+        // `&&` and `||` only evaluate the right operand if the left one does not
+        // decide the result, so it can rely on the left one, like `p != null && *p > 0`
+        match expr.operator.kind {
+            ASTBinaryOperatorKind::LogicAND if !left.is_true() => {
+                self.result = Value::Bool(false);
+                return;
+            }
+            ASTBinaryOperatorKind::LogicOR if left.is_true() => {
+                self.result = Value::Bool(true);
+                return;
+            }
+            _ => {}
+        }
         self.visit_expression(&expr.right);
-        let right = self.result.unwrap();
-        self.result = Some(match expr.operator.kind {
-            ASTBinaryOperatorKind::Plus => left + right,
-            ASTBinaryOperatorKind::Minus => left - right,
-            ASTBinaryOperatorKind::Multiply => left * right,
-            ASTBinaryOperatorKind::Divide => left / right,
-            ASTBinaryOperatorKind::EqualTo => (left == right) as i64 as f64,
-            ASTBinaryOperatorKind::NotEqualTo => (left != right) as i64 as f64,
-            ASTBinaryOperatorKind::LogicAND => ((left != 0.0) && (right != 0.0)) as i64 as f64,
-            ASTBinaryOperatorKind::LogicOR => ((left != 0.0) || (right != 0.0)) as i64 as f64,
-            ASTBinaryOperatorKind::GreaterThan => (left > right) as i64 as f64,
-            ASTBinaryOperatorKind::GreaterThanOrEqual => (left >= right) as i64 as f64,
-            ASTBinaryOperatorKind::LessThan => (left < right) as i64 as f64,
-            ASTBinaryOperatorKind::LessThanOrEqual => (left <= right) as i64 as f64,
-            ASTBinaryOperatorKind::BitwiseOR => ((left as i64) | (right as i64)) as f64,
-            ASTBinaryOperatorKind::BitwiseAND => ((left as i64) & (right as i64)) as f64,
-            ASTBinaryOperatorKind::BitwiseXOR => ((left as i64) ^ (right as i64)) as f64,
-        })
+        if self.is_unwinding() {
+            return;
+        }
+        let right = self.result.clone();
+
+        let ordering = left.compare(&right);
+        let result = match expr.operator.kind {
+            ASTBinaryOperatorKind::Plus => left.add(&right),
+            ASTBinaryOperatorKind::Minus => left.sub(&right),
+            ASTBinaryOperatorKind::Multiply => left.mul(&right),
+            ASTBinaryOperatorKind::Divide => match left.div(&right) {
+                Some(value) => Some(value),
+                None => {
+                    self.diagnostics
+                        .borrow_mut()
+                        .report_division_by_zero(expr.right.span().clone());
+                    self.abort();
+                    return;
+                }
+            },
+            ASTBinaryOperatorKind::EqualTo => Some(Value::Bool(ordering == Some(Ordering::Equal))),
+            ASTBinaryOperatorKind::NotEqualTo => {
+                Some(Value::Bool(ordering != Some(Ordering::Equal)))
+            }
+            ASTBinaryOperatorKind::LogicAND => Some(Value::Bool(left.is_true() && right.is_true())),
+            ASTBinaryOperatorKind::LogicOR => Some(Value::Bool(left.is_true() || right.is_true())),
+            ASTBinaryOperatorKind::GreaterThan => {
+                Some(Value::Bool(ordering == Some(Ordering::Greater)))
+            }
+            ASTBinaryOperatorKind::GreaterThanOrEqual => Some(Value::Bool(matches!(
+                ordering,
+                Some(Ordering::Greater | Ordering::Equal)
+            ))),
+            ASTBinaryOperatorKind::LessThan => Some(Value::Bool(ordering == Some(Ordering::Less))),
+            ASTBinaryOperatorKind::LessThanOrEqual => Some(Value::Bool(matches!(
+                ordering,
+                Some(Ordering::Less | Ordering::Equal)
+            ))),
+            ASTBinaryOperatorKind::BitwiseOR => left.bitwise_or(&right),
+            ASTBinaryOperatorKind::BitwiseAND => left.bitwise_and(&right),
+            ASTBinaryOperatorKind::BitwiseXOR => left.bitwise_xor(&right),
+        };
+        self.result = result.expect("operands are checked by the type checker");
     }
 
     fn visit_parenthesised_expression(&mut self, expr: &super::ASTParenthesizedExpression) {
         self.visit_expression(&expr.expr);
     }
 
-    fn visit_binary_operator(&mut self, op: &ASTBinaryOperator) {}
-
-    fn visit_integer(&mut self, integer: &i64) {
-        self.result = Some(integer.clone() as f64);
+    fn visit_integer(&mut self, integer: &i128) {
+        self.result = Value::IntegerLiteral(*integer);
     }
+
     fn visit_float(&mut self, float: &f64) {
-        self.result = Some(float.clone());
+        self.result = Value::FloatLiteral(*float);
+    }
+
+    fn visit_string(&mut self, string: &str) {
+        self.result = Value::Str(string.to_string());
+    }
+
+    fn visit_character(&mut self, character: &char) {
+        self.result = Value::Char(*character);
+    }
+
+    fn visit_boolean(&mut self, boolean: &bool) {
+        self.result = Value::Bool(*boolean);
+    }
+
+    fn visit_null(&mut self) {
+        self.result = Value::Null(Type::Null);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::ast::value::Value;
+    use crate::compilation_unit::{CompilationUnit, CompileOptions};
+    use crate::diagnostics::printer::DiagnosticsFormat;
+    use crate::diagnostics::DiagnosticsColletion;
+    use crate::source_text::SourceText;
+
+    fn solve(input: &str) -> Result<Value, ()> {
+        CompilationUnit::compile(input)?.run()
+    }
+
+    // Messages of the errors a script reports when it runs
+    fn runtime_errors(input: &str) -> Vec<String> {
+        let entry = SourceText::new("main.zn".to_string(), input.to_string());
+        let options = CompileOptions {
+            script_mode: true,
+            colored: false,
+            diagnostics_format: DiagnosticsFormat::Json,
+        };
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let compilation_unit = CompilationUnit::compile_into(
+            "main",
+            entry,
+            &|name| Err(format!("{}.zn", name)),
+            options,
+            Rc::clone(&diagnostics_colletion),
+        );
+        let _ = compilation_unit.unwrap().run();
+        let diagnostics = &diagnostics_colletion.borrow().diagnostics;
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_declared_integer_width() {
+        let input = "\
+        let a: i8 = 127;
+        let b: u64 = 9007199254740993;
+        a + 1;
+        b + 2;
+        ";
+        assert_eq!(solve(input), Ok(Value::U64(9007199254740995)));
+        assert_eq!(solve("let a: i8 = 127; a + 1;"), Ok(Value::I8(-128)));
+    }
+
+    #[test]
+    fn should_keep_integer_literals_at_the_i64_and_u64_boundaries() {
+        assert_eq!(
+            solve("let a: u64 = 18446744073709551615; a;"),
+            Ok(Value::U64(u64::MAX))
+        );
+        assert_eq!(
+            solve("let a: u64 = 0xFFFFFFFFFFFFFFFF; a - 1;"),
+            Ok(Value::U64(u64::MAX - 1))
+        );
+        assert_eq!(
+            solve("let a: i64 = 9223372036854775807; a;"),
+            Ok(Value::I64(i64::MAX))
+        );
+        assert_eq!(
+            solve("let a: i64 = -9223372036854775808; a;"),
+            Ok(Value::I64(i64::MIN))
+        );
+        assert_eq!(solve("let a: u64 = 18446744073709551616;"), Err(()));
+    }
+
+    #[test]
+    fn should_convert_arguments_and_result_to_declared_types() {
+        let input = "\
+        func f(a: u8, b: i16) -> i16 { return a + b; }
+        f(250, 10);
+        ";
+        assert_eq!(solve(input), Ok(Value::I16(260)));
+    }
+
+    #[test]
+    fn should_execute_for_loop_over_half_open_range() {
+        let input = "\
+        var sum: i64 = 0;
+        for i in 2..5 {
+            let square: i32 = i * i;
+            sum += square;
+        }
+        sum;
+        ";
+        assert_eq!(solve(input), Ok(Value::I64(4 + 9 + 16)));
+    }
+
+    #[test]
+    fn should_execute_while_loop() {
+        let input = "\
+        var a: i32 = 5;
+        var b: i32 = 0;
+        while a > 0 {
+            a -= 1;
+            b += 2;
+        }
+        b;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(10)));
+    }
+
+    #[test]
+    fn should_report_runaway_loop() {
+        let input = "\
+        var a: i32 = 0;
+        while a == 0 {
+            a = 0;
+        }
+        ";
+        let mut compilation_unit = CompilationUnit::compile(input).unwrap();
+        compilation_unit.set_max_loop_iterations(100);
+        assert_eq!(compilation_unit.run(), Err(()));
+    }
+
+    #[test]
+    fn should_report_runaway_recursion() {
+        let input = "func f(n: i32) -> i32 { return f(n + 1); } f(0);";
+        let mut compilation_unit = CompilationUnit::compile(input).unwrap();
+        compilation_unit.set_max_call_depth(100);
+        assert_eq!(compilation_unit.run(), Err(()));
+
+        let input = "\
+        func f(n: i32) -> i32 {
+            if n == 0 { return 0; }
+            return f(n - 1) + 1;
+        }
+        f(100);
+        ";
+        let mut compilation_unit = CompilationUnit::compile(input).unwrap();
+        compilation_unit.set_max_call_depth(101);
+        assert_eq!(compilation_unit.run(), Ok(Value::I32(100)));
+    }
+
+    #[test]
+    fn should_resolve_names_of_a_function_where_it_is_declared() {
+        let input = "\
+        let a: i32 = 1;
+        func f() -> i32 { return a; }
+        func g() -> i32 {
+            let a: i32 = 2;
+            return f();
+        }
+        return g();
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(1)));
+    }
+
+    #[test]
+    fn should_return_first_reached_return_value() {
+        let input = "\
+        func f(limit: i32) -> i32 {
+            var a: i32 = 0;
+            while a < 100 {
+                for i in 0..10 {
+                    if a + i > limit {
+                        return a + i;
+                    }
+                }
+                a += 10;
+            }
+            return -1;
+        }
+        f(25);
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(26)));
+    }
+
+    #[test]
+    fn should_end_program_at_top_level_return() {
+        let input = "\
+        var a: i32 = 1;
+        return a;
+        a = 2;
+        return a;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(1)));
+    }
+
+    #[test]
+    fn should_report_integer_division_by_zero() {
+        let input = "\
+        let a: i32 = 0;
+        7 / a;
+        ";
+        assert_eq!(solve(input), Err(()));
+    }
+
+    #[test]
+    fn should_short_circuit_logical_operators() {
+        let input = "\
+        let p: *i32 = null;
+        var checked: bool = false;
+        if p != null && *p > 0 {
+            checked = true;
+        }
+        checked || p == null || *p > 0;
+        ";
+        assert_eq!(solve(input), Ok(Value::Bool(true)));
+
+        let input = "\
+        let a: i32 = 7;
+        let b: i32 = 0;
+        b != 0 && a / b > 1;
+        ";
+        assert_eq!(solve(input), Ok(Value::Bool(false)));
+        assert_eq!(
+            runtime_errors("let b: i32 = 0; b == 0 || 1 / b > 1; b != 0 || 1 / b > 1;"),
+            vec!["Division by zero"]
+        );
+    }
+
+    #[test]
+    fn should_report_one_error_per_failing_binary_expression() {
+        let input = "\
+        let zero: i32 = 0;
+        (1 / zero) + (2 / zero);
+        ";
+        assert_eq!(runtime_errors(input), vec!["Division by zero"]);
+        assert_eq!(
+            runtime_errors("let p: *i32 = null; *p == *p;"),
+            vec!["Null pointer dereferenced"]
+        );
+    }
+
+    #[test]
+    fn should_leave_innermost_loop_at_break() {
+        let input = "\
+        var count: i32 = 0;
+        for i in 0..4 {
+            var j: i32 = 0;
+            while true {
+                if j == i {
+                    break;
+                }
+                j += 1;
+                count += 1;
+            }
+        }
+        count;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(1 + 2 + 3)));
+    }
+
+    #[test]
+    fn should_skip_rest_of_body_at_continue() {
+        let input = "\
+        var sum: i32 = 0;
+        var i: i32 = 0;
+        while i < 6 {
+            i += 1;
+            if i == 3 {
+                continue;
+            }
+            sum += i;
+        }
+        sum;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(1 + 2 + 4 + 5 + 6)));
+    }
+
+    #[test]
+    fn should_concatenate_and_compare_strings() {
+        let input = "\
+        let greeting: str = \"Hello, \";
+        let message: str = greeting + \"zeno\\n\";
+        message;
+        ";
+        assert_eq!(solve(input), Ok(Value::Str("Hello, zeno\n".to_string())));
+
+        let input = "\
+        let a: str = \"ab\" + \"c\";
+        (a == \"abc\") && (a != \"ab\");
+        ";
+        assert_eq!(solve(input), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn should_evaluate_boolean_expressions() {
+        let input = "\
+        let a: i32 = 3;
+        let b: bool = a > 2 && !false;
+        b == true || a == 0;
+        ";
+        assert_eq!(solve(input), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn should_compare_characters_by_scalar_value() {
+        let input = "\
+        let a: char = '\\u{e9}';
+        a == 'é' && a > 'z' && 'A' < 'a';
+        ";
+        assert_eq!(solve(input), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn should_read_and_write_struct_fields() {
+        let input = "\
+        struct Point { x: i32, y: i32 }
+        struct Line { start: Point, end: Point }
+        var l: Line = Line { end: Point { y: 4, x: 3 }, start: Point { x: 1, y: 2 } };
+        l.start.x = 5;
+        l.end.y += 1;
+        l.start.x + l.end.y;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(10)));
+        assert_eq!(
+            solve("struct P { x: i32 } P { x: 1 } == P { x: 1 };"),
+            Ok(Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn should_call_methods_on_copy_of_receiver() {
+        let input = "\
+        struct Rect {
+            width: i32,
+            height: i32,
+            func area(self) -> i32 { return self.width * self.height; }
+            func grow(self, by: i32) -> Rect {
+                self.width += by;
+                return Rect { width: self.width, height: self.height + by };
+            }
+        }
+        var r: Rect = Rect { width: 2, height: 3 };
+        let grown: i32 = r.grow(1).area();
+        grown * 100 + r.area();
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(1206)));
+    }
+
+    #[test]
+    fn should_read_and_write_through_pointers() {
+        let input = "\
+        struct Point { x: i32, y: i32 }
+        var a: i32 = 1;
+        let p: *i32 = &a;
+        *p += 1;
+        var pt: Point = Point { x: 1, y: 2 };
+        let px: *i32 = &pt.y;
+        *px = *p * 10;
+        let pp: *Point = &pt;
+        a + (*pp).y;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(22)));
+    }
+
+    #[test]
+    fn should_report_null_and_dangling_pointer_dereference() {
+        assert_eq!(solve("let p: *i32 = null; *p;"), Err(()));
+
+        let input = "\
+        func dangle() -> *i32 {
+            let local: i32 = 5;
+            return &local;
+        }
+        let p: *i32 = dangle();
+        let unrelated: i32 = 7;
+        *p;
+        ";
+        assert_eq!(solve(input), Err(()));
+    }
+
+    #[test]
+    fn should_report_pointers_to_fields_of_an_optional_set_to_null() {
+        let message = "Pointer dereferenced after the value it points to was freed or set to null";
+        let input = "\
+        struct P { x: i32 }
+        var o: P? = P { x: 1 };
+        var p: *i32 = null;
+        if o != null { p = &o.x; }
+        o = null;
+        return *p;
+        ";
+        assert_eq!(runtime_errors(input), vec![message]);
+        let input = input.replace("return *p;", "*p = 2;");
+        assert_eq!(runtime_errors(&input), vec![message]);
+    }
+
+    #[test]
+    fn should_return_and_check_optional_values() {
+        let input = "\
+        func half_of_large(n: i32) -> i32? {
+            if n < 5 { return null; }
+            return n / 2;
+        }
+        var total: i32 = 0;
+        var result: i32? = half_of_large(3);
+        if result == null { total = 100; }
+        result = half_of_large(8);
+        if result != null { total = total + result; }
+        total;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(104)));
+    }
+
+    #[test]
+    fn should_only_evaluate_narrowed_operands_after_the_null_check() {
+        let input = "\
+        func ratio_above_one(a: i32, b: i32?) -> bool {
+            return b != null && a / b > 1;
+        }
+        func missing_or_negative(value: i32?) -> bool {
+            return value == null || value < 0;
+        }
+        let p: *i32 = null;
+        var count: i32 = 0;
+        if p != null && *p > 0 { count += 100; }
+        if !ratio_above_one(7, null) { count += 1; }
+        if ratio_above_one(7, 2) { count += 10; }
+        if missing_or_negative(null) { count += 1000; }
+        if !missing_or_negative(3) { count += 10000; }
+        count;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(11011)));
+    }
+
+    #[test]
+    fn should_index_and_iterate_arrays() {
+        let input = "\
+        struct Point { x: i32, y: i32 }
+        var points: [Point; 2] = [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+        var numbers: [i32; 3] = [1, 2, 3];
+        numbers[1] += 10;
+        points[1].y = numbers[1];
+        let p: *i32 = &points[0].x;
+        *p = 100;
+        var sum: i32 = 0;
+        for n in numbers { sum += n; }
+        for point in points { sum += point.x + point.y; }
+        sum;
+        ";
+        assert_eq!(solve(input), Ok(Value::I32(133)));
+    }
+
+    #[test]
+    fn should_report_index_out_of_bounds() {
+        let input = "\
+        let a: [i32; 2] = [1, 2];
+        var i: i32 = 0;
+        while true { a[i]; i += 1; }
+        ";
+        assert_eq!(solve(input), Err(()));
     }
 }
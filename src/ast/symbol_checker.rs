@@ -2,50 +2,291 @@ use std::collections::HashMap;
 
 use crate::diagnostics::DiagnosticsColletionCell;
 
-use super::ASTVisitor;
+use super::lexer::Token;
+use super::type_checker::Type;
+use super::{
+    ASTExpression, ASTExpressionKind, ASTForIterable, ASTFunctionCallExpression,
+    ASTFunctionStatement, ASTType, ASTVisitor, Ast,
+};
 
+type Scope = HashMap<String, IdentifierSymbol>;
+
+// Identifiers are recorded with the name of their declared type, which is
+// enough to find the struct a method is called on
+#[derive(Clone)]
+struct IdentifierSymbol {
+    data_type: String,
+    declaration: Token,
+    // module the identifier is declared in
+    module: Option<String>,
+    // `let` bindings, loop variables and functions cannot be assigned to
+    mutable: bool,
+}
+
+#[derive(Clone)]
+struct FunctionSymbol {
+    arguments: Vec<String>,
+    return_type: String,
+    declaration: Token,
+    // module the function is declared in
+    module: Option<String>,
+}
+
+#[derive(Clone)]
+struct StructSymbol {
+    fields: Vec<(String, String)>,
+    methods: HashMap<String, FunctionSymbol>,
+    declaration: Token,
+    // module the struct is declared in
+    module: Option<String>,
+}
+
+#[derive(Clone)]
 pub struct SymbolChecker {
-    active_scope: usize,
-    scopes: Vec<Vec<String>>,
-    functions: HashMap<String, Vec<String>>,
+    scopes: Vec<Scope>,
+    functions: HashMap<String, FunctionSymbol>,
+    // struct names are shared by all modules
+    structs: HashMap<String, StructSymbol>,
+    // functions of the modules checked before, by module name
+    modules: HashMap<String, HashMap<String, FunctionSymbol>>,
+    // module being checked
+    module: Option<String>,
+    loop_depth: usize,
     diagnostics: DiagnosticsColletionCell,
 }
 
 impl SymbolChecker {
     pub fn new(diagnostics: DiagnosticsColletionCell) -> Self {
         Self {
-            active_scope: 0,
-            scopes: vec![Vec::new()],
+            scopes: vec![Scope::new()],
             functions: HashMap::new(),
+            structs: HashMap::new(),
+            modules: HashMap::new(),
+            module: None,
+            loop_depth: 0,
             diagnostics,
         }
     }
 
-    fn get_active_scope(&self) -> &Vec<String> {
-        self.scopes.get(self.active_scope).unwrap()
+    // Checks a module with its own global scope, its functions can be called
+    // by the modules checked afterwards
+    pub fn check_module(&mut self, module: &str, ast: &Ast) {
+        self.scopes = vec![Scope::new()];
+        self.set_module(module);
+        ast.visit(self);
+        let functions = std::mem::take(&mut self.functions);
+        self.modules.insert(module.to_string(), functions);
     }
 
-    fn enter_scope(&mut self, scope_variables: Vec<String>) {
+    // Module the declarations visited from now on belong to
+    pub fn set_module(&mut self, module: &str) {
+        self.module = Some(module.to_string());
+    }
+
+    fn function_of(&self, call: &ASTFunctionCallExpression) -> Option<&FunctionSymbol> {
+        match call.module() {
+            Some(module) => self.modules.get(module)?.get(call.identifier()),
+            None => self.functions.get(call.identifier()),
+        }
+    }
+
+    fn enter_scope(&mut self, scope_variables: Scope) {
         self.scopes.push(scope_variables);
-        self.active_scope += 1;
     }
 
     fn leave_scope(&mut self) {
         self.scopes.pop();
-        self.active_scope -= 1;
     }
 
-    fn add_identifier_to_scope(&mut self, identifier: &String) {
-        self.scopes.last_mut().unwrap().push(identifier.clone());
+    fn add_identifier_to_scope(&mut self, identifier: &Token, data_type: &ASTType, mutable: bool) {
+        let (name, symbol) = self.identifier_symbol(identifier, data_type.to_string(), mutable);
+        self.scopes.last_mut().unwrap().insert(name, symbol);
+    }
+
+    // Declares a variable, unless the innermost scope already has one of that
+    // name. Inputs of the REPL are modules of their own, which shadow the
+    // globals of the previous ones.
+    fn declare_variable(&mut self, identifier: &Token, data_type: &ASTType, mutable: bool) {
+        let scope = self.scopes.last().unwrap();
+        if let Some(first) = scope
+            .get(&identifier.span.literal)
+            .filter(|first| first.module == self.module)
+        {
+            self.diagnostics.borrow_mut().report_duplicate_variable(
+                identifier,
+                &first.declaration,
+                first.module.as_deref(),
+            );
+            return;
+        }
+        self.add_identifier_to_scope(identifier, data_type, mutable);
+    }
+
+    fn identifier_symbol(
+        &self,
+        identifier: &Token,
+        data_type: String,
+        mutable: bool,
+    ) -> (String, IdentifierSymbol) {
+        let symbol = IdentifierSymbol {
+            data_type,
+            declaration: identifier.clone(),
+            module: self.module.clone(),
+            mutable,
+        };
+        (identifier.span.literal.clone(), symbol)
+    }
+
+    fn identifier_in_scope(&self, identifier: &str) -> Option<&IdentifierSymbol> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(identifier))
     }
 
-    fn check_identifier_in_scope(&self, identifier: &String) -> bool {
-        for scope in self.scopes.iter().rev() {
-            if scope.contains(identifier) {
-                return true;
+    // Reports assignments to undeclared and immutable variables
+    fn check_assignment_to(&self, variable: &Token) {
+        match self.identifier_in_scope(&variable.span.literal) {
+            Some(symbol) if !symbol.mutable => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_assignment_to_immutable(
+                        variable.span.clone(),
+                        &symbol.declaration,
+                        symbol.module.as_deref(),
+                    );
+            }
+            Some(_) => {}
+            None => {
+                let similar =
+                    closest_declaration(&variable.span.literal, self.declarations_in_scope());
+                self.diagnostics
+                    .borrow_mut()
+                    .report_undefined_variable(variable.span.clone(), similar);
             }
         }
-        return false;
+    }
+
+    fn get_identifier_in_scope(&self, identifier: &str) -> Option<&String> {
+        self.identifier_in_scope(identifier)
+            .map(|symbol| &symbol.data_type)
+    }
+
+    // Declarations of the identifiers in scope with their module
+    fn declarations_in_scope(&self) -> impl Iterator<Item = (&Token, Option<&str>)> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.values())
+            .map(|symbol| (&symbol.declaration, symbol.module.as_deref()))
+    }
+
+    fn check_identifier_in_scope(&self, identifier: &str) -> bool {
+        self.get_identifier_in_scope(identifier).is_some()
+    }
+
+    fn function_symbol(&self, function: &ASTFunctionStatement) -> FunctionSymbol {
+        FunctionSymbol {
+            arguments: function
+                .arguments
+                .iter()
+                .map(|arg| arg.identifier.span.literal.clone())
+                .collect(),
+            return_type: function.return_type.to_string(),
+            declaration: function.identifier.clone(),
+            module: self.module.clone(),
+        }
+    }
+
+    // Declarations of functions with their module
+    fn declarations_of(
+        functions: &HashMap<String, FunctionSymbol>,
+    ) -> impl Iterator<Item = (&Token, Option<&str>)> {
+        functions
+            .values()
+            .map(|symbol| (&symbol.declaration, symbol.module.as_deref()))
+    }
+
+    // Name of the struct an expression evaluates to, if it can be told from the
+    // declarations alone. Everything else is left to the type checker.
+    fn struct_of(&self, expr: &ASTExpression) -> Option<String> {
+        let type_name = self.type_name_of(expr)?;
+        self.structs.contains_key(&type_name).then_some(type_name)
+    }
+
+    // Declared type of an expression as written in the source, like `*Point`
+    fn type_name_of(&self, expr: &ASTExpression) -> Option<String> {
+        let type_name = match &expr.kind {
+            ASTExpressionKind::Variable(variable) => {
+                self.get_identifier_in_scope(variable.identifier())?.clone()
+            }
+            ASTExpressionKind::Assignment(assignment) => self
+                .get_identifier_in_scope(&assignment.identifier.span.literal)?
+                .clone(),
+            ASTExpressionKind::StructLiteral(literal) => literal.identifier.span.literal.clone(),
+            ASTExpressionKind::Parenthesized(parenthesized) => {
+                self.type_name_of(&parenthesized.expr)?
+            }
+            ASTExpressionKind::AddressOf(address_of) => {
+                format!("*{}", self.type_name_of(&address_of.expr)?)
+            }
+            ASTExpressionKind::Dereference(dereference) => self
+                .type_name_of(&dereference.expr)?
+                .strip_prefix('*')?
+                .to_string(),
+            ASTExpressionKind::FieldAccess(access) => self.field_type(access)?,
+            // `[T; N]` holds elements of type `T`
+            ASTExpressionKind::Index(index) => self
+                .type_name_of(&index.object)?
+                .strip_prefix('[')?
+                .rsplit_once(';')?
+                .0
+                .to_string(),
+            ASTExpressionKind::FieldAssignment(assignment) => {
+                self.field_type(&assignment.target)?
+            }
+            ASTExpressionKind::FunctionCall(call) => self.function_of(call)?.return_type.clone(),
+            ASTExpressionKind::MethodCall(call) => {
+                let struct_name = self.struct_of(&call.object)?;
+                self.structs
+                    .get(&struct_name)?
+                    .methods
+                    .get(&call.method.span.literal)?
+                    .return_type
+                    .clone()
+            }
+            _ => return None,
+        };
+        Some(type_name)
+    }
+
+    fn field_type(&self, access: &super::ASTFieldAccessExpression) -> Option<String> {
+        let struct_name = self.struct_of(&access.object)?;
+        self.structs
+            .get(&struct_name)?
+            .fields
+            .iter()
+            .find(|(name, _)| *name == access.field.span.literal)
+            .map(|(_, data_type)| data_type.clone())
+    }
+
+    // The body sees the globals of the module, but not the locals around the declaration
+    fn visit_function_body(&mut self, function: &ASTFunctionStatement, scope: Scope) {
+        let enclosing_scopes = self.scopes.split_off(1);
+        self.enter_scope(scope);
+        // a loop around the declaration does not reach into the function body
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        self.visit_statement(&function.body);
+        self.loop_depth = loop_depth;
+        self.leave_scope();
+        self.scopes.extend(enclosing_scopes);
+    }
+
+    fn arguments_scope(&self, function: &ASTFunctionStatement) -> Scope {
+        function
+            .arguments
+            .iter()
+            .map(|arg| self.identifier_symbol(&arg.identifier, arg.data_type.to_string(), true))
+            .collect()
     }
 }
 
@@ -54,18 +295,19 @@ impl ASTVisitor for SymbolChecker {
         self.visit_expression(&statement.expr);
     }
 
+    // the initializer cannot refer to the variable it initializes
     fn visit_let_statement(&mut self, statement: &super::ASTLetStatement) {
-        self.add_identifier_to_scope(&statement.identifier.span.literal);
         self.visit_expression(&statement.initializer);
+        self.declare_variable(&statement.identifier, &statement.data_type, false);
     }
 
     fn visit_var_statement(&mut self, statement: &super::ASTVarStatement) {
-        self.add_identifier_to_scope(&statement.identifier.span.literal);
         self.visit_expression(&statement.initializer);
+        self.declare_variable(&statement.identifier, &statement.data_type, true);
     }
 
     fn visit_compound_statement(&mut self, statement: &super::ASTCompoundStatement) {
-        self.enter_scope(Vec::new());
+        self.enter_scope(Scope::new());
         for statement in statement.statements.iter() {
             self.visit_statement(statement);
         }
@@ -73,54 +315,161 @@ impl ASTVisitor for SymbolChecker {
     }
 
     fn visit_if_statement(&mut self, statement: &super::ASTIfStatement) {
+        self.visit_expression(&statement.condition);
         self.visit_statement(&statement.then_branch);
         if let Some(else_branch) = &statement.else_branch {
             self.visit_statement(&else_branch.else_branch);
         }
     }
 
-    fn visit_for_loop_statement(&mut self, statement: &super::ASTForStatement) {}
+    fn visit_for_loop_statement(&mut self, statement: &super::ASTForStatement) {
+        match &statement.iterable {
+            ASTForIterable::Range(start, end) => {
+                self.visit_expression(start);
+                self.visit_expression(end);
+            }
+            ASTForIterable::Array(array) => self.visit_expression(array),
+        }
+        // the type of the loop variable is derived by the type checker
+        self.enter_scope(Scope::from([self.identifier_symbol(
+            &statement.loop_variable,
+            String::new(),
+            false,
+        )]));
+        self.loop_depth += 1;
+        self.visit_statement(&statement.body);
+        self.loop_depth -= 1;
+        self.leave_scope();
+    }
 
-    fn visit_while_loop_statement(&mut self, statement: &super::ASTWhileStatement) {}
+    fn visit_while_loop_statement(&mut self, statement: &super::ASTWhileStatement) {
+        self.visit_expression(&statement.condition);
+        self.loop_depth += 1;
+        self.visit_statement(&statement.body);
+        self.loop_depth -= 1;
+    }
 
-    fn visit_funtion_statement(&mut self, function: &super::ASTFunctionStatement) {
-        self.add_identifier_to_scope(&function.identifier.span.literal);
+    fn visit_break_statement(&mut self, statement: &super::ASTBreakStatement) {
+        if self.loop_depth == 0 {
+            self.diagnostics
+                .borrow_mut()
+                .report_outside_of_loop(&statement.keyword);
+        }
+    }
 
-        let mut arguments_names: Vec<String> = Vec::new();
-        // add arguments to scope of local variable call
-        for arg in function.arguments.iter() {
-            arguments_names.push(arg.identifier.span.literal.clone());
+    fn visit_continue_statement(&mut self, statement: &super::ASTContinueStatement) {
+        if self.loop_depth == 0 {
+            self.diagnostics
+                .borrow_mut()
+                .report_outside_of_loop(&statement.keyword);
         }
-        self.functions.insert(
-            function.identifier.span.literal.clone(),
-            arguments_names.clone(),
+    }
+
+    fn visit_struct_statement(&mut self, statement: &super::ASTStructStatement) {
+        let struct_name = &statement.identifier.span.literal;
+        if let Some(first) = self.structs.get(struct_name) {
+            self.diagnostics.borrow_mut().report_duplicate_struct(
+                &statement.identifier,
+                &first.declaration,
+                first.module.as_deref(),
+            );
+            return;
+        }
+        let mut fields: Vec<(String, String)> = Vec::new();
+        for field in statement.fields.iter() {
+            if fields
+                .iter()
+                .any(|(name, _)| *name == field.identifier.span.literal)
+            {
+                let first = statement
+                    .fields
+                    .iter()
+                    .find(|first| first.identifier.span.literal == field.identifier.span.literal)
+                    .unwrap();
+                self.diagnostics.borrow_mut().report_duplicate_field(
+                    &field.identifier,
+                    struct_name,
+                    &first.identifier,
+                );
+                continue;
+            }
+            fields.push((
+                field.identifier.span.literal.clone(),
+                field.data_type.to_string(),
+            ));
+        }
+
+        // all methods are known before the bodies are checked, so they can call
+        // each other in any order
+        let mut methods: HashMap<String, FunctionSymbol> = HashMap::new();
+        for method in statement.methods.iter() {
+            if let Some(first) = methods.get(&method.identifier.span.literal) {
+                self.diagnostics.borrow_mut().report_duplicate_method(
+                    &method.identifier,
+                    struct_name,
+                    &first.declaration,
+                );
+                continue;
+            }
+            methods.insert(
+                method.identifier.span.literal.clone(),
+                self.function_symbol(method),
+            );
+        }
+        self.structs.insert(
+            struct_name.clone(),
+            StructSymbol {
+                fields,
+                methods,
+                declaration: statement.identifier.clone(),
+                module: self.module.clone(),
+            },
         );
 
-        // arguments_names.push(function.identifier.span.literal.clone());
-        self.enter_scope(arguments_names);
+        for method in statement.methods.iter() {
+            let mut scope = self.arguments_scope(method);
+            if let Some(receiver) = &method.receiver {
+                let (name, symbol) = self.identifier_symbol(receiver, struct_name.clone(), true);
+                scope.insert(name, symbol);
+            }
+            self.visit_function_body(method, scope);
+        }
+    }
 
-        self.visit_statement(&function.body);
-        // match &function.body.kind {
-        //     super::ASTStatementKind::Compound(statement) => {
-        //         for statement in statement.statements.iter() {
-        //             self.visit_statement(statement);
-        //         }
-        //     }
-        //     _ => todo!(),
-        // };
-        self.leave_scope();
+    fn visit_funtion_statement(&mut self, function: &super::ASTFunctionStatement) {
+        self.add_identifier_to_scope(&function.identifier, &function.return_type, false);
+        self.functions.insert(
+            function.identifier.span.literal.clone(),
+            self.function_symbol(function),
+        );
+        self.visit_function_body(function, self.arguments_scope(function));
     }
 
-    fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {}
+    fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {
+        self.check_assignment_to(&expr.identifier);
+        self.visit_expression(&expr.expr);
+    }
 
     fn visit_function_call_expression(&mut self, expr: &super::ASTFunctionCallExpression) {
-        if !self.check_identifier_in_scope(&expr.identifier().to_string()) {
+        let Some(function) = self.function_of(expr) else {
+            // a call of another module can only be to one of its functions
+            let similar = match expr.module() {
+                Some(module) => self.modules.get(module).and_then(|functions| {
+                    closest_declaration(expr.identifier(), Self::declarations_of(functions))
+                }),
+                None => closest_declaration(
+                    expr.identifier(),
+                    self.declarations_in_scope()
+                        .chain(Self::declarations_of(&self.functions)),
+                ),
+            };
             self.diagnostics
                 .borrow_mut()
-                .report_undefined_variable(expr.identifier.span.clone());
-        }
+                .report_undefined_variable(expr.identifier.span.clone(), similar);
+            return;
+        };
 
-        let expected_number_of_arguments = self.functions.get(expr.identifier()).unwrap().len();
+        let expected_number_of_arguments = function.arguments.len();
         if expected_number_of_arguments != expr.arguments.len() {
             self.diagnostics
                 .borrow_mut()
@@ -128,6 +477,8 @@ impl ASTVisitor for SymbolChecker {
                     expr.identifier.span.clone(),
                     expected_number_of_arguments,
                     expr.arguments.len(),
+                    &function.declaration,
+                    function.module.as_deref(),
                 );
             return;
         }
@@ -137,11 +488,155 @@ impl ASTVisitor for SymbolChecker {
         }
     }
 
+    fn visit_method_call_expression(&mut self, expr: &super::ASTMethodCallExpression) {
+        self.visit_expression(&expr.object);
+        for arg in expr.arguments.iter() {
+            self.visit_expression(arg);
+        }
+
+        let Some(struct_name) = self.struct_of(&expr.object) else {
+            return;
+        };
+        let Some(method) = self.structs[&struct_name]
+            .methods
+            .get(&expr.method.span.literal)
+        else {
+            self.diagnostics
+                .borrow_mut()
+                .report_unknown_method(&expr.method, &Type::Struct(struct_name));
+            return;
+        };
+        if method.arguments.len() != expr.arguments.len() {
+            self.diagnostics
+                .borrow_mut()
+                .report_number_of_function_arguments_mismatch(
+                    expr.method.span.clone(),
+                    method.arguments.len(),
+                    expr.arguments.len(),
+                    &method.declaration,
+                    method.module.as_deref(),
+                );
+        }
+    }
+
+    fn visit_struct_literal_expression(&mut self, expr: &super::ASTStructLiteralExpression) {
+        let struct_name = &expr.identifier.span.literal;
+        let Some(declared_fields) = self
+            .structs
+            .get(struct_name)
+            .map(|symbol| symbol.fields.clone())
+        else {
+            self.diagnostics
+                .borrow_mut()
+                .report_undefined_variable(expr.identifier.span.clone(), None);
+            return;
+        };
+
+        let mut initialized: Vec<&str> = Vec::new();
+        for field in expr.fields.iter() {
+            let field_name = field.identifier.span.literal.as_str();
+            if !declared_fields.iter().any(|(name, _)| name == field_name) {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_unknown_field(&field.identifier, &Type::Struct(struct_name.clone()));
+            } else if initialized.contains(&field_name) {
+                let first = expr
+                    .fields
+                    .iter()
+                    .find(|first| first.identifier.span.literal == field_name)
+                    .unwrap();
+                self.diagnostics.borrow_mut().report_duplicate_field(
+                    &field.identifier,
+                    struct_name,
+                    &first.identifier,
+                );
+            } else {
+                initialized.push(field_name);
+            }
+            self.visit_expression(&field.expr);
+        }
+
+        let missing: Vec<&str> = declared_fields
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| !initialized.contains(name))
+            .collect();
+        if !missing.is_empty() {
+            self.diagnostics
+                .borrow_mut()
+                .report_missing_fields(&expr.identifier, &missing);
+        }
+    }
+
+    fn visit_field_access_expression(&mut self, expr: &super::ASTFieldAccessExpression) {
+        self.visit_expression(&expr.object);
+    }
+
+    fn visit_field_assignment_expression(&mut self, expr: &super::ASTFieldAssignmentExpression) {
+        // only fields of variables can be written
+        match expr.target.object.root_variable() {
+            Some(variable) => self.check_assignment_to(variable),
+            None => self
+                .diagnostics
+                .borrow_mut()
+                .report_invalid_assignment_target(expr.target.object.span().clone()),
+        }
+        self.visit_field_access_expression(&expr.target);
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_address_of_expression(&mut self, expr: &super::ASTAddressOfExpression) {
+        // only variables, their fields and their elements have an address
+        if expr.expr.root_variable().is_none() {
+            self.diagnostics
+                .borrow_mut()
+                .report_invalid_address_of(expr.expr.span().clone());
+        }
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_dereference_expression(&mut self, expr: &super::ASTDereferenceExpression) {
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_array_literal_expression(&mut self, expr: &super::ASTArrayLiteralExpression) {
+        for element in expr.elements.iter() {
+            self.visit_expression(element);
+        }
+    }
+
+    fn visit_index_expression(&mut self, expr: &super::ASTIndexExpression) {
+        self.visit_expression(&expr.object);
+        self.visit_expression(&expr.index);
+    }
+
+    fn visit_index_assignment_expression(&mut self, expr: &super::ASTIndexAssignmentExpression) {
+        // only elements of variables can be written
+        match expr.target.object.root_variable() {
+            Some(variable) => self.check_assignment_to(variable),
+            None => self
+                .diagnostics
+                .borrow_mut()
+                .report_invalid_assignment_target(expr.target.object.span().clone()),
+        }
+        self.visit_index_expression(&expr.target);
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_dereference_assignment_expression(
+        &mut self,
+        expr: &super::ASTDereferenceAssignmentExpression,
+    ) {
+        self.visit_dereference_expression(&expr.target);
+        self.visit_expression(&expr.expr);
+    }
+
     fn visit_variable_expression(&mut self, expr: &super::ASTVariableExpression) {
-        if !self.check_identifier_in_scope(&expr.identifier().to_string()) {
+        if !self.check_identifier_in_scope(expr.identifier()) {
+            let similar = closest_declaration(expr.identifier(), self.declarations_in_scope());
             self.diagnostics
                 .borrow_mut()
-                .report_undefined_variable(expr.identifier.span.clone());
+                .report_undefined_variable(expr.identifier.span.clone(), similar);
         }
     }
 
@@ -158,7 +653,306 @@ impl ASTVisitor for SymbolChecker {
         self.visit_expression(&expr.expr);
     }
 
-    fn visit_binary_operator(&mut self, op: &super::ASTBinaryOperator) {}
-    fn visit_integer(&mut self, integer: &i64) {}
-    fn visit_float(&mut self, float: &f64) {}
+    fn visit_integer(&mut self, _integer: &i128) {}
+    fn visit_float(&mut self, _float: &f64) {}
+    fn visit_string(&mut self, _string: &str) {}
+    fn visit_character(&mut self, _character: &char) {}
+    fn visit_boolean(&mut self, _boolean: &bool) {}
+    fn visit_null(&mut self) {}
+}
+
+// Declaration of the name closest to an unknown one by edit distance. Names
+// that differ in more than a third of their characters are not similar, and
+// neither are names that differ in all of them, like `a` and `g`.
+fn closest_declaration<'a>(
+    name: &str,
+    declarations: impl Iterator<Item = (&'a Token, Option<&'a str>)>,
+) -> Option<(&'a Token, Option<&'a str>)> {
+    let length = name.chars().count();
+    let limit = (length / 3).max(1);
+    declarations
+        .map(|(declaration, module)| {
+            let distance = edit_distance(name, &declaration.span.literal);
+            (distance, declaration, module)
+        })
+        .filter(|(distance, _, _)| *distance <= limit && *distance < length)
+        // the scopes are not ordered, ties go to the alphabetically first name
+        .min_by(|(a, a_declaration, _), (b, b_declaration, _)| {
+            a.cmp(b)
+                .then_with(|| a_declaration.span.literal.cmp(&b_declaration.span.literal))
+        })
+        .map(|(_, declaration, module)| (declaration, module))
+}
+
+// Levenshtein distance, the number of characters to insert, delete or replace
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // distances of the prefix of `a` seen so far to every prefix of `b`
+    let mut distances: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = distances[0];
+        distances[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let replaced = diagonal + usize::from(a != *b);
+            diagonal = distances[j + 1];
+            distances[j + 1] = replaced.min(distances[j] + 1).min(diagonal + 1);
+        }
+    }
+    distances[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::ast::parser::Parser;
+    use crate::ast::Ast;
+    use crate::diagnostics::{Diagnostic, DiagnosticsColletion};
+
+    use super::{edit_distance, SymbolChecker};
+
+    fn check(input: &str) -> Vec<String> {
+        check_diagnostics(input)
+            .iter()
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect()
+    }
+
+    // Diagnostics with their labels and help
+    fn check_diagnostics(input: &str) -> Vec<Diagnostic> {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let mut parser = Parser::from_input(input.to_string(), Rc::clone(&diagnostics_colletion));
+        let mut ast = Ast::new();
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        ast.visit(&mut SymbolChecker::new(Rc::clone(&diagnostics_colletion)));
+
+        let diagnostics = &mut diagnostics_colletion.borrow_mut().diagnostics;
+        std::mem::take(diagnostics)
+    }
+
+    #[test]
+    fn should_suggest_similar_names() {
+        assert_eq!(edit_distance("sum_untl", "sum_until"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+
+        let input = "\
+        func sum_until(limit: i32) -> i32 { return limit; }
+        let counter: i32 = 1;
+        {
+            let count: i32 = 2;
+            sum_untl(countr);
+            conter;
+        }
+        counts + xyz;
+        let a: i32 = g();
+        let zz: i32 = zy;
+        ";
+        let diagnostics = check_diagnostics(input);
+        let suggestions: Vec<(&str, Option<&str>)> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let label = diagnostic.labels.first();
+                (
+                    diagnostic.span.literal.as_str(),
+                    label.map(|label| &input[label.span.start..label.span.end]),
+                )
+            })
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![
+                ("sum_untl", Some("sum_until")),
+                ("conter", Some("counter")),
+                ("counts", Some("counter")),
+                ("xyz", None),
+                ("g", None),
+                ("zy", None),
+            ]
+        );
+        assert_eq!(diagnostics[0].help, vec!["did you mean <sum_until>?"]);
+        // `count` is closer to `counts` but out of scope
+        assert!(diagnostics[2].labels[0].span.start < input.find("let count:").unwrap());
+    }
+
+    #[test]
+    fn should_accept_break_and_continue_inside_loops() {
+        let input = "\
+        for i in 0..10 {
+            if i == 2 {
+                continue;
+            }
+            while true {
+                break;
+            }
+        }
+        ";
+        assert!(check(input).is_empty());
+    }
+
+    #[test]
+    fn should_reject_break_and_continue_outside_of_loops() {
+        let input = "\
+        break;
+        while true {
+            func f() -> i32 {
+                continue;
+                return 0;
+            }
+            break;
+        }
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "<Break> can only be used inside of a loop",
+                "<Continue> can only be used inside of a loop",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_struct_fields() {
+        let input = "\
+        struct Point { x: i32, y: i32, x: u8 }
+        let a: Point = Point { x: 1, z: 2, x: 3 };
+        let b: Line = Line { x: 1 };
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Field <x> is already declared in <Point>",
+                "Type <Point> has no field <z>",
+                "Field <x> is already declared in <Point>",
+                "Missing fields <y> in initializer of <Point>",
+                "Not found in this scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_resolve_methods_and_check_their_arity() {
+        let input = "\
+        struct Point {
+            x: i32,
+            func get(self) -> i32 { return self.x; }
+            func add(self, other: Point) -> Point { return Point { x: self.x + other.get() }; }
+            func get(self) -> i32 { return 0; }
+        }
+        let p: Point = Point { x: 1 };
+        p.add(p).add(p, 2).get();
+        p.length();
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Method <get> is already declared in <Point>",
+                "Function add expects 1 arguments but 2 were given",
+                "Type <Point> has no method <length>",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_only_take_address_of_variables_and_fields() {
+        let input = "\
+        struct Point { x: i32 }
+        var p: Point = Point { x: 1 };
+        let a: *i32 = &p.x;
+        let b: *i32 = &(1 + 2);
+        let c: *Point = &Point { x: 1 };
+        (*c).x;
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Cannot take the address of this expression",
+                "Cannot take the address of this expression",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_only_assign_to_mutable_variables() {
+        let input = "\
+        struct Point { x: i32 }
+        func f(n: i32) -> i32 { n += 1; return n; }
+        let sum: i32 = 0;
+        var total: i32 = 0;
+        let p: Point = Point { x: 1 };
+        let primes: [i32; 2] = [2, 3];
+        for i in 0..3 {
+            sum += i;
+            total += i;
+            i = 0;
+        }
+        p.x = 2;
+        primes[0] = 1;
+        f = 1;
+        totl = 1;
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Cannot assign to immutable variable <sum>",
+                "Cannot assign to immutable variable <i>",
+                "Cannot assign to immutable variable <p>",
+                "Cannot assign to immutable variable <primes>",
+                "Cannot assign to immutable variable <f>",
+                "Not found in this scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_variables_declared_twice_in_a_scope() {
+        let input = "\
+        let a: i32 = 1;
+        var a: i32 = 2;
+        {
+            let a: i32 = 3;
+            let b: i32 = a;
+        }
+        let b: i32 = a;
+        func f(x: i32) -> i32 {
+            let x: i32 = 1;
+            let x: i32 = 2;
+            return x;
+        }
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Variable <a> is already declared in this scope",
+                "Variable <x> is already declared in this scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_only_see_globals_and_arguments_in_function_bodies() {
+        let input = "\
+        let a: i32 = 1;
+        func f(b: i32) -> i32 {
+            let c: i32 = a + b;
+            func g() -> i32 { return a + c; }
+            return g();
+        }
+        ";
+        assert_eq!(check(input), vec!["Not found in this scope"]);
+    }
+
+    #[test]
+    fn should_declare_variables_after_their_initializer() {
+        let input = "\
+        let x: i32 = x;
+        var y: i32 = 1;
+        {
+            var y: i32 = y + 1;
+        }
+        ";
+        assert_eq!(check(input), vec!["Not found in this scope"]);
+    }
 }
@@ -0,0 +1,655 @@
+use std::collections::{HashMap, HashSet};
+
+use super::lexer::{TextSpan, Token, TokenKind};
+use super::printer::Highlight;
+use super::{
+    ASTExpression, ASTExpressionKind, ASTForIterable, ASTFunctionStatement, ASTStatementKind,
+    ASTStructStatement, ASTType, ASTTypeKind, ASTVisitor, Ast,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SymbolKind {
+    Variable,
+    Parameter,
+    Function,
+    Struct,
+    Field,
+    Method,
+}
+
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    // identifier of the declaration
+    pub span: TextSpan,
+    // whole declaration, like a function with its body
+    pub range: TextSpan,
+    // declaration as written, like `let a: i32`
+    pub detail: String,
+    // struct that declares a field or method
+    pub parent: Option<usize>,
+}
+
+impl Symbol {
+    pub fn highlight(&self) -> Highlight {
+        match self.kind {
+            SymbolKind::Variable | SymbolKind::Parameter | SymbolKind::Field => Highlight::Variable,
+            SymbolKind::Function | SymbolKind::Method => Highlight::Function,
+            SymbolKind::Struct => Highlight::Type,
+        }
+    }
+}
+
+// Declarations of a file and the identifiers referring to them, resolved with
+// the scoping rules of the symbol checker. Calls into imported modules are
+// not resolved.
+pub struct SymbolIndex {
+    symbols: Vec<Symbol>,
+    references: Vec<(TextSpan, usize)>,
+    // starts of the recorded references, `a += 1` names `a` twice in the tree
+    referenced: HashSet<usize>,
+    scopes: Vec<HashMap<String, usize>>,
+    functions: HashMap<String, usize>,
+    structs: HashMap<String, usize>,
+    // declared type of variables, fields and the return type of functions
+    types: HashMap<usize, String>,
+}
+
+impl SymbolIndex {
+    pub fn new(ast: &Ast) -> Self {
+        let mut index = Self {
+            symbols: Vec::new(),
+            references: Vec::new(),
+            referenced: HashSet::new(),
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            types: HashMap::new(),
+        };
+        // functions and structs can be used before their declaration
+        for statement in ast.statements.iter() {
+            match &statement.kind {
+                ASTStatementKind::FuncDecl(function) => {
+                    let id = index.declare_function(function, SymbolKind::Function, None);
+                    index
+                        .functions
+                        .insert(function.identifier.span.literal.clone(), id);
+                }
+                ASTStatementKind::StructDecl(statement_struct) => {
+                    index.declare_struct(statement_struct, statement.span.clone())
+                }
+                _ => {}
+            }
+        }
+        ast.visit(&mut index);
+        index
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn symbol(&self, id: usize) -> &Symbol {
+        &self.symbols[id]
+    }
+
+    // Symbol declared or referenced by the identifier at the offset, together
+    // with the span of that identifier
+    pub fn symbol_at(&self, offset: usize) -> Option<(&TextSpan, usize)> {
+        let contains = |span: &TextSpan| span.start <= offset && offset <= span.end;
+        self.symbols
+            .iter()
+            .enumerate()
+            .find(|(_, symbol)| contains(&symbol.span))
+            .map(|(id, symbol)| (&symbol.span, id))
+            .or_else(|| {
+                self.references
+                    .iter()
+                    .find(|(span, _)| contains(span))
+                    .map(|(span, id)| (span, *id))
+            })
+    }
+
+    // Identifiers referring to the symbol, without its declaration
+    pub fn references(&self, id: usize) -> Vec<&TextSpan> {
+        self.references
+            .iter()
+            .filter(|(_, symbol)| *symbol == id)
+            .map(|(span, _)| span)
+            .collect()
+    }
+
+    fn declare(&mut self, symbol: Symbol, data_type: Option<String>) -> usize {
+        let id = self.symbols.len();
+        if let Some(data_type) = data_type {
+            self.types.insert(id, data_type);
+        }
+        self.symbols.push(symbol);
+        id
+    }
+
+    // Declares a variable or parameter in the innermost scope
+    fn declare_local(
+        &mut self,
+        identifier: &Token,
+        kind: SymbolKind,
+        detail: String,
+        data_type: Option<String>,
+    ) {
+        let id = self.declare(
+            Symbol {
+                name: identifier.span.literal.clone(),
+                kind,
+                span: identifier.span.clone(),
+                range: identifier.span.clone(),
+                detail,
+                parent: None,
+            },
+            data_type,
+        );
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(identifier.span.literal.clone(), id);
+    }
+
+    fn declare_function(
+        &mut self,
+        function: &ASTFunctionStatement,
+        kind: SymbolKind,
+        parent: Option<usize>,
+    ) -> usize {
+        let mut arguments: Vec<String> = function
+            .receiver
+            .iter()
+            .map(|r| r.span.literal.clone())
+            .collect();
+        arguments.extend(
+            function
+                .arguments
+                .iter()
+                .map(|arg| format!("{}: {}", arg.identifier.span.literal, arg.data_type)),
+        );
+        let mut detail = format!(
+            "func {}({})",
+            function.identifier.span.literal,
+            arguments.join(", ")
+        );
+        if !function.return_type.is_void() {
+            detail.push_str(&format!(" -> {}", function.return_type));
+        }
+        let end = match &function.body.kind {
+            ASTStatementKind::Compound(body) => body.close_brace.span.clone(),
+            _ => function.identifier.span.clone(),
+        };
+        self.declare(
+            Symbol {
+                name: function.identifier.span.literal.clone(),
+                kind,
+                span: function.identifier.span.clone(),
+                range: TextSpan::combine(vec![function.keyword.span.clone(), end]),
+                detail,
+                parent,
+            },
+            Some(function.return_type.to_string()),
+        )
+    }
+
+    fn declare_struct(&mut self, statement: &ASTStructStatement, range: TextSpan) {
+        let name = &statement.identifier.span.literal;
+        let id = self.declare(
+            Symbol {
+                name: name.clone(),
+                kind: SymbolKind::Struct,
+                span: statement.identifier.span.clone(),
+                range,
+                detail: format!("struct {}", name),
+                parent: None,
+            },
+            None,
+        );
+        self.structs.insert(name.clone(), id);
+        for field in statement.fields.iter() {
+            self.declare(
+                Symbol {
+                    name: field.identifier.span.literal.clone(),
+                    kind: SymbolKind::Field,
+                    span: field.identifier.span.clone(),
+                    range: field.identifier.span.clone(),
+                    detail: format!(
+                        "{}.{}: {}",
+                        name, field.identifier.span.literal, field.data_type
+                    ),
+                    parent: Some(id),
+                },
+                Some(field.data_type.to_string()),
+            );
+        }
+        for method in statement.methods.iter() {
+            self.declare_function(method, SymbolKind::Method, Some(id));
+        }
+    }
+
+    fn add_reference(&mut self, span: &TextSpan, id: usize) {
+        if self.referenced.insert(span.start) {
+            self.references.push((span.clone(), id));
+        }
+    }
+
+    fn resolve_variable(&mut self, identifier: &Token) {
+        let id = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&identifier.span.literal));
+        if let Some(id) = id.copied() {
+            self.add_reference(&identifier.span, id);
+        }
+    }
+
+    // Struct names mentioned by a type annotation
+    fn visit_type(&mut self, data_type: &ASTType) {
+        match &data_type.kind {
+            ASTTypeKind::Named(token) if token.kind == TokenKind::Identifier => {
+                if let Some(id) = self.structs.get(&token.span.literal).copied() {
+                    self.add_reference(&token.span, id);
+                }
+            }
+            ASTTypeKind::Pointer(data_type)
+            | ASTTypeKind::Optional(data_type)
+            | ASTTypeKind::Array(data_type, _) => self.visit_type(data_type),
+            _ => {}
+        }
+    }
+
+    // Member of a struct by its name
+    fn member(&self, struct_id: usize, name: &str) -> Option<usize> {
+        self.symbols
+            .iter()
+            .position(|symbol| symbol.parent == Some(struct_id) && symbol.name == name)
+    }
+
+    // Struct an expression evaluates to, as far as declarations tell
+    fn struct_of(&self, expr: &ASTExpression) -> Option<usize> {
+        let type_name = match &expr.kind {
+            ASTExpressionKind::Parenthesized(parenthesized) => {
+                return self.struct_of(&parenthesized.expr)
+            }
+            ASTExpressionKind::Dereference(dereference) => {
+                return self.struct_of(&dereference.expr)
+            }
+            ASTExpressionKind::StructLiteral(literal) => literal.identifier.span.literal.clone(),
+            ASTExpressionKind::Variable(variable) => {
+                let id = self
+                    .scopes
+                    .iter()
+                    .rev()
+                    .find_map(|scope| scope.get(variable.identifier()))?;
+                self.types.get(id)?.clone()
+            }
+            ASTExpressionKind::FunctionCall(call) if call.module.is_none() => self
+                .types
+                .get(self.functions.get(call.identifier())?)?
+                .clone(),
+            ASTExpressionKind::FieldAccess(access) => {
+                let member =
+                    self.member(self.struct_of(&access.object)?, &access.field.span.literal)?;
+                self.types.get(&member)?.clone()
+            }
+            ASTExpressionKind::MethodCall(call) => {
+                let member =
+                    self.member(self.struct_of(&call.object)?, &call.method.span.literal)?;
+                self.types.get(&member)?.clone()
+            }
+            _ => return None,
+        };
+        // methods are called through pointers as well
+        self.structs.get(type_name.trim_start_matches('*')).copied()
+    }
+
+    fn visit_function_body(
+        &mut self,
+        function: &ASTFunctionStatement,
+        receiver_type: Option<&str>,
+    ) {
+        // the body only sees the globals, not the locals around the declaration
+        let enclosing_scopes = self.scopes.split_off(1);
+        self.scopes.push(HashMap::new());
+        if let (Some(receiver), Some(receiver_type)) = (&function.receiver, receiver_type) {
+            self.declare_local(
+                receiver,
+                SymbolKind::Parameter,
+                format!("self: {}", receiver_type),
+                Some(receiver_type.to_string()),
+            );
+        }
+        for arg in function.arguments.iter() {
+            self.visit_type(&arg.data_type);
+            self.declare_local(
+                &arg.identifier,
+                SymbolKind::Parameter,
+                format!("{}: {}", arg.identifier.span.literal, arg.data_type),
+                Some(arg.data_type.to_string()),
+            );
+        }
+        self.visit_type(&function.return_type);
+        self.visit_statement(&function.body);
+        self.scopes.pop();
+        self.scopes.extend(enclosing_scopes);
+    }
+}
+
+impl ASTVisitor for SymbolIndex {
+    fn visit_return_statement(&mut self, statement: &super::ASTReturnStatement) {
+        self.visit_expression(&statement.expr);
+    }
+
+    fn visit_let_statement(&mut self, statement: &super::ASTLetStatement) {
+        self.visit_expression(&statement.initializer);
+        self.visit_type(&statement.data_type);
+        self.declare_local(
+            &statement.identifier,
+            SymbolKind::Variable,
+            format!(
+                "let {}: {}",
+                statement.identifier.span.literal, statement.data_type
+            ),
+            Some(statement.data_type.to_string()),
+        );
+    }
+
+    fn visit_var_statement(&mut self, statement: &super::ASTVarStatement) {
+        self.visit_expression(&statement.initializer);
+        self.visit_type(&statement.data_type);
+        self.declare_local(
+            &statement.identifier,
+            SymbolKind::Variable,
+            format!(
+                "var {}: {}",
+                statement.identifier.span.literal, statement.data_type
+            ),
+            Some(statement.data_type.to_string()),
+        );
+    }
+
+    fn visit_compound_statement(&mut self, statement: &super::ASTCompoundStatement) {
+        self.scopes.push(HashMap::new());
+        for statement in statement.statements.iter() {
+            self.visit_statement(statement);
+        }
+        self.scopes.pop();
+    }
+
+    fn visit_if_statement(&mut self, statement: &super::ASTIfStatement) {
+        self.visit_expression(&statement.condition);
+        self.visit_statement(&statement.then_branch);
+        if let Some(else_branch) = &statement.else_branch {
+            self.visit_statement(&else_branch.else_branch);
+        }
+    }
+
+    fn visit_for_loop_statement(&mut self, statement: &super::ASTForStatement) {
+        match &statement.iterable {
+            ASTForIterable::Range(start, end) => {
+                self.visit_expression(start);
+                self.visit_expression(end);
+            }
+            ASTForIterable::Array(array) => self.visit_expression(array),
+        }
+        self.scopes.push(HashMap::new());
+        self.declare_local(
+            &statement.loop_variable,
+            SymbolKind::Variable,
+            format!("for {}", statement.loop_variable.span.literal),
+            None,
+        );
+        self.visit_statement(&statement.body);
+        self.scopes.pop();
+    }
+
+    fn visit_while_loop_statement(&mut self, statement: &super::ASTWhileStatement) {
+        self.visit_expression(&statement.condition);
+        self.visit_statement(&statement.body);
+    }
+
+    fn visit_funtion_statement(&mut self, function: &ASTFunctionStatement) {
+        self.visit_function_body(function, None);
+    }
+
+    fn visit_struct_statement(&mut self, statement: &ASTStructStatement) {
+        for field in statement.fields.iter() {
+            self.visit_type(&field.data_type);
+        }
+        for method in statement.methods.iter() {
+            self.visit_function_body(method, Some(&statement.identifier.span.literal));
+        }
+    }
+
+    fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {
+        self.resolve_variable(&expr.identifier);
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_function_call_expression(&mut self, expr: &super::ASTFunctionCallExpression) {
+        if expr.module.is_none() {
+            if let Some(id) = self.functions.get(expr.identifier()).copied() {
+                self.add_reference(&expr.identifier.span, id);
+            }
+        }
+        for argument in expr.arguments.iter() {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_method_call_expression(&mut self, expr: &super::ASTMethodCallExpression) {
+        self.visit_expression(&expr.object);
+        let method = self
+            .struct_of(&expr.object)
+            .and_then(|struct_id| self.member(struct_id, &expr.method.span.literal));
+        if let Some(id) = method {
+            self.add_reference(&expr.method.span, id);
+        }
+        for argument in expr.arguments.iter() {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_variable_expression(&mut self, expr: &super::ASTVariableExpression) {
+        self.resolve_variable(&expr.identifier);
+    }
+
+    fn visit_struct_literal_expression(&mut self, expr: &super::ASTStructLiteralExpression) {
+        let struct_id = self.structs.get(&expr.identifier.span.literal).copied();
+        if let Some(struct_id) = struct_id {
+            self.add_reference(&expr.identifier.span, struct_id);
+        }
+        for field in expr.fields.iter() {
+            let member = struct_id.and_then(|id| self.member(id, &field.identifier.span.literal));
+            if let Some(id) = member {
+                self.add_reference(&field.identifier.span, id);
+            }
+            self.visit_expression(&field.expr);
+        }
+    }
+
+    fn visit_field_access_expression(&mut self, expr: &super::ASTFieldAccessExpression) {
+        self.visit_expression(&expr.object);
+        let field = self
+            .struct_of(&expr.object)
+            .and_then(|struct_id| self.member(struct_id, &expr.field.span.literal));
+        if let Some(id) = field {
+            self.add_reference(&expr.field.span, id);
+        }
+    }
+
+    fn visit_field_assignment_expression(&mut self, expr: &super::ASTFieldAssignmentExpression) {
+        self.visit_field_access_expression(&expr.target);
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_array_literal_expression(&mut self, expr: &super::ASTArrayLiteralExpression) {
+        for element in expr.elements.iter() {
+            self.visit_expression(element);
+        }
+    }
+
+    fn visit_index_expression(&mut self, expr: &super::ASTIndexExpression) {
+        self.visit_expression(&expr.object);
+        self.visit_expression(&expr.index);
+    }
+
+    fn visit_index_assignment_expression(&mut self, expr: &super::ASTIndexAssignmentExpression) {
+        self.visit_index_expression(&expr.target);
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_address_of_expression(&mut self, expr: &super::ASTAddressOfExpression) {
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_dereference_expression(&mut self, expr: &super::ASTDereferenceExpression) {
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_dereference_assignment_expression(
+        &mut self,
+        expr: &super::ASTDereferenceAssignmentExpression,
+    ) {
+        self.visit_dereference_expression(&expr.target);
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_unary_expression(&mut self, expr: &super::ASTUnaryExpression) {
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_binary_expression(&mut self, expr: &super::ASTBinaryExpression) {
+        self.visit_expression(&expr.left);
+        self.visit_expression(&expr.right);
+    }
+
+    fn visit_parenthesised_expression(&mut self, expr: &super::ASTParenthesizedExpression) {
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_integer(&mut self, _integer: &i128) {}
+    fn visit_float(&mut self, _float: &f64) {}
+    fn visit_string(&mut self, _string: &str) {}
+    fn visit_character(&mut self, _character: &char) {}
+    fn visit_boolean(&mut self, _boolean: &bool) {}
+    fn visit_null(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::ast::parser::Parser;
+    use crate::ast::Ast;
+    use crate::diagnostics::DiagnosticsColletion;
+
+    use super::{SymbolIndex, SymbolKind};
+
+    fn index(input: &str) -> SymbolIndex {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let mut parser = Parser::from_input(input.to_string(), diagnostics_colletion);
+        let mut ast = Ast::new();
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        SymbolIndex::new(&ast)
+    }
+
+    // Declaration the identifier at the `n`th occurrence of `name` refers to
+    fn declaration_of(input: &str, index: &SymbolIndex, name: &str, n: usize) -> String {
+        let offset = input.match_indices(name).nth(n).unwrap().0;
+        let (_, id) = index.symbol_at(offset).unwrap();
+        index.symbol(id).detail.clone()
+    }
+
+    #[test]
+    fn should_resolve_identifiers_by_scope() {
+        let input = "\
+let a: i32 = twice(1);
+func twice(a: i32) -> i32 {
+    return a * 2;
+}
+struct P {
+    x: i32,
+    func get(self) -> i32 { return self.x; }
+}
+let p: P = P { x: a };
+p.get();";
+        let index = index(input);
+        assert_eq!(
+            declaration_of(input, &index, "twice", 0),
+            "func twice(a: i32) -> i32"
+        );
+        assert_eq!(declaration_of(input, &index, "a", 1), "a: i32");
+        assert_eq!(declaration_of(input, &index, "a", 2), "a: i32");
+        assert_eq!(declaration_of(input, &index, "a }", 0), "let a: i32");
+        assert_eq!(declaration_of(input, &index, "x", 2), "P.x: i32");
+        assert_eq!(
+            declaration_of(input, &index, "get", 1),
+            "func get(self) -> i32"
+        );
+
+        let (_, p) = index.symbol_at(input.find("p:").unwrap()).unwrap();
+        assert_eq!(index.references(p).len(), 1);
+        let kinds: Vec<SymbolKind> = index.symbols().iter().map(|symbol| symbol.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SymbolKind::Function,
+                SymbolKind::Struct,
+                SymbolKind::Field,
+                SymbolKind::Method,
+                SymbolKind::Variable,
+                SymbolKind::Parameter,
+                SymbolKind::Parameter,
+                SymbolKind::Variable,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_resolve_shadowed_variables_to_the_innermost_declaration() {
+        let input = "\
+let x: i32 = 1;
+{
+    var x: i64 = x;
+    x;
+}
+x;
+for x in 0..2 { x; }";
+        let index = index(input);
+        assert_eq!(declaration_of(input, &index, "x", 2), "let x: i32");
+        assert_eq!(declaration_of(input, &index, "x", 3), "var x: i64");
+        assert_eq!(declaration_of(input, &index, "x", 4), "let x: i32");
+        assert_eq!(declaration_of(input, &index, "x", 6), "for x");
+
+        let (_, outer) = index.symbol_at(input.find("x").unwrap()).unwrap();
+        assert_eq!(index.references(outer).len(), 2);
+    }
+
+    #[test]
+    fn should_only_resolve_globals_and_parameters_in_function_bodies() {
+        let input = "\
+let outer: i32 = 1;
+{
+    let inner: i32 = 2;
+    func f(param: i32) -> i32 { return outer + inner + param; }
+    inner;
+}
+func g() -> i32 { return outer; }";
+        let index = index(input);
+        assert_eq!(declaration_of(input, &index, "outer", 1), "let outer: i32");
+        assert_eq!(declaration_of(input, &index, "param", 1), "param: i32");
+        assert_eq!(declaration_of(input, &index, "inner", 2), "let inner: i32");
+        let inside_function = input.match_indices("inner").nth(1).unwrap().0;
+        assert!(index.symbol_at(inside_function).is_none());
+
+        let (_, outer) = index.symbol_at(input.find("outer").unwrap()).unwrap();
+        assert_eq!(index.references(outer).len(), 2);
+    }
+}
@@ -0,0 +1,1574 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::diagnostics::DiagnosticsColletionCell;
+
+use super::lexer::{TextSpan, Token, TokenKind};
+use super::{
+    ASTBinaryOperatorKind, ASTExpressionKind, ASTForIterable, ASTFunctionStatement, ASTStatement,
+    ASTStatementKind, ASTType, ASTTypeKind, ASTUnaryOperatorKind, ASTVisitor, Ast,
+};
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Type {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Bool,
+    Char,
+    Str,
+    Void,
+    Struct(String),
+    Pointer(Box<Type>),
+    Array(Box<Type>, usize),
+    // either a value of the type or `null`
+    Optional(Box<Type>),
+    // type of the `null` literal, which converts to any pointer or optional
+    Null,
+    // Literals without a declared type adapt to the type they are used with
+    IntegerLiteral,
+    FloatLiteral,
+    // Already reported, suppresses follow-up errors
+    Error,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::I8 => write!(f, "i8"),
+            Type::I16 => write!(f, "i16"),
+            Type::I32 => write!(f, "i32"),
+            Type::I64 => write!(f, "i64"),
+            Type::U8 => write!(f, "u8"),
+            Type::U16 => write!(f, "u16"),
+            Type::U32 => write!(f, "u32"),
+            Type::U64 => write!(f, "u64"),
+            Type::F32 => write!(f, "f32"),
+            Type::F64 => write!(f, "f64"),
+            Type::Bool => write!(f, "bool"),
+            Type::Char => write!(f, "char"),
+            Type::Str => write!(f, "str"),
+            Type::Void => write!(f, "void"),
+            Type::IntegerLiteral => write!(f, "{{integer}}"),
+            Type::FloatLiteral => write!(f, "{{float}}"),
+            Type::Struct(name) => write!(f, "{}", name),
+            // `*T?` is an optional pointer, a pointer to an optional is `*(T?)`
+            Type::Pointer(pointee) if matches!(**pointee, Type::Optional(_)) => {
+                write!(f, "*({})", pointee)
+            }
+            Type::Pointer(pointee) => write!(f, "*{}", pointee),
+            Type::Optional(data_type) if matches!(**data_type, Type::Optional(_)) => {
+                write!(f, "({})?", data_type)
+            }
+            Type::Optional(data_type) => write!(f, "{}?", data_type),
+            Type::Array(element_type, length) => write!(f, "[{}; {}]", element_type, length),
+            Type::Null => write!(f, "null"),
+            Type::Error => write!(f, "?"),
+        }
+    }
+}
+
+impl Type {
+    fn from_token(token: &Token) -> Option<Type> {
+        match token.kind {
+            TokenKind::I8 => Some(Type::I8),
+            TokenKind::I16 => Some(Type::I16),
+            TokenKind::I32 => Some(Type::I32),
+            TokenKind::I64 => Some(Type::I64),
+            TokenKind::U8 => Some(Type::U8),
+            TokenKind::U16 => Some(Type::U16),
+            TokenKind::U32 => Some(Type::U32),
+            TokenKind::U64 => Some(Type::U64),
+            TokenKind::F32 => Some(Type::F32),
+            TokenKind::F64 => Some(Type::F64),
+            TokenKind::Bool => Some(Type::Bool),
+            TokenKind::Char => Some(Type::Char),
+            TokenKind::Str => Some(Type::Str),
+            TokenKind::Void => Some(Type::Void),
+            _ => None,
+        }
+    }
+
+    // Types that are known without any declarations, None for struct names
+    pub fn from_ast(data_type: &ASTType) -> Option<Type> {
+        match &data_type.kind {
+            ASTTypeKind::Named(token) => Self::from_token(token),
+            ASTTypeKind::Pointer(pointee) => {
+                Self::from_ast(pointee).map(|pointee| Type::Pointer(Box::new(pointee)))
+            }
+            ASTTypeKind::Optional(data_type) => {
+                Self::from_ast(data_type).map(|data_type| Type::Optional(Box::new(data_type)))
+            }
+            ASTTypeKind::Array(element_type, length) => Self::from_ast(element_type)
+                .map(|element_type| Type::Array(Box::new(element_type), *length)),
+            ASTTypeKind::Error => Some(Type::Error),
+        }
+    }
+
+    // Types that can hold `null`
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, Type::Pointer(_) | Type::Optional(_) | Type::Null)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Type::I8
+                | Type::I16
+                | Type::I32
+                | Type::I64
+                | Type::U8
+                | Type::U16
+                | Type::U32
+                | Type::U64
+                | Type::IntegerLiteral
+        )
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::F32 | Type::F64 | Type::FloatLiteral)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    pub fn is_unsigned(&self) -> bool {
+        matches!(self, Type::U8 | Type::U16 | Type::U32 | Type::U64)
+    }
+
+    // Smallest and largest value of an integer type
+    fn integer_range(&self) -> Option<(i128, i128)> {
+        match self {
+            Type::I8 => Some((i8::MIN.into(), i8::MAX.into())),
+            Type::I16 => Some((i16::MIN.into(), i16::MAX.into())),
+            Type::I32 => Some((i32::MIN.into(), i32::MAX.into())),
+            Type::I64 => Some((i64::MIN.into(), i64::MAX.into())),
+            Type::U8 => Some((0, u8::MAX.into())),
+            Type::U16 => Some((0, u16::MAX.into())),
+            Type::U32 => Some((0, u32::MAX.into())),
+            Type::U64 => Some((0, u64::MAX.into())),
+            _ => None,
+        }
+    }
+
+    fn bit_width(&self) -> usize {
+        match self {
+            Type::I8 | Type::U8 => 8,
+            Type::I16 | Type::U16 => 16,
+            Type::I32 | Type::U32 | Type::F32 => 32,
+            Type::I64 | Type::U64 | Type::F64 => 64,
+            _ => 0,
+        }
+    }
+
+    // The type a literal settles on when nothing else decides it
+    pub fn concrete(&self) -> Type {
+        match self {
+            Type::IntegerLiteral => Type::I32,
+            Type::FloatLiteral => Type::F64,
+            Type::Array(element_type, length) => {
+                Type::Array(Box::new(element_type.concrete()), *length)
+            }
+            _ => self.clone(),
+        }
+    }
+
+    pub fn is_assignable_to(&self, target: &Type) -> bool {
+        match (self, target) {
+            (Type::Error, _) | (_, Type::Error) => true,
+            (Type::Null, target) => target.is_nullable(),
+            (Type::Optional(source), Type::Optional(target)) => source.is_assignable_to(target),
+            (source, Type::Optional(target)) => source.is_assignable_to(target),
+            (Type::Array(source, source_length), Type::Array(target, target_length)) => {
+                source_length == target_length && source.is_assignable_to(target)
+            }
+            (Type::IntegerLiteral, target) => target.is_numeric(),
+            (Type::FloatLiteral, target) => target.is_float(),
+            (source, target) => source == target,
+        }
+    }
+
+    // Whether every value of this type is also a value of the target: integers
+    // of the same signedness widen to a wider integer, unsigned integers also
+    // widen to a strictly wider signed one and floats widen to a wider float.
+    fn widens_to(&self, target: &Type) -> bool {
+        if self == target {
+            return true;
+        }
+        if self.is_float() || target.is_float() {
+            return self.is_float() && target.is_float() && self.bit_width() <= target.bit_width();
+        }
+        if !self.is_integer() || !target.is_integer() {
+            return false;
+        }
+        match (self.is_unsigned(), target.is_unsigned()) {
+            (false, true) => false,
+            (true, false) => self.bit_width() < target.bit_width(),
+            _ => self.bit_width() <= target.bit_width(),
+        }
+    }
+
+    // Operands are promoted to the type the other one widens to without loss
+    // and literals take the type of the other operand. Mixed signedness without
+    // such a type (e.g. `i32` and `u32`, or any signed type and `u64`) is
+    // rejected, as are integers mixed with floats unless the integer is a literal.
+    pub fn arithmetic_result(left: &Type, right: &Type) -> Option<Type> {
+        if *left == Type::Error || *right == Type::Error {
+            return Some(Type::Error);
+        }
+        if !left.is_numeric() || !right.is_numeric() {
+            return None;
+        }
+        match (left, right) {
+            (Type::IntegerLiteral, other) | (other, Type::IntegerLiteral) => Some(other.clone()),
+            (Type::FloatLiteral, other) | (other, Type::FloatLiteral) => {
+                other.is_float().then(|| other.clone())
+            }
+            (left, right) if left.widens_to(right) => Some(right.clone()),
+            (left, right) if right.widens_to(left) => Some(left.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct FunctionSignature {
+    arguments: Vec<Type>,
+    return_type: Type,
+}
+
+#[derive(Clone)]
+struct Scope {
+    variables: HashMap<String, Type>,
+    // optional variables known to be non-null in this scope, mapped to the unwrapped type
+    narrowed: HashMap<String, Type>,
+}
+
+impl Scope {
+    fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+            narrowed: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, identifier: String, data_type: Type) {
+        self.variables.insert(identifier, data_type);
+    }
+}
+
+#[derive(Clone)]
+pub struct TypeChecker {
+    result: Type,
+    scopes: Vec<Scope>,
+    functions: HashMap<String, FunctionSignature>,
+    structs: HashMap<String, Vec<(String, Type)>>,
+    methods: HashMap<String, HashMap<String, FunctionSignature>>,
+    // functions of the modules checked before, by module name
+    modules: HashMap<String, HashMap<String, FunctionSignature>>,
+    return_types: Vec<Type>,
+    // scripts may return from the top level, programs start at `main`
+    script_mode: bool,
+    diagnostics: DiagnosticsColletionCell,
+}
+
+impl TypeChecker {
+    pub fn new(diagnostics: DiagnosticsColletionCell) -> Self {
+        Self {
+            result: Type::Void,
+            scopes: vec![Scope::new()],
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            methods: HashMap::new(),
+            modules: HashMap::new(),
+            return_types: Vec::new(),
+            script_mode: true,
+            diagnostics,
+        }
+    }
+
+    pub fn set_script_mode(&mut self, script_mode: bool) {
+        self.script_mode = script_mode;
+    }
+
+    // A program starts at `func main() -> i32` of its entry module, which is
+    // checked after the module itself
+    pub fn check_entry_point(&mut self, ast: &Ast) {
+        let entry_points: Vec<&ASTFunctionStatement> = ast
+            .functions()
+            .into_iter()
+            .filter(|function| function.identifier.span.literal == "main")
+            .collect();
+        let Some((main, duplicates)) = entry_points.split_first() else {
+            self.diagnostics
+                .borrow_mut()
+                .report_missing_entry_point(TextSpan::new(0, 0, String::new()));
+            return;
+        };
+        for duplicate in duplicates {
+            self.diagnostics
+                .borrow_mut()
+                .report_duplicate_entry_point(&duplicate.identifier, &main.identifier);
+        }
+        let returns_exit_code = matches!(
+            Type::from_ast(&main.return_type),
+            Some(Type::I32 | Type::Void)
+        );
+        if !main.arguments.is_empty() || !returns_exit_code {
+            self.diagnostics
+                .borrow_mut()
+                .report_invalid_entry_point(&main.identifier);
+        }
+    }
+
+    // Checks a module with its own global scope, its functions can be called
+    // by the modules checked afterwards
+    pub fn check_module(&mut self, module: &str, ast: &Ast) {
+        self.scopes = vec![Scope::new()];
+        ast.visit(self);
+        let functions = std::mem::take(&mut self.functions);
+        self.modules.insert(module.to_string(), functions);
+    }
+
+    fn enter_scope(&mut self, scope_variables: Scope) {
+        self.scopes.push(scope_variables);
+    }
+
+    fn leave_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn add_identifier_to_scope(&mut self, identifier: &str, data_type: Type) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(identifier.to_string(), data_type);
+    }
+
+    fn get_identifier_type(&self, identifier: &str) -> Type {
+        for scope in self.scopes.iter().rev() {
+            if let Some(data_type) = scope
+                .variables
+                .get(identifier)
+                .or_else(|| scope.narrowed.get(identifier))
+            {
+                return data_type.clone();
+            }
+        }
+        Type::Error
+    }
+
+    // Type the variable was declared with, ignoring null checks
+    fn get_declared_type(&self, identifier: &str) -> Type {
+        for scope in self.scopes.iter().rev() {
+            if let Some(data_type) = scope.variables.get(identifier) {
+                return data_type.clone();
+            }
+        }
+        Type::Error
+    }
+
+    // Scope in which the given optional variables are treated as non-null
+    fn narrowing_scope(&self, identifiers: &[String]) -> Scope {
+        let mut scope = Scope::new();
+        for identifier in identifiers {
+            if let Type::Optional(data_type) = self.get_identifier_type(identifier) {
+                scope.narrowed.insert(identifier.clone(), *data_type);
+            }
+        }
+        scope
+    }
+
+    fn visit_narrowed_statement(
+        &mut self,
+        statement: &super::ASTStatement,
+        identifiers: &[String],
+    ) {
+        let scope = self.narrowing_scope(identifiers);
+        self.enter_scope(scope);
+        self.visit_statement(statement);
+        self.leave_scope();
+    }
+
+    // Variables that are known to be non-null when the condition is true and
+    // when it is false, e.g. `x != null && y != null` checks both when true
+    fn null_checks(condition: &super::ASTExpression) -> (Vec<String>, Vec<String>) {
+        match &condition.kind {
+            ASTExpressionKind::Binary(expr) => {
+                let compared_variable = match (&expr.left.kind, &expr.right.kind) {
+                    (ASTExpressionKind::Variable(variable), ASTExpressionKind::Null)
+                    | (ASTExpressionKind::Null, ASTExpressionKind::Variable(variable)) => {
+                        Some(variable.identifier().to_string())
+                    }
+                    _ => None,
+                };
+                match (&expr.operator.kind, compared_variable) {
+                    (ASTBinaryOperatorKind::NotEqualTo, Some(variable)) => {
+                        (vec![variable], Vec::new())
+                    }
+                    (ASTBinaryOperatorKind::EqualTo, Some(variable)) => {
+                        (Vec::new(), vec![variable])
+                    }
+                    (ASTBinaryOperatorKind::LogicAND, _) => {
+                        let (mut when_true, _) = Self::null_checks(&expr.left);
+                        when_true.extend(Self::null_checks(&expr.right).0);
+                        (when_true, Vec::new())
+                    }
+                    (ASTBinaryOperatorKind::LogicOR, _) => {
+                        let (_, mut when_false) = Self::null_checks(&expr.left);
+                        when_false.extend(Self::null_checks(&expr.right).1);
+                        (Vec::new(), when_false)
+                    }
+                    _ => (Vec::new(), Vec::new()),
+                }
+            }
+            ASTExpressionKind::Unary(expr)
+                if expr.operator.kind == ASTUnaryOperatorKind::LogicNot =>
+            {
+                let (when_true, when_false) = Self::null_checks(&expr.expr);
+                (when_false, when_true)
+            }
+            ASTExpressionKind::Parenthesized(expr) => Self::null_checks(&expr.expr),
+            _ => (Vec::new(), Vec::new()),
+        }
+    }
+
+    // Optional values have to be checked for null before they are used
+    fn unwrap_optional(&mut self, span: TextSpan, data_type: Type) -> Type {
+        match data_type {
+            Type::Optional(inner) => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_unchecked_optional(span, &Type::Optional(inner.clone()));
+                *inner
+            }
+            data_type => data_type,
+        }
+    }
+
+    fn resolve_type(&mut self, data_type: &ASTType) -> Type {
+        match &data_type.kind {
+            ASTTypeKind::Named(token) => match Type::from_token(token) {
+                Some(data_type) => data_type,
+                None if self.structs.contains_key(&token.span.literal) => {
+                    Type::Struct(token.span.literal.clone())
+                }
+                None => {
+                    self.diagnostics.borrow_mut().report_unknown_type(token);
+                    Type::Error
+                }
+            },
+            ASTTypeKind::Pointer(pointee) => Type::Pointer(Box::new(self.resolve_type(pointee))),
+            ASTTypeKind::Optional(data_type) => {
+                Type::Optional(Box::new(self.resolve_type(data_type)))
+            }
+            ASTTypeKind::Array(element_type, length) => {
+                Type::Array(Box::new(self.resolve_type(element_type)), *length)
+            }
+            // reported by the parser
+            ASTTypeKind::Error => Type::Error,
+        }
+    }
+
+    fn check_expression(&mut self, expr: &super::ASTExpression, expected: &Type) {
+        self.visit_expression(expr);
+        if let Type::Optional(inner) = &self.result {
+            if !self.result.is_assignable_to(expected) && inner.is_assignable_to(expected) {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_unchecked_optional(expr.span().clone(), &self.result);
+                return;
+            }
+        }
+        if !self.result.is_assignable_to(expected) {
+            self.diagnostics.borrow_mut().report_type_mismatch(
+                expr.span().clone(),
+                expected,
+                &self.result,
+            );
+            return;
+        }
+        // literals and constant expressions would wrap around when they are
+        // cast to the declared type
+        let expected = match expected {
+            Type::Optional(inner) => inner,
+            expected => expected,
+        };
+        if let (Some(value), Some((min, max))) = (constant_integer(expr), expected.integer_range())
+        {
+            if value >= min && value <= max {
+                return;
+            }
+            let mut diagnostics = self.diagnostics.borrow_mut();
+            match literal_integer(expr) {
+                Some(_) => diagnostics.report_integer_literal_out_of_range(
+                    expr.span().clone(),
+                    value,
+                    expected,
+                ),
+                None => {
+                    diagnostics.report_constant_out_of_range(expr.span().clone(), value, expected)
+                }
+            }
+        }
+    }
+
+    fn function_signature(&mut self, function: &ASTFunctionStatement) -> FunctionSignature {
+        FunctionSignature {
+            arguments: function
+                .arguments
+                .iter()
+                .map(|arg| self.resolve_type(&arg.data_type))
+                .collect(),
+            return_type: self.resolve_type(&function.return_type),
+        }
+    }
+
+    fn visit_function_body(
+        &mut self,
+        function: &ASTFunctionStatement,
+        signature: &FunctionSignature,
+        mut scope: Scope,
+    ) {
+        for (arg, data_type) in function.arguments.iter().zip(signature.arguments.iter()) {
+            scope.insert(arg.identifier.span.literal.clone(), data_type.clone());
+        }
+        // like at run time, the body only sees the globals of the module
+        let enclosing_scopes = self.scopes.split_off(1);
+        self.enter_scope(scope);
+        self.return_types.push(signature.return_type.clone());
+        self.visit_statement(&function.body);
+        self.return_types.pop();
+        self.leave_scope();
+        self.scopes.extend(enclosing_scopes);
+
+        let returns_value = !matches!(signature.return_type, Type::Void | Type::Error);
+        if let ASTStatementKind::Compound(body) = &function.body.kind {
+            if returns_value && !always_returns(&function.body) {
+                self.diagnostics.borrow_mut().report_missing_return(
+                    &function.identifier,
+                    body.close_brace.span.clone(),
+                    &signature.return_type,
+                );
+            }
+        }
+    }
+
+    fn check_arguments(&mut self, arguments: &[super::ASTExpression], expected: &[Type]) {
+        for (index, arg) in arguments.iter().enumerate() {
+            match expected.get(index) {
+                Some(expected) => self.check_expression(arg, expected),
+                None => self.visit_expression(arg),
+            }
+        }
+    }
+
+    // Type of the loop variable of a `start..end` range
+    fn range_type(&mut self, start: &super::ASTExpression, end: &super::ASTExpression) -> Type {
+        self.visit_expression(start);
+        let start_type = self.unwrap_optional(start.span().clone(), self.result.clone());
+        self.visit_expression(end);
+        let end_type = self.unwrap_optional(end.span().clone(), self.result.clone());
+
+        match Type::arithmetic_result(&start_type, &end_type) {
+            Some(data_type) if data_type.is_integer() || data_type == Type::Error => {
+                data_type.concrete()
+            }
+            _ => {
+                self.diagnostics.borrow_mut().report_invalid_range(
+                    TextSpan::combine(vec![start.span().clone(), end.span().clone()]),
+                    &start_type,
+                    &end_type,
+                );
+                Type::Error
+            }
+        }
+    }
+
+    // Indices known at compile time are checked against the array length
+    fn check_constant_index(&mut self, index: &super::ASTExpression, length: usize) {
+        let Some(value) = constant_integer(index) else {
+            return;
+        };
+        if value < 0 || value as usize >= length {
+            self.diagnostics.borrow_mut().report_index_out_of_bounds(
+                index.span().clone(),
+                value,
+                length,
+            );
+        }
+    }
+
+    fn visit_declaration(
+        &mut self,
+        identifier: &Token,
+        data_type: &ASTType,
+        initializer: &super::ASTExpression,
+    ) {
+        let declared_type = self.resolve_type(data_type);
+        self.check_expression(initializer, &declared_type);
+        self.add_identifier_to_scope(&identifier.span.literal, declared_type);
+    }
+}
+
+impl ASTVisitor for TypeChecker {
+    fn visit_return_statement(&mut self, statement: &super::ASTReturnStatement) {
+        match self.return_types.last().cloned() {
+            Some(return_type) => self.check_expression(&statement.expr, &return_type),
+            None => {
+                if !self.script_mode {
+                    self.diagnostics
+                        .borrow_mut()
+                        .report_return_outside_of_function(&statement.keyword);
+                }
+                self.visit_expression(&statement.expr);
+            }
+        }
+    }
+
+    fn visit_let_statement(&mut self, statement: &super::ASTLetStatement) {
+        self.visit_declaration(
+            &statement.identifier,
+            &statement.data_type,
+            &statement.initializer,
+        );
+    }
+
+    fn visit_var_statement(&mut self, statement: &super::ASTVarStatement) {
+        self.visit_declaration(
+            &statement.identifier,
+            &statement.data_type,
+            &statement.initializer,
+        );
+    }
+
+    fn visit_compound_statement(&mut self, statement: &super::ASTCompoundStatement) {
+        self.enter_scope(Scope::new());
+        for statement in statement.statements.iter() {
+            self.visit_statement(statement);
+        }
+        self.leave_scope();
+    }
+
+    fn visit_if_statement(&mut self, statement: &super::ASTIfStatement) {
+        self.check_expression(&statement.condition, &Type::Bool);
+        let (when_true, when_false) = Self::null_checks(&statement.condition);
+        self.visit_narrowed_statement(&statement.then_branch, &when_true);
+        if let Some(else_branch) = &statement.else_branch {
+            self.visit_narrowed_statement(&else_branch.else_branch, &when_false);
+        }
+    }
+
+    fn visit_for_loop_statement(&mut self, statement: &super::ASTForStatement) {
+        let loop_variable_type = match &statement.iterable {
+            ASTForIterable::Range(start, end) => self.range_type(start, end),
+            ASTForIterable::Array(array) => {
+                self.visit_expression(array);
+                match self.unwrap_optional(array.span().clone(), self.result.clone()) {
+                    Type::Array(element_type, _) => element_type.concrete(),
+                    Type::Error => Type::Error,
+                    data_type => {
+                        self.diagnostics
+                            .borrow_mut()
+                            .report_not_iterable(array.span().clone(), &data_type);
+                        Type::Error
+                    }
+                }
+            }
+        };
+
+        let mut loop_scope = Scope::new();
+        loop_scope.insert(
+            statement.loop_variable.span.literal.clone(),
+            loop_variable_type,
+        );
+        self.enter_scope(loop_scope);
+        self.visit_statement(&statement.body);
+        self.leave_scope();
+    }
+
+    fn visit_while_loop_statement(&mut self, statement: &super::ASTWhileStatement) {
+        self.check_expression(&statement.condition, &Type::Bool);
+        let (when_true, _) = Self::null_checks(&statement.condition);
+        self.visit_narrowed_statement(&statement.body, &when_true);
+    }
+
+    fn visit_funtion_statement(&mut self, function: &super::ASTFunctionStatement) {
+        let signature = self.function_signature(function);
+        // registered before the body is checked to allow recursive calls
+        self.functions
+            .insert(function.identifier.span.literal.clone(), signature.clone());
+        self.visit_function_body(function, &signature, Scope::new());
+    }
+
+    fn visit_assignment_expression(&mut self, expr: &super::ASTAssignmentExpression) {
+        let identifier = &expr.identifier.span.literal;
+        let variable_type = self.get_declared_type(identifier);
+        self.check_expression(&expr.expr, &variable_type);
+        // a value that may be null undoes previous null checks of the variable
+        if matches!(self.result, Type::Null | Type::Optional(_)) {
+            for scope in self.scopes.iter_mut() {
+                scope.narrowed.remove(identifier);
+            }
+        }
+        self.result = variable_type;
+    }
+
+    fn visit_struct_statement(&mut self, statement: &super::ASTStructStatement) {
+        let fields = statement
+            .fields
+            .iter()
+            .map(|field| {
+                (
+                    field.identifier.span.literal.clone(),
+                    self.resolve_type(&field.data_type),
+                )
+            })
+            .collect();
+        let struct_name = &statement.identifier.span.literal;
+        self.structs.insert(struct_name.clone(), fields);
+
+        let signatures: Vec<FunctionSignature> = statement
+            .methods
+            .iter()
+            .map(|method| self.function_signature(method))
+            .collect();
+        let mut methods: HashMap<String, FunctionSignature> = HashMap::new();
+        for (method, signature) in statement.methods.iter().zip(signatures.iter()) {
+            methods
+                .entry(method.identifier.span.literal.clone())
+                .or_insert(signature.clone());
+        }
+        self.methods.insert(struct_name.clone(), methods);
+
+        for (method, signature) in statement.methods.iter().zip(signatures.iter()) {
+            let mut scope = Scope::new();
+            if let Some(receiver) = &method.receiver {
+                scope.insert(
+                    receiver.span.literal.clone(),
+                    Type::Struct(struct_name.clone()),
+                );
+            }
+            self.visit_function_body(method, signature, scope);
+        }
+    }
+
+    fn visit_struct_literal_expression(&mut self, expr: &super::ASTStructLiteralExpression) {
+        let fields = self
+            .structs
+            .get(&expr.identifier.span.literal)
+            .cloned()
+            .unwrap_or_default();
+        for field in expr.fields.iter() {
+            match fields
+                .iter()
+                .find(|(name, _)| *name == field.identifier.span.literal)
+            {
+                Some((_, data_type)) => self.check_expression(&field.expr, data_type),
+                None => self.visit_expression(&field.expr),
+            }
+        }
+        // unknown structs and fields are reported by the symbol checker
+        self.result = if self.structs.contains_key(&expr.identifier.span.literal) {
+            Type::Struct(expr.identifier.span.literal.clone())
+        } else {
+            Type::Error
+        };
+    }
+
+    fn visit_field_access_expression(&mut self, expr: &super::ASTFieldAccessExpression) {
+        self.visit_expression(&expr.object);
+        let object_type = self.unwrap_optional(expr.object.span().clone(), self.result.clone());
+        let field_type = match &object_type {
+            Type::Error => Some(Type::Error),
+            Type::Struct(name) => self.structs.get(name).and_then(|fields| {
+                fields
+                    .iter()
+                    .find(|(name, _)| *name == expr.field.span.literal)
+                    .map(|(_, data_type)| data_type.clone())
+            }),
+            _ => None,
+        };
+        self.result = field_type.unwrap_or_else(|| {
+            self.diagnostics
+                .borrow_mut()
+                .report_unknown_field(&expr.field, &object_type);
+            Type::Error
+        });
+    }
+
+    fn visit_field_assignment_expression(&mut self, expr: &super::ASTFieldAssignmentExpression) {
+        self.visit_field_access_expression(&expr.target);
+        let field_type = self.result.clone();
+        self.check_expression(&expr.expr, &field_type);
+        self.result = field_type;
+    }
+
+    fn visit_function_call_expression(&mut self, expr: &super::ASTFunctionCallExpression) {
+        let functions = match expr.module() {
+            Some(module) => self.modules.get(module),
+            None => Some(&self.functions),
+        };
+        let (argument_types, return_type) =
+            match functions.and_then(|functions| functions.get(expr.identifier())) {
+                Some(signature) => (signature.arguments.clone(), signature.return_type.clone()),
+                None => (Vec::new(), Type::Error),
+            };
+
+        self.check_arguments(&expr.arguments, &argument_types);
+        self.result = return_type;
+    }
+
+    fn visit_method_call_expression(&mut self, expr: &super::ASTMethodCallExpression) {
+        self.visit_expression(&expr.object);
+        let object_type = self.unwrap_optional(expr.object.span().clone(), self.result.clone());
+        let signature = match &object_type {
+            Type::Struct(name) => self
+                .methods
+                .get(name)
+                .and_then(|methods| methods.get(&expr.method.span.literal))
+                .cloned(),
+            _ => None,
+        };
+
+        let Some(signature) = signature else {
+            // unknown methods of structs are reported by the symbol checker
+            if !matches!(object_type, Type::Struct(_) | Type::Error) {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_unknown_method(&expr.method, &object_type);
+            }
+            self.check_arguments(&expr.arguments, &[]);
+            self.result = Type::Error;
+            return;
+        };
+        self.check_arguments(&expr.arguments, &signature.arguments);
+        self.result = signature.return_type;
+    }
+
+    fn visit_array_literal_expression(&mut self, expr: &super::ASTArrayLiteralExpression) {
+        // the elements share the most specific type, `[1, a]` takes the type of `a`
+        let mut element_type: Option<Type> = None;
+        for element in expr.elements.iter() {
+            self.visit_expression(element);
+            let found = self.result.clone();
+            element_type = match element_type {
+                None => Some(found),
+                Some(expected) if found.is_assignable_to(&expected) => Some(expected),
+                Some(expected) if expected.is_assignable_to(&found) => Some(found),
+                Some(expected) => {
+                    self.diagnostics.borrow_mut().report_type_mismatch(
+                        element.span().clone(),
+                        &expected,
+                        &found,
+                    );
+                    Some(expected)
+                }
+            };
+        }
+        // an empty literal fits arrays of any element type
+        self.result = Type::Array(
+            Box::new(element_type.unwrap_or(Type::Error)),
+            expr.elements.len(),
+        );
+    }
+
+    fn visit_index_expression(&mut self, expr: &super::ASTIndexExpression) {
+        self.visit_expression(&expr.object);
+        let object_type = self.unwrap_optional(expr.object.span().clone(), self.result.clone());
+        self.visit_expression(&expr.index);
+        let index_type = self.unwrap_optional(expr.index.span().clone(), self.result.clone());
+        if !index_type.is_integer() && index_type != Type::Error {
+            self.diagnostics
+                .borrow_mut()
+                .report_invalid_index(expr.index.span().clone(), &index_type);
+        }
+
+        self.result = match object_type {
+            Type::Array(element_type, length) => {
+                self.check_constant_index(&expr.index, length);
+                *element_type
+            }
+            Type::Error => Type::Error,
+            data_type => {
+                self.diagnostics
+                    .borrow_mut()
+                    .report_not_indexable(expr.object.span().clone(), &data_type);
+                Type::Error
+            }
+        };
+    }
+
+    fn visit_index_assignment_expression(&mut self, expr: &super::ASTIndexAssignmentExpression) {
+        self.visit_index_expression(&expr.target);
+        let element_type = self.result.clone();
+        self.check_expression(&expr.expr, &element_type);
+        self.result = element_type;
+    }
+
+    fn visit_address_of_expression(&mut self, expr: &super::ASTAddressOfExpression) {
+        self.visit_expression(&expr.expr);
+        if self.result != Type::Error {
+            self.result = Type::Pointer(Box::new(self.result.clone()));
+        }
+    }
+
+    fn visit_dereference_expression(&mut self, expr: &super::ASTDereferenceExpression) {
+        self.visit_expression(&expr.expr);
+        self.result = self.unwrap_optional(expr.expr.span().clone(), self.result.clone());
+        self.result = match &self.result {
+            Type::Pointer(pointee) => *pointee.clone(),
+            Type::Error => Type::Error,
+            operand => {
+                self.diagnostics.borrow_mut().report_invalid_unary_operand(
+                    expr.span(),
+                    &expr.operator.kind,
+                    operand,
+                );
+                Type::Error
+            }
+        };
+    }
+
+    fn visit_dereference_assignment_expression(
+        &mut self,
+        expr: &super::ASTDereferenceAssignmentExpression,
+    ) {
+        self.visit_dereference_expression(&expr.target);
+        let pointee_type = self.result.clone();
+        self.check_expression(&expr.expr, &pointee_type);
+        self.result = pointee_type;
+    }
+
+    fn visit_variable_expression(&mut self, expr: &super::ASTVariableExpression) {
+        self.result = self.get_identifier_type(expr.identifier());
+    }
+
+    fn visit_unary_expression(&mut self, expr: &super::ASTUnaryExpression) {
+        self.visit_expression(&expr.expr);
+        let operand = self.unwrap_optional(expr.expr.span().clone(), self.result.clone());
+        if operand == Type::Error {
+            return;
+        }
+
+        let valid = match expr.operator.kind {
+            ASTUnaryOperatorKind::Minus => operand.is_numeric() && !operand.is_unsigned(),
+            ASTUnaryOperatorKind::BitwiseNOT => operand.is_integer(),
+            ASTUnaryOperatorKind::LogicNot => operand == Type::Bool,
+        };
+        if !valid {
+            self.diagnostics.borrow_mut().report_invalid_unary_operand(
+                TextSpan::combine(vec![
+                    expr.operator.token.span.clone(),
+                    expr.expr.span().clone(),
+                ]),
+                &expr.operator.token.kind,
+                &operand,
+            );
+            self.result = Type::Error;
+        }
+    }
+
+    fn visit_binary_expression(&mut self, expr: &super::ASTBinaryExpression) {
+        self.visit_expression(&expr.left);
+        let mut left = self.result.clone();
+        // the right operand is only evaluated if the left one did not decide the result
+        let (when_true, when_false) = Self::null_checks(&expr.left);
+        let scope = match expr.operator.kind {
+            ASTBinaryOperatorKind::LogicAND => self.narrowing_scope(&when_true),
+            ASTBinaryOperatorKind::LogicOR => self.narrowing_scope(&when_false),
+            _ => Scope::new(),
+        };
+        self.enter_scope(scope);
+        self.visit_expression(&expr.right);
+        self.leave_scope();
+        let mut right = self.result.clone();
+
+        // only comparing with null does not need a null check
+        let null_comparison = matches!(
+            expr.operator.kind,
+            ASTBinaryOperatorKind::EqualTo | ASTBinaryOperatorKind::NotEqualTo
+        ) && left.is_nullable()
+            && right.is_nullable();
+        if !null_comparison {
+            left = self.unwrap_optional(expr.left.span().clone(), left);
+            right = self.unwrap_optional(expr.right.span().clone(), right);
+        }
+
+        let result = match expr.operator.kind {
+            // `+` concatenates strings
+            ASTBinaryOperatorKind::Plus if left == Type::Str && right == Type::Str => {
+                Some(Type::Str)
+            }
+            ASTBinaryOperatorKind::Plus
+            | ASTBinaryOperatorKind::Minus
+            | ASTBinaryOperatorKind::Multiply
+            | ASTBinaryOperatorKind::Divide => Type::arithmetic_result(&left, &right),
+            ASTBinaryOperatorKind::BitwiseOR
+            | ASTBinaryOperatorKind::BitwiseAND
+            | ASTBinaryOperatorKind::BitwiseXOR => Type::arithmetic_result(&left, &right)
+                .filter(|data_type| data_type.is_integer() || *data_type == Type::Error),
+            // pointers and optionals are only compared for equality, `null` with any of them
+            ASTBinaryOperatorKind::EqualTo | ASTBinaryOperatorKind::NotEqualTo
+                if null_comparison =>
+            {
+                (left == right || left == Type::Null || right == Type::Null).then_some(Type::Bool)
+            }
+            ASTBinaryOperatorKind::EqualTo
+            | ASTBinaryOperatorKind::NotEqualTo
+            | ASTBinaryOperatorKind::GreaterThan
+            | ASTBinaryOperatorKind::GreaterThanOrEqual
+            | ASTBinaryOperatorKind::LessThan
+            | ASTBinaryOperatorKind::LessThanOrEqual => {
+                if (left == right && !left.is_nullable())
+                    || Type::arithmetic_result(&left, &right).is_some()
+                {
+                    Some(Type::Bool)
+                } else {
+                    None
+                }
+            }
+            ASTBinaryOperatorKind::LogicAND | ASTBinaryOperatorKind::LogicOR => {
+                match (&left, &right) {
+                    (Type::Error, _) | (_, Type::Error) => Some(Type::Error),
+                    (Type::Bool, Type::Bool) => Some(Type::Bool),
+                    _ => None,
+                }
+            }
+        };
+
+        self.result = match result {
+            Some(data_type) => data_type,
+            None => {
+                self.diagnostics.borrow_mut().report_incompatible_operands(
+                    TextSpan::combine(vec![expr.left.span().clone(), expr.right.span().clone()]),
+                    &expr.operator.token.kind,
+                    &left,
+                    &right,
+                );
+                Type::Error
+            }
+        };
+    }
+
+    fn visit_parenthesised_expression(&mut self, expr: &super::ASTParenthesizedExpression) {
+        self.visit_expression(&expr.expr);
+    }
+
+    fn visit_error(&mut self, _span: &TextSpan) {
+        self.result = Type::Error;
+    }
+
+    fn visit_integer(&mut self, _integer: &i128) {
+        self.result = Type::IntegerLiteral;
+    }
+
+    fn visit_float(&mut self, _float: &f64) {
+        self.result = Type::FloatLiteral;
+    }
+
+    fn visit_string(&mut self, _string: &str) {
+        self.result = Type::Str;
+    }
+
+    fn visit_character(&mut self, _character: &char) {
+        self.result = Type::Char;
+    }
+
+    fn visit_boolean(&mut self, _boolean: &bool) {
+        self.result = Type::Bool;
+    }
+
+    fn visit_null(&mut self) {
+        self.result = Type::Null;
+    }
+}
+
+// Value of an integer literal or of a negated one
+fn literal_integer(expr: &super::ASTExpression) -> Option<i128> {
+    match &expr.kind {
+        ASTExpressionKind::IntegerLiteral(value) => Some(*value),
+        ASTExpressionKind::Unary(unary) => match (&unary.operator.kind, &unary.expr.kind) {
+            (ASTUnaryOperatorKind::Minus, ASTExpressionKind::IntegerLiteral(value)) => Some(-value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Value of an expression of integer literals, computed exactly like the
+// interpreter does before the result is cast to the declared type
+fn constant_integer(expr: &super::ASTExpression) -> Option<i128> {
+    match &expr.kind {
+        ASTExpressionKind::Parenthesized(parenthesized) => constant_integer(&parenthesized.expr),
+        ASTExpressionKind::Unary(unary) => {
+            let value = constant_integer(&unary.expr)?;
+            match unary.operator.kind {
+                ASTUnaryOperatorKind::Minus => value.checked_neg(),
+                ASTUnaryOperatorKind::BitwiseNOT => Some(!value),
+                _ => None,
+            }
+        }
+        ASTExpressionKind::Binary(binary) => {
+            let left = constant_integer(&binary.left)?;
+            let right = constant_integer(&binary.right)?;
+            match binary.operator.kind {
+                ASTBinaryOperatorKind::Plus => left.checked_add(right),
+                ASTBinaryOperatorKind::Minus => left.checked_sub(right),
+                ASTBinaryOperatorKind::Multiply => left.checked_mul(right),
+                // division by zero is reported when the program runs
+                ASTBinaryOperatorKind::Divide => left.checked_div(right),
+                ASTBinaryOperatorKind::BitwiseAND => Some(left & right),
+                ASTBinaryOperatorKind::BitwiseOR => Some(left | right),
+                ASTBinaryOperatorKind::BitwiseXOR => Some(left ^ right),
+                _ => None,
+            }
+        }
+        _ => literal_integer(expr),
+    }
+}
+
+// Every path through the statement ends with a `return`, loops may not run at all
+fn always_returns(statement: &ASTStatement) -> bool {
+    match &statement.kind {
+        ASTStatementKind::Return(_) => true,
+        ASTStatementKind::Compound(compound) => compound.statements.iter().any(always_returns),
+        ASTStatementKind::If(statement) => {
+            statement.else_branch.as_ref().is_some_and(|else_branch| {
+                always_returns(&statement.then_branch) && always_returns(&else_branch.else_branch)
+            })
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::ast::parser::Parser;
+    use crate::ast::symbol_checker::SymbolChecker;
+    use crate::ast::Ast;
+    use crate::diagnostics::DiagnosticsColletion;
+
+    use super::TypeChecker;
+
+    fn check(input: &str) -> Vec<String> {
+        check_with_mode(input, true)
+    }
+
+    // Programs are checked for their entry point in addition
+    fn check_with_mode(input: &str, script_mode: bool) -> Vec<String> {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let mut parser = Parser::from_input(input.to_string(), Rc::clone(&diagnostics_colletion));
+        let mut ast = Ast::new();
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        ast.visit(&mut SymbolChecker::new(Rc::clone(&diagnostics_colletion)));
+        let mut type_checker = TypeChecker::new(Rc::clone(&diagnostics_colletion));
+        type_checker.set_script_mode(script_mode);
+        ast.visit(&mut type_checker);
+        if !script_mode {
+            type_checker.check_entry_point(&ast);
+        }
+
+        let diagnostics = diagnostics_colletion.borrow();
+        diagnostics
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn should_accept_matching_types() {
+        let input = "\
+        func f(a: u8, b: u16, c: u64) -> u64 { return a + b + c; }
+        let x: f64 = (7.2 - 10) / 2;
+        var y: i32 = -7;
+        y += 1;
+        for i in 0..10 { y = y + i; }
+        let z: u64 = f(1, 2, 3);
+        ";
+        assert_eq!(check(input), Vec::<String>::new());
+    }
+
+    #[test]
+    fn should_report_float_assigned_to_integer() {
+        let diagnostics = check("let x: i32 = 7.67;");
+        assert_eq!(
+            diagnostics,
+            vec!["Expected type <i32>, but found <{float}>".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_report_integer_literals_out_of_range() {
+        let input = "\
+        let a: i64 = 9223372036854775807;
+        let b: i64 = -9223372036854775808;
+        let c: u64 = 18446744073709551615;
+        let d: i64 = 9223372036854775808;
+        let e: i64 = -9223372036854775809;
+        let f: u64 = -1;
+        let g: u8? = 256;
+        func h(x: i8) -> u16 { return 65536; }
+        h(-129);
+        let i: u8 = 200 + 100;
+        let j: u8 = (1000 - 900) * 2;
+        let k: i8 = -(100 + 29);
+        let l: u8 = 1 / 0;
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Integer literal <9223372036854775808> does not fit into <i64>".to_string(),
+                "Integer literal <-9223372036854775809> does not fit into <i64>".to_string(),
+                "Integer literal <-1> does not fit into <u64>".to_string(),
+                "Integer literal <256> does not fit into <u8>".to_string(),
+                "Integer literal <65536> does not fit into <u16>".to_string(),
+                "Integer literal <-129> does not fit into <i8>".to_string(),
+                "Constant expression evaluates to <300>, which does not fit into <u8>".to_string(),
+                "Constant expression evaluates to <-129>, which does not fit into <i8>".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_return_type_mismatch() {
+        let diagnostics = check("func a(arg1: i32, arg2: u8) -> i32 { return arg2; }");
+        assert_eq!(
+            diagnostics,
+            vec!["Expected type <i32>, but found <u8>".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_report_argument_type_mismatch() {
+        let diagnostics = check(
+            "\
+        func a(arg1: i32, arg2: f32) -> f32 { return arg2; }
+        let b: i64 = 1;
+        a(b, 2.5);
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec!["Expected type <i32>, but found <i64>".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_report_incompatible_operands() {
+        let diagnostics = check(
+            "\
+        let a: i32 = 1;
+        let b: f32 = 2.5;
+        let c: u8 = 3;
+        a + b;
+        -c;
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Operator <+> cannot be applied to <i32> and <f32>".to_string(),
+                "Operator <-> cannot be applied to <u8>".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_promote_integers_only_without_loss() {
+        let diagnostics = check(
+            "\
+        func f(a: u8, b: i16, c: u32, d: i32, e: u64) -> i16 {
+            c + d;
+            d + c;
+            e + d;
+            return a + b;
+        }
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Operator <+> cannot be applied to <u32> and <i32>".to_string(),
+                "Operator <+> cannot be applied to <i32> and <u32>".to_string(),
+                "Operator <+> cannot be applied to <u64> and <i32>".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_functions_that_can_end_without_returning() {
+        let diagnostics = check(
+            "\
+        func f() -> i32 { let a: i32 = 1; }
+        func g(a: bool) -> i32 { if a { return 1; } }
+        func h(a: bool) -> i32 { if a { return 1; } else { return 2; } }
+        func i(a: bool) -> i32 { while a { return 1; } }
+        func j() { let a: i32 = 1; }
+        struct P { x: i32, func get(self) -> i32 { self.x; } }
+        let x: i32 = f();
+        return x + 1;
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Function <f> does not return <i32> on every path".to_string(),
+                "Function <g> does not return <i32> on every path".to_string(),
+                "Function <i> does not return <i32> on every path".to_string(),
+                "Function <get> does not return <i32> on every path".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_tell_optional_pointers_and_pointers_to_optionals_apart() {
+        let diagnostics = check(
+            "\
+        var a: i32? = null;
+        let p: *i32? = &a;
+        let q: *(i32?) = &a;
+        let r: (*i32)? = q;
+        let s: [(i32?)?; 1] = [null];
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Expected type <*i32?>, but found <*(i32?)>".to_string(),
+                "Expected type <*i32?>, but found <*(i32?)>".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_non_type_annotation() {
+        let diagnostics = check("let a: b = 1;");
+        assert_eq!(
+            diagnostics,
+            vec!["Expected type, but found <Identifier>".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_only_concatenate_strings() {
+        let diagnostics = check(
+            "\
+        let a: str = \"zeno\";
+        let b: str = a + \"!\";
+        a + 1;
+        let c: str = 2;
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Operator <+> cannot be applied to <str> and <{integer}>".to_string(),
+                "Expected type <str>, but found <{integer}>".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_require_boolean_conditions() {
+        let diagnostics = check(
+            "\
+        var a: i32 = 3;
+        while a {
+            a -= 1;
+        }
+        if a > 0 && true {
+            a = 0;
+        }
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec!["Expected type <bool>, but found <i32>".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_only_compare_characters() {
+        let diagnostics = check(
+            "\
+        let a: char = 'a';
+        let b: bool = a < 'b';
+        a + 'b';
+        ",
+        );
+        assert_eq!(
+            diagnostics,
+            vec!["Operator <+> cannot be applied to <char> and <char>".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_check_struct_field_types() {
+        let input = "\
+        struct Point { x: i32, y: f64 }
+        var p: Point = Point { x: 1.5, y: 2 };
+        p.y = p.x;
+        p.x = true;
+        p.z;
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Expected type <i32>, but found <{float}>",
+                "Expected type <f64>, but found <i32>",
+                "Expected type <i32>, but found <bool>",
+                "Type <Point> has no field <z>",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_check_method_calls() {
+        let input = "\
+        struct Counter {
+            count: u32,
+            func next(self, step: u32) -> u32 { return self.count + step; }
+        }
+        let c: Counter = Counter { count: 1 };
+        let a: u32 = c.next(true);
+        let b: i64 = c.next(1);
+        let n: i32 = 5;
+        n.next(1);
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Expected type <u32>, but found <bool>",
+                "Expected type <i64>, but found <u32>",
+                "Type <i32> has no method <next>",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_check_pointer_types() {
+        let input = "\
+        var a: i32 = 1;
+        let b: u8 = 2;
+        let p: *i32 = &a;
+        let pp: **i32 = &p;
+        let q: *i32 = &b;
+        let n: i32 = null;
+        *a;
+        **pp = 3;
+        p < p;
+        p == null;
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Expected type <*i32>, but found <*u8>",
+                "Expected type <i32>, but found <null>",
+                "Operator <*> cannot be applied to <i32>",
+                "Operator <<> cannot be applied to <*i32> and <*i32>",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_require_null_checks_for_optionals() {
+        let input = "\
+        func find(n: i32) -> i32? {
+            if n < 0 { return null; }
+            return n;
+        }
+        var a: i32? = find(1);
+        let e: u8? = 1;
+        let b: i32 = a;
+        a + 1;
+        if a != null { let c: i32 = a + 1; } else { let d: i32 = a; }
+        if a == null || a > 2 { a = null; }
+        while a != null && a < 10 { a = a + 1; }
+        if a != null { a = find(2); a + 1; }
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Value of optional type <i32?> is used without checking it for null",
+                "Value of optional type <i32?> is used without checking it for null",
+                "Value of optional type <i32?> is used without checking it for null",
+                "Value of optional type <i32?> is used without checking it for null",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_check_array_types_and_constant_indices() {
+        let input = "\
+        var a: [i32; 3] = [1, 2, 3];
+        let b: [i32; 2] = a;
+        let c: [u8; 2] = [1, true];
+        let d: bool = a[0];
+        a[3] = 1;
+        a[-1];
+        a[true];
+        let n: i32 = 5;
+        n[0];
+        for x in n {}
+        for x in a { let y: i32 = x; }
+        ";
+        assert_eq!(
+            check(input),
+            vec![
+                "Expected type <[i32; 2]>, but found <[i32; 3]>",
+                "Expected type <{integer}>, but found <bool>",
+                "Expected type <bool>, but found <i32>",
+                "Index 3 is out of bounds for an array of length 3",
+                "Index -1 is out of bounds for an array of length 3",
+                "Expected integer index, but found <bool>",
+                "Type <i32> cannot be indexed",
+                "Type <i32> cannot be iterated over",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_validate_the_entry_point_of_programs() {
+        let valid = "\
+        let limit: i32 = 3;
+        func main() -> i32 {
+            return limit;
+        }
+        ";
+        assert!(check_with_mode(valid, false).is_empty());
+
+        assert_eq!(
+            check_with_mode("let a: i32 = 1;\nreturn a;", false),
+            vec![
+                "<Return> outside of a function is only allowed in script mode",
+                "Missing entry point, expected <func main() -> i32>",
+            ]
+        );
+
+        let invalid = "\
+        func main(code: i32) -> bool { return true; }
+        func main() {}
+        ";
+        assert_eq!(
+            check_with_mode(invalid, false),
+            vec![
+                "Entry point <main> is already declared",
+                "Entry point <main> takes no arguments and returns <i32> or <void>",
+            ]
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use core::fmt;
+use std::cmp::Ordering;
+use std::ops::{BitAnd, BitOr, BitXor};
+
+use super::memory::{Address, PathSegment};
+use super::type_checker::Type;
+
+// Runtime value of the interpreter. Integers wrap around on overflow like
+// two's complement machine integers, floats follow IEEE 754.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Value {
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    // fields are kept in declaration order
+    Struct {
+        name: String,
+        fields: Vec<(String, Value)>,
+    },
+    Array(Vec<Value>),
+    // points into a variable or one of its nested fields and elements
+    Pointer {
+        address: Address,
+        path: Vec<PathSegment>,
+        pointee: Type,
+    },
+    // remembers the pointer or optional type it was stored as
+    Null(Type),
+    Void,
+    // Literals keep their full precision until they meet a declared type
+    IntegerLiteral(i128),
+    FloatLiteral(f64),
+}
+
+macro_rules! numeric_cast {
+    ($value:expr, $target:ty) => {
+        match $value {
+            Value::I8(v) => Some(*v as $target),
+            Value::I16(v) => Some(*v as $target),
+            Value::I32(v) => Some(*v as $target),
+            Value::I64(v) => Some(*v as $target),
+            Value::U8(v) => Some(*v as $target),
+            Value::U16(v) => Some(*v as $target),
+            Value::U32(v) => Some(*v as $target),
+            Value::U64(v) => Some(*v as $target),
+            Value::F32(v) => Some(*v as $target),
+            Value::F64(v) => Some(*v as $target),
+            Value::IntegerLiteral(v) => Some(*v as $target),
+            Value::FloatLiteral(v) => Some(*v as $target),
+            _ => None,
+        }
+    };
+}
+
+macro_rules! integer_operation {
+    ($left:expr, $right:expr, $operation:ident) => {
+        match ($left, $right) {
+            (Value::I8(l), Value::I8(r)) => Some(Value::I8(l.$operation(*r))),
+            (Value::I16(l), Value::I16(r)) => Some(Value::I16(l.$operation(*r))),
+            (Value::I32(l), Value::I32(r)) => Some(Value::I32(l.$operation(*r))),
+            (Value::I64(l), Value::I64(r)) => Some(Value::I64(l.$operation(*r))),
+            (Value::U8(l), Value::U8(r)) => Some(Value::U8(l.$operation(*r))),
+            (Value::U16(l), Value::U16(r)) => Some(Value::U16(l.$operation(*r))),
+            (Value::U32(l), Value::U32(r)) => Some(Value::U32(l.$operation(*r))),
+            (Value::U64(l), Value::U64(r)) => Some(Value::U64(l.$operation(*r))),
+            (Value::IntegerLiteral(l), Value::IntegerLiteral(r)) => {
+                Some(Value::IntegerLiteral(l.$operation(*r)))
+            }
+            _ => None,
+        }
+    };
+}
+
+macro_rules! float_operation {
+    ($left:expr, $right:expr, $operator:tt) => {
+        match ($left, $right) {
+            (Value::F32(l), Value::F32(r)) => Some(Value::F32(l $operator r)),
+            (Value::F64(l), Value::F64(r)) => Some(Value::F64(l $operator r)),
+            (Value::FloatLiteral(l), Value::FloatLiteral(r)) => {
+                Some(Value::FloatLiteral(l $operator r))
+            }
+            _ => None,
+        }
+    };
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::I8(v) => write!(f, "{}", v),
+            Value::I16(v) => write!(f, "{}", v),
+            Value::I32(v) => write!(f, "{}", v),
+            Value::I64(v) => write!(f, "{}", v),
+            Value::U8(v) => write!(f, "{}", v),
+            Value::U16(v) => write!(f, "{}", v),
+            Value::U32(v) => write!(f, "{}", v),
+            Value::U64(v) => write!(f, "{}", v),
+            Value::F32(v) => write!(f, "{}", v),
+            Value::F64(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Char(v) => write!(f, "{}", v),
+            Value::Str(v) => write!(f, "{}", v),
+            Value::Struct { name, fields } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect();
+                write!(f, "{} {{ {} }}", name, fields.join(", "))
+            }
+            Value::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(Value::to_string).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Value::Pointer { address, path, .. } => {
+                write!(f, "0x{:x}", address.index())?;
+                for segment in path.iter() {
+                    match segment {
+                        PathSegment::Field(field) => write!(f, ".{}", field)?,
+                        PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                    }
+                }
+                Ok(())
+            }
+            Value::Null(_) => write!(f, "null"),
+            Value::Void => write!(f, "void"),
+            Value::IntegerLiteral(v) => write!(f, "{}", v),
+            Value::FloatLiteral(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl Value {
+    pub fn get_type(&self) -> Type {
+        match self {
+            Value::I8(_) => Type::I8,
+            Value::I16(_) => Type::I16,
+            Value::I32(_) => Type::I32,
+            Value::I64(_) => Type::I64,
+            Value::U8(_) => Type::U8,
+            Value::U16(_) => Type::U16,
+            Value::U32(_) => Type::U32,
+            Value::U64(_) => Type::U64,
+            Value::F32(_) => Type::F32,
+            Value::F64(_) => Type::F64,
+            Value::Bool(_) => Type::Bool,
+            Value::Char(_) => Type::Char,
+            Value::Str(_) => Type::Str,
+            Value::Struct { name, .. } => Type::Struct(name.clone()),
+            Value::Array(elements) => Type::Array(
+                Box::new(elements.first().map_or(Type::Void, Value::get_type)),
+                elements.len(),
+            ),
+            Value::Pointer { pointee, .. } => Type::Pointer(Box::new(pointee.clone())),
+            Value::Null(data_type) => data_type.clone(),
+            Value::Void => Type::Void,
+            Value::IntegerLiteral(_) => Type::IntegerLiteral,
+            Value::FloatLiteral(_) => Type::FloatLiteral,
+        }
+    }
+
+    // Numeric conversions truncate or wrap like `as` casts, values of other
+    // types are returned unchanged.
+    pub fn cast(&self, target: &Type) -> Value {
+        match (self, target) {
+            (Value::Null(_), Type::Pointer(_) | Type::Optional(_)) => {
+                return Value::Null(target.clone())
+            }
+            // a variable of an optional type can hold a plain value and still be set to null
+            (Value::Null(_), _) => return Value::Null(Type::Optional(Box::new(target.clone()))),
+            (_, Type::Optional(data_type)) => return self.cast(data_type),
+            (Value::Array(elements), Type::Array(element_type, _)) => {
+                return Value::Array(
+                    elements
+                        .iter()
+                        .map(|element| element.cast(element_type))
+                        .collect(),
+                )
+            }
+            _ => {}
+        }
+        let converted = match target {
+            Type::I8 => numeric_cast!(self, i8).map(Value::I8),
+            Type::I16 => numeric_cast!(self, i16).map(Value::I16),
+            Type::I32 => numeric_cast!(self, i32).map(Value::I32),
+            Type::I64 => numeric_cast!(self, i64).map(Value::I64),
+            Type::U8 => numeric_cast!(self, u8).map(Value::U8),
+            Type::U16 => numeric_cast!(self, u16).map(Value::U16),
+            Type::U32 => numeric_cast!(self, u32).map(Value::U32),
+            Type::U64 => numeric_cast!(self, u64).map(Value::U64),
+            Type::F32 => numeric_cast!(self, f32).map(Value::F32),
+            Type::F64 => numeric_cast!(self, f64).map(Value::F64),
+            Type::IntegerLiteral => numeric_cast!(self, i128).map(Value::IntegerLiteral),
+            Type::FloatLiteral => numeric_cast!(self, f64).map(Value::FloatLiteral),
+            _ => None,
+        };
+        converted.unwrap_or_else(|| self.clone())
+    }
+
+    // Brings both operands to the type the type checker derived for the operation
+    fn promote(left: &Value, right: &Value) -> (Value, Value) {
+        match Type::arithmetic_result(&left.get_type(), &right.get_type()) {
+            Some(common_type) => (left.cast(&common_type), right.cast(&common_type)),
+            None => (left.clone(), right.clone()),
+        }
+    }
+
+    // Field or element the segment leads to
+    pub fn part(&self, segment: &PathSegment) -> Option<&Value> {
+        match (self, segment) {
+            (Value::Array(elements), PathSegment::Index(index)) => elements.get(*index),
+            (_, PathSegment::Field(field)) => self.field(field),
+            _ => None,
+        }
+    }
+
+    pub fn part_mut(&mut self, segment: &PathSegment) -> Option<&mut Value> {
+        match (self, segment) {
+            (Value::Array(elements), PathSegment::Index(index)) => elements.get_mut(*index),
+            (value, PathSegment::Field(field)) => value.field_mut(field),
+            _ => None,
+        }
+    }
+
+    pub fn field(&self, field: &str) -> Option<&Value> {
+        match self {
+            Value::Struct { fields, .. } => fields
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn field_mut(&mut self, field: &str) -> Option<&mut Value> {
+        match self {
+            Value::Struct { fields, .. } => fields
+                .iter_mut()
+                .find(|(name, _)| name == field)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        numeric_cast!(self, i128)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        numeric_cast!(self, f64).is_some_and(|value| value == 0.0)
+    }
+
+    pub fn is_true(&self) -> bool {
+        match self {
+            Value::Bool(value) => *value,
+            _ => !self.is_zero(),
+        }
+    }
+
+    pub fn add(&self, other: &Value) -> Option<Value> {
+        if let (Value::Str(left), Value::Str(right)) = (self, other) {
+            return Some(Value::Str(format!("{}{}", left, right)));
+        }
+        let (left, right) = Self::promote(self, other);
+        integer_operation!(&left, &right, wrapping_add).or(float_operation!(&left, &right, +))
+    }
+
+    pub fn sub(&self, other: &Value) -> Option<Value> {
+        let (left, right) = Self::promote(self, other);
+        integer_operation!(&left, &right, wrapping_sub).or(float_operation!(&left, &right, -))
+    }
+
+    pub fn mul(&self, other: &Value) -> Option<Value> {
+        let (left, right) = Self::promote(self, other);
+        integer_operation!(&left, &right, wrapping_mul).or(float_operation!(&left, &right, *))
+    }
+
+    // Integer division by zero has no result, the caller reports it
+    pub fn div(&self, other: &Value) -> Option<Value> {
+        let (left, right) = Self::promote(self, other);
+        if left.get_type().is_integer() && right.is_zero() {
+            return None;
+        }
+        integer_operation!(&left, &right, wrapping_div).or(float_operation!(&left, &right, /))
+    }
+
+    pub fn bitwise_or(&self, other: &Value) -> Option<Value> {
+        let (left, right) = Self::promote(self, other);
+        integer_operation!(&left, &right, bitor)
+    }
+
+    pub fn bitwise_and(&self, other: &Value) -> Option<Value> {
+        let (left, right) = Self::promote(self, other);
+        integer_operation!(&left, &right, bitand)
+    }
+
+    pub fn bitwise_xor(&self, other: &Value) -> Option<Value> {
+        let (left, right) = Self::promote(self, other);
+        integer_operation!(&left, &right, bitxor)
+    }
+
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        if let (Value::Null(_), Value::Null(_)) = (self, other) {
+            return Some(Ordering::Equal);
+        }
+        let (left, right) = Self::promote(self, other);
+        left.partial_cmp(&right)
+    }
+
+    pub fn negate(&self) -> Option<Value> {
+        match self {
+            Value::I8(v) => Some(Value::I8(v.wrapping_neg())),
+            Value::I16(v) => Some(Value::I16(v.wrapping_neg())),
+            Value::I32(v) => Some(Value::I32(v.wrapping_neg())),
+            Value::I64(v) => Some(Value::I64(v.wrapping_neg())),
+            Value::IntegerLiteral(v) => Some(Value::IntegerLiteral(v.wrapping_neg())),
+            Value::F32(v) => Some(Value::F32(-v)),
+            Value::F64(v) => Some(Value::F64(-v)),
+            Value::FloatLiteral(v) => Some(Value::FloatLiteral(-v)),
+            _ => None,
+        }
+    }
+
+    pub fn bitwise_not(&self) -> Option<Value> {
+        match self {
+            Value::I8(v) => Some(Value::I8(!v)),
+            Value::I16(v) => Some(Value::I16(!v)),
+            Value::I32(v) => Some(Value::I32(!v)),
+            Value::I64(v) => Some(Value::I64(!v)),
+            Value::U8(v) => Some(Value::U8(!v)),
+            Value::U16(v) => Some(Value::U16(!v)),
+            Value::U32(v) => Some(Value::U32(!v)),
+            Value::U64(v) => Some(Value::U64(!v)),
+            Value::IntegerLiteral(v) => Some(Value::IntegerLiteral(!v)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Value;
+    use crate::ast::type_checker::Type;
+
+    #[test]
+    fn integers_wrap_at_their_width() {
+        let a = Value::IntegerLiteral(127).cast(&Type::I8);
+        assert_eq!(a.add(&Value::IntegerLiteral(1)), Some(Value::I8(-128)));
+
+        let b = Value::IntegerLiteral(0).cast(&Type::U8);
+        assert_eq!(b.sub(&Value::IntegerLiteral(1)), Some(Value::U8(255)));
+    }
+
+    #[test]
+    fn u64_keeps_full_precision() {
+        let a = Value::U64((1 << 53) + 1);
+        assert_eq!(
+            a.bitwise_or(&Value::U64(1 << 63)),
+            Some(Value::U64((1 << 63) | (1 << 53) | 1))
+        );
+        assert_eq!(
+            a.add(&Value::IntegerLiteral(1)),
+            Some(Value::U64((1 << 53) + 2))
+        );
+    }
+
+    #[test]
+    fn operands_are_promoted_to_common_type() {
+        assert_eq!(Value::U8(200).add(&Value::I16(100)), Some(Value::I16(300)));
+        assert_eq!(
+            Value::IntegerLiteral(3).mul(&Value::FloatLiteral(0.5)),
+            Some(Value::FloatLiteral(1.5))
+        );
+    }
+
+    #[test]
+    fn integer_division_by_zero_has_no_result() {
+        assert_eq!(Value::I32(7).div(&Value::IntegerLiteral(0)), None);
+        assert_eq!(
+            Value::F64(1.0).div(&Value::IntegerLiteral(0)),
+            Some(Value::F64(f64::INFINITY))
+        );
+    }
+}
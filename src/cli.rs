@@ -0,0 +1,541 @@
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::ExitCode;
+use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+
+use crate::ast::formatter;
+use crate::ast::lexer::{Lexer, TokenKind};
+use crate::ast::parser::Parser;
+use crate::ast::value::Value;
+use crate::ast::Ast;
+use crate::compilation_unit::{CompilationUnit, CompileOptions};
+use crate::diagnostics::explanations::explanation;
+use crate::diagnostics::printer::{DiagnosticsFormat, DiagnosticsPrinter};
+use crate::diagnostics::{DiagnosticCode, DiagnosticsColletion, DiagnosticsColletionCell};
+use crate::lsp;
+use crate::repl::Repl;
+use crate::source_text::SourceText;
+use crate::terminal::{strip_colors, ColorChoice};
+
+const USAGE: &str = "\
+Usage: zeno-compiler <command> [options] <file>
+       zeno-compiler repl [options]
+       zeno-compiler lsp
+       zeno-compiler --explain <code>
+
+Commands:
+  lex      Print the tokens of the file
+  parse    Print the syntax tree of the file
+  check    Report the diagnostics of the program without running it
+  run      Run the program
+  fmt      Print the formatted source of the file, or rewrite it with `--write`
+  repl     Evaluate inputs interactively, `:help` lists its commands
+  lsp      Serve the language server protocol on stdin and stdout
+
+Options:
+  --quiet                       Only print diagnostics and the requested output
+  --color=auto|always|never     Color the output, `auto` colors it for terminals
+  --script                      Run the top-level statements instead of `main`
+  --max-loop-iterations=<n>     Abort loops after <n> iterations
+  --max-call-depth=<n>          Abort calls nested deeper than <n>
+  --diagnostics-format=human|json|sarif
+                                Write the diagnostics for people, as JSON lines or as a SARIF log
+  --check                       Let `fmt` fail if the file is not formatted
+  --write                       Let `fmt` rewrite the file in place
+  --explain <code>              Print the explanation of an error code like `Z0001`
+  -h, --help                    Print this help
+
+The file `-` reads the source from stdin.";
+
+// Exit code for usage errors, compile and runtime errors exit with 1
+const USAGE_ERROR: u8 = 2;
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Lex,
+    Parse,
+    Check,
+    Run,
+    Fmt,
+    Repl,
+    Lsp,
+    Explain(DiagnosticCode),
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    command: Command,
+    // `-` reads the source from stdin
+    path: String,
+    quiet: bool,
+    color: ColorChoice,
+    script_mode: bool,
+    max_loop_iterations: Option<usize>,
+    max_call_depth: Option<usize>,
+    diagnostics_format: DiagnosticsFormat,
+    // options of `fmt`
+    check: bool,
+    write: bool,
+}
+
+impl Options {
+    // `Ok(None)` asks for the help
+    fn parse(args: &[String]) -> Result<Option<Options>, String> {
+        let mut quiet = false;
+        let mut color = ColorChoice::Auto;
+        let mut script_mode = false;
+        let mut max_loop_iterations = None;
+        let mut max_call_depth = None;
+        let mut diagnostics_format = DiagnosticsFormat::Human;
+        let mut check = false;
+        let mut write = false;
+        let mut explain = None;
+        let mut positional: Vec<&str> = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--explain" => {
+                    let code = args
+                        .next()
+                        .ok_or("expected an error code after `--explain`")?;
+                    let code = code
+                        .parse()
+                        .map_err(|_| format!("unknown error code `{}`", code))?;
+                    explain = Some(code);
+                }
+                "--quiet" => quiet = true,
+                "--script" => script_mode = true,
+                "--check" => check = true,
+                "--write" => write = true,
+                _ => {
+                    if let Some(choice) = arg.strip_prefix("--color=") {
+                        color = choice
+                            .parse()
+                            .map_err(|_| format!("invalid color choice `{}`", choice))?;
+                    } else if let Some(limit) = arg.strip_prefix("--max-loop-iterations=") {
+                        let limit = limit
+                            .parse()
+                            .map_err(|_| format!("invalid loop limit `{}`", limit))?;
+                        max_loop_iterations = Some(limit);
+                    } else if let Some(limit) = arg.strip_prefix("--max-call-depth=") {
+                        let limit = limit
+                            .parse()
+                            .map_err(|_| format!("invalid call depth `{}`", limit))?;
+                        max_call_depth = Some(limit);
+                    } else if let Some(format) = arg.strip_prefix("--diagnostics-format=") {
+                        diagnostics_format = format
+                            .parse()
+                            .map_err(|_| format!("invalid diagnostics format `{}`", format))?;
+                    } else if arg.starts_with("--") {
+                        return Err(format!("unknown option `{}`", arg));
+                    } else {
+                        positional.push(arg);
+                    }
+                }
+            }
+        }
+
+        // `--explain`, the REPL and the language server read no file
+        let (command, path) = match (explain, &positional[..]) {
+            (Some(code), []) => (Command::Explain(code), ""),
+            (Some(_), _) => return Err("`--explain` takes no command or file".to_string()),
+            (None, [command @ ("repl" | "lsp")]) => (Self::command(command)?, ""),
+            (None, [command, path]) if !matches!(*command, "repl" | "lsp") => {
+                (Self::command(command)?, *path)
+            }
+            _ => return Err("expected a command and a file".to_string()),
+        };
+        if write && path == "-" {
+            return Err("cannot write the formatted source back to stdin".to_string());
+        }
+        Ok(Some(Options {
+            command,
+            path: path.to_string(),
+            quiet,
+            color,
+            script_mode,
+            max_loop_iterations,
+            max_call_depth,
+            diagnostics_format,
+            check,
+            write,
+        }))
+    }
+
+    fn command(name: &str) -> Result<Command, String> {
+        match name {
+            "lex" => Ok(Command::Lex),
+            "parse" => Ok(Command::Parse),
+            "check" => Ok(Command::Check),
+            "run" => Ok(Command::Run),
+            "fmt" => Ok(Command::Fmt),
+            "repl" => Ok(Command::Repl),
+            "lsp" => Ok(Command::Lsp),
+            _ => Err(format!("unknown command `{}`", name)),
+        }
+    }
+}
+
+// Runs the command line without the program name
+pub fn main(args: &[String]) -> ExitCode {
+    let options = match Options::parse(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(USAGE_ERROR);
+        }
+    };
+    if options.command == Command::Repl {
+        let mut repl = Repl::new(options.color.enabled());
+        if let Some(max_loop_iterations) = options.max_loop_iterations {
+            repl.set_max_loop_iterations(max_loop_iterations);
+        }
+        if let Some(max_call_depth) = options.max_call_depth {
+            repl.set_max_call_depth(max_call_depth);
+        }
+        return repl.run();
+    }
+    if options.command == Command::Lsp {
+        return lsp::main();
+    }
+    if let Command::Explain(code) = options.command {
+        println!("{}: {}", code, explanation(code));
+        return ExitCode::SUCCESS;
+    }
+    let source_text = match read_source(&options.path) {
+        Ok(source_text) => source_text,
+        Err(error) => {
+            eprintln!("error: could not read {}: {}", options.path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let driver = Driver {
+        module: module_name(&options.path),
+        colored: options.color.enabled(),
+        options,
+    };
+    match driver.options.command {
+        Command::Lex => driver.lex(source_text),
+        Command::Parse => driver.parse(source_text),
+        Command::Check => driver.check(source_text),
+        Command::Run => driver.run(source_text),
+        Command::Fmt => driver.fmt(source_text),
+        Command::Repl | Command::Lsp | Command::Explain(_) => {
+            unreachable!("{:?} reads no file", driver.options.command)
+        }
+    }
+}
+
+// Source of the entry module
+fn read_source(path: &str) -> io::Result<SourceText> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        return Ok(SourceText::new("<stdin>".to_string(), input));
+    }
+    fs::read_to_string(path).map(|input| SourceText::new(path.to_string(), input))
+}
+
+// Exit statuses are a single byte, codes outside of 0 to 255 exit with 1
+// instead of wrapping around to a status that may look like success
+fn exit_status(exit_code: i32) -> u8 {
+    u8::try_from(exit_code).unwrap_or(1)
+}
+
+// The entry module is named after its file, source from stdin is `main`
+pub(crate) fn module_name(path: &str) -> String {
+    match Path::new(path).file_stem() {
+        Some(stem) if path != "-" => stem.to_string_lossy().to_string(),
+        _ => "main".to_string(),
+    }
+}
+
+// One token per line as `line:column kind literal`, whitespace is left out
+pub(crate) fn stringify_tokens(
+    source_text: &SourceText,
+    diagnostics_colletion: &DiagnosticsColletionCell,
+) -> String {
+    let mut lexer = Lexer::new(
+        source_text.input().to_string(),
+        Rc::clone(diagnostics_colletion),
+    );
+    let mut output = String::new();
+    while let Some(token) = lexer.next_token() {
+        if token.kind == TokenKind::Whitespace {
+            continue;
+        }
+        output.push_str(&format!(
+            "{}:{} {:?} {:?}\n",
+            source_text.get_linenumber(token.span.start),
+            source_text.get_column(token.span.start) + 1,
+            token.kind,
+            token.span.literal
+        ));
+    }
+    output
+}
+
+struct Driver {
+    options: Options,
+    module: String,
+    colored: bool,
+}
+
+impl Driver {
+    fn lex(&self, source_text: SourceText) -> ExitCode {
+        let diagnostics_colletion = self.diagnostics_colletion();
+        let output = stringify_tokens(&source_text, &diagnostics_colletion);
+        if self.report(source_text, &diagnostics_colletion) {
+            return ExitCode::FAILURE;
+        }
+        print!("{}", output);
+        ExitCode::SUCCESS
+    }
+
+    fn parse(&self, source_text: SourceText) -> ExitCode {
+        let Some(ast) = self.parse_ast(source_text) else {
+            return ExitCode::FAILURE;
+        };
+        self.print(&ast.visualize());
+        ExitCode::SUCCESS
+    }
+
+    fn check(&self, source_text: SourceText) -> ExitCode {
+        if CompilationUnit::compile_source(&self.module, source_text, self.compile_options())
+            .is_err()
+        {
+            return ExitCode::FAILURE;
+        }
+        if self.options.diagnostics_format == DiagnosticsFormat::Sarif {
+            // tools reading the log expect one for a clean program as well
+            let sources = HashMap::new();
+            let mut printer = DiagnosticsPrinter::new(&sources, &[]);
+            printer.set_format(DiagnosticsFormat::Sarif);
+            printer.print();
+        }
+        // tools asking for JSON or SARIF only get the requested format
+        if !self.options.quiet && self.options.diagnostics_format == DiagnosticsFormat::Human {
+            println!("No errors found in {}", self.options.path);
+        }
+        ExitCode::SUCCESS
+    }
+
+    fn run(&self, source_text: SourceText) -> ExitCode {
+        let Ok(mut compilation_unit) =
+            CompilationUnit::compile_source(&self.module, source_text, self.compile_options())
+        else {
+            return ExitCode::FAILURE;
+        };
+        if let Some(max_loop_iterations) = self.options.max_loop_iterations {
+            compilation_unit.set_max_loop_iterations(max_loop_iterations);
+        }
+        if let Some(max_call_depth) = self.options.max_call_depth {
+            compilation_unit.set_max_call_depth(max_call_depth);
+        }
+        let Ok(value) = compilation_unit.run() else {
+            return ExitCode::FAILURE;
+        };
+        if !self.options.quiet {
+            let value = value.cast(&value.get_type().concrete());
+            println!("Result: {} ({})", value, value.get_type());
+        }
+        match value {
+            // the value returned by `main` is the exit code of the program
+            Value::I32(exit_code) if !self.options.script_mode => {
+                ExitCode::from(exit_status(exit_code))
+            }
+            _ => ExitCode::SUCCESS,
+        }
+    }
+
+    fn fmt(&self, source_text: SourceText) -> ExitCode {
+        let diagnostics_colletion = self.diagnostics_colletion();
+        let Ok(formatted) = formatter::format(source_text.input(), &diagnostics_colletion) else {
+            self.report(source_text, &diagnostics_colletion);
+            return ExitCode::FAILURE;
+        };
+        if self.options.check {
+            if formatted == source_text.input() {
+                return ExitCode::SUCCESS;
+            }
+            eprintln!("{} is not formatted", self.options.path);
+            return ExitCode::FAILURE;
+        }
+        if !self.options.write {
+            print!("{}", formatted);
+            return ExitCode::SUCCESS;
+        }
+        if formatted != source_text.input() {
+            if let Err(error) = fs::write(&self.options.path, formatted) {
+                eprintln!("error: could not write {}: {}", self.options.path, error);
+                return ExitCode::FAILURE;
+            }
+        }
+        ExitCode::SUCCESS
+    }
+
+    fn compile_options(&self) -> CompileOptions {
+        CompileOptions {
+            script_mode: self.options.script_mode,
+            colored: self.colored,
+            diagnostics_format: self.options.diagnostics_format,
+        }
+    }
+
+    fn diagnostics_colletion(&self) -> DiagnosticsColletionCell {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        diagnostics_colletion.borrow_mut().set_module(&self.module);
+        diagnostics_colletion
+    }
+
+    // Syntax tree of the entry module alone, its imports are not resolved
+    fn parse_ast(&self, source_text: SourceText) -> Option<Ast> {
+        let diagnostics_colletion = self.diagnostics_colletion();
+        let mut parser = Parser::from_input(
+            source_text.input().to_string(),
+            Rc::clone(&diagnostics_colletion),
+        );
+        let mut ast = Ast::new();
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        (!self.report(source_text, &diagnostics_colletion)).then_some(ast)
+    }
+
+    // Prints the diagnostics and tells whether there were any
+    fn report(
+        &self,
+        source_text: SourceText,
+        diagnostics_colletion: &DiagnosticsColletionCell,
+    ) -> bool {
+        let diagnostics = &diagnostics_colletion.borrow().diagnostics;
+        if diagnostics.is_empty() {
+            return false;
+        }
+        let sources = HashMap::from([(self.module.clone(), source_text)]);
+        let mut printer = DiagnosticsPrinter::new(&sources, diagnostics);
+        printer.set_colored(self.colored);
+        printer.set_format(self.options.diagnostics_format);
+        printer.print();
+        true
+    }
+
+    fn print(&self, text: &str) {
+        if self.colored {
+            print!("{}", text);
+        } else {
+            print!("{}", strip_colors(text));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{exit_status, module_name, Command, Options};
+    use crate::diagnostics::printer::DiagnosticsFormat;
+    use crate::diagnostics::DiagnosticCode;
+    use crate::terminal::ColorChoice;
+
+    fn parse(args: &[&str]) -> Result<Option<Options>, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Options::parse(&args)
+    }
+
+    #[test]
+    fn should_parse_commands_and_options() {
+        assert_eq!(
+            parse(&[
+                "run",
+                "--quiet",
+                "main.zn",
+                "--color=never",
+                "--max-loop-iterations=10",
+                "--max-call-depth=50",
+                "--diagnostics-format=json"
+            ]),
+            Ok(Some(Options {
+                command: Command::Run,
+                path: "main.zn".to_string(),
+                quiet: true,
+                color: ColorChoice::Never,
+                script_mode: false,
+                max_loop_iterations: Some(10),
+                max_call_depth: Some(50),
+                diagnostics_format: DiagnosticsFormat::Json,
+                check: false,
+                write: false,
+            }))
+        );
+        assert_eq!(parse(&["check", "-", "--help"]), Ok(None));
+        assert_eq!(
+            parse(&["repl", "--color=always"]).map(|options| options.map(|o| o.command)),
+            Ok(Some(Command::Repl))
+        );
+        assert_eq!(
+            parse(&["lsp"]).map(|options| options.map(|o| o.command)),
+            Ok(Some(Command::Lsp))
+        );
+        assert_eq!(
+            parse(&["--explain", "z0003"]).map(|options| options.map(|o| o.command)),
+            Ok(Some(Command::Explain(DiagnosticCode::UndefinedVariable)))
+        );
+        assert_eq!(module_name("examples/demo.zn"), "demo");
+        assert_eq!(module_name("-"), "main");
+    }
+
+    #[test]
+    fn should_reject_invalid_arguments() {
+        assert_eq!(
+            parse(&["build", "main.zn"]),
+            Err("unknown command `build`".to_string())
+        );
+        assert_eq!(
+            parse(&["run"]),
+            Err("expected a command and a file".to_string())
+        );
+        assert_eq!(
+            parse(&["repl", "main.zn"]),
+            Err("expected a command and a file".to_string())
+        );
+        assert_eq!(
+            parse(&["run", "main.zn", "--color=blue"]),
+            Err("invalid color choice `blue`".to_string())
+        );
+        assert_eq!(
+            parse(&["check", "main.zn", "--diagnostics-format=xml"]),
+            Err("invalid diagnostics format `xml`".to_string())
+        );
+        assert_eq!(
+            parse(&["--explain", "Z9999"]),
+            Err("unknown error code `Z9999`".to_string())
+        );
+        assert_eq!(
+            parse(&["check", "main.zn", "--explain", "Z0001"]),
+            Err("`--explain` takes no command or file".to_string())
+        );
+        assert_eq!(
+            parse(&["fmt", "--write", "-"]),
+            Err("cannot write the formatted source back to stdin".to_string())
+        );
+        assert_eq!(
+            parse(&["run", "main.zn", "--verbose"]),
+            Err("unknown option `--verbose`".to_string())
+        );
+    }
+
+    #[test]
+    fn should_map_exit_codes_out_of_range_to_failure() {
+        assert_eq!(exit_status(0), 0);
+        assert_eq!(exit_status(42), 42);
+        assert_eq!(exit_status(255), 255);
+        assert_eq!(exit_status(256), 1);
+        assert_eq!(exit_status(-1), 1);
+        assert_eq!(exit_status(i32::MIN), 1);
+    }
+}
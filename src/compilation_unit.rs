@@ -1,77 +1,464 @@
 use crate::{ast, diagnostics};
-use ast::lexer::Token;
-use ast::printer::ASTHiglightPrinter;
 use ast::solver::ASTSolver;
-use ast::symbol_checker;
-use diagnostics::printer::DiagnosticsPrinter;
-use diagnostics::{DiagnosticsColletion, DiagnosticsColletionCell};
-use std::{cell::RefCell, rc::Rc};
+use ast::value::Value;
+use ast::{symbol_checker, type_checker};
+use diagnostics::printer::{DiagnosticsFormat, DiagnosticsPrinter};
+use diagnostics::{Diagnostic, DiagnosticsColletion, DiagnosticsColletionCell};
+use std::collections::HashMap;
+use std::path::Path;
+use std::{cell::RefCell, fs, rc::Rc};
 
 use crate::source_text::SourceText;
 
-pub struct CompilationUnit {
+// Source of an imported module by its name, or the path where it was expected
+type ModuleLoader<'a> = dyn Fn(&str) -> Result<SourceText, String> + 'a;
+
+pub struct Module {
+    pub(crate) name: String,
     pub(crate) ast: ast::Ast,
+}
+
+pub struct CompilationUnit {
+    // imported modules come before the modules importing them, the entry module is last
+    modules: Vec<Module>,
+    sources: HashMap<String, SourceText>,
     diagnostics_colletion: DiagnosticsColletionCell,
+    max_loop_iterations: usize,
+    max_call_depth: usize,
+    options: CompileOptions,
 }
 
-impl CompilationUnit {
-    pub fn compile(input: &str) -> Result<CompilationUnit, ()> {
-        let source_text = SourceText::new(input.to_string());
-        let mut lexer = ast::lexer::Lexer::new(input.to_string());
-        let mut tokens: Vec<Token> = Vec::new();
-        while let Some(token) = lexer.next_token() {
-            tokens.push(token);
-        }
+#[derive(Clone, Copy)]
+pub struct CompileOptions {
+    // runs the top-level statements only instead of calling `main` afterwards
+    pub script_mode: bool,
+    // diagnostics are printed with ANSI colors
+    pub colored: bool,
+    pub diagnostics_format: DiagnosticsFormat,
+}
 
-        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+// Loads the modules reachable from the entry module
+struct ModuleGraph<'a> {
+    load: &'a ModuleLoader<'a>,
+    modules: Vec<Module>,
+    sources: HashMap<String, SourceText>,
+    // modules whose imports are loaded at the moment, an import of one of them is a cycle
+    loading: Vec<String>,
+    diagnostics_colletion: DiagnosticsColletionCell,
+}
+
+impl ModuleGraph<'_> {
+    fn add_module(&mut self, name: &str, source_text: SourceText) {
+        self.diagnostics_colletion.borrow_mut().set_module(name);
         let mut ast = ast::Ast::new();
-        let mut parser = ast::parser::Parser::new(tokens, Rc::clone(&diagnostics_colletion));
+        let mut parser = ast::parser::Parser::from_input(
+            source_text.input().to_string(),
+            Rc::clone(&self.diagnostics_colletion),
+        );
         while let Some(statement) = parser.next_statement() {
             ast.add_statement(statement);
         }
-        ast.visualize();
+        if let Some(declared) = ast.module_declaration() {
+            if declared.span.literal != name {
+                self.diagnostics_colletion
+                    .borrow_mut()
+                    .report_module_name_mismatch(declared, name);
+            }
+        }
+        self.sources.insert(name.to_string(), source_text);
 
-        let mut highlight_printer = ASTHiglightPrinter::new();
-        ast.visit(&mut highlight_printer);
-        highlight_printer.print_result();
+        self.loading.push(name.to_string());
+        for import in ast.imports() {
+            let imported = &import.span.literal;
+            if let Some(position) = self.loading.iter().position(|module| module == imported) {
+                let mut cycle = self.loading[position..].to_vec();
+                cycle.push(imported.clone());
+                let mut diagnostics = self.diagnostics_colletion.borrow_mut();
+                diagnostics.set_module(name);
+                diagnostics.report_import_cycle(import, &cycle);
+                continue;
+            }
+            if self.sources.contains_key(imported) {
+                continue;
+            }
+            match (self.load)(imported) {
+                Ok(source_text) => self.add_module(imported, source_text),
+                Err(path) => {
+                    let mut diagnostics = self.diagnostics_colletion.borrow_mut();
+                    diagnostics.set_module(name);
+                    diagnostics.report_module_not_found(import, &path);
+                }
+            }
+        }
+        self.loading.pop();
 
-        println!(
-            "Synatx Errors: {}",
-            diagnostics_colletion.borrow_mut().diagnostics.len()
-        );
-        Self::check_diagstics(&source_text, &diagnostics_colletion)?;
+        self.modules.push(Module {
+            name: name.to_string(),
+            ast,
+        });
+    }
+}
+
+impl CompilationUnit {
+    // Compiles a script of a single module, imports cannot be resolved
+    #[cfg(test)]
+    pub fn compile(input: &str) -> Result<CompilationUnit, ()> {
+        let entry = SourceText::new("main.zn".to_string(), input.to_string());
+        let options = CompileOptions {
+            script_mode: true,
+            colored: true,
+            diagnostics_format: DiagnosticsFormat::Human,
+        };
+        Self::compile_modules("main", entry, &|name| Err(format!("{}.zn", name)), options)
+    }
+
+    // Compiles the entry module and the modules it imports, which are looked up
+    // in the directory of the entry module
+    pub fn compile_source(
+        name: &str,
+        entry: SourceText,
+        options: CompileOptions,
+    ) -> Result<CompilationUnit, ()> {
+        let directory = Path::new(entry.path())
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let load = |name: &str| {
+            let module_path = directory.join(format!("{}.zn", name));
+            let module_path_str = module_path.display().to_string();
+            fs::read_to_string(&module_path)
+                .map(|input| SourceText::new(module_path_str.clone(), input))
+                .map_err(|_| module_path_str)
+        };
+        Self::compile_modules(name, entry, &load, options)
+    }
+
+    pub fn compile_modules(
+        name: &str,
+        entry: SourceText,
+        load: &ModuleLoader,
+        options: CompileOptions,
+    ) -> Result<CompilationUnit, ()> {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        Self::compile_into(name, entry, load, options, diagnostics_colletion)
+    }
+
+    // Compiles with the given collection, which keeps the diagnostics of a
+    // failed compilation and receives the ones of the run
+    pub(crate) fn compile_into(
+        name: &str,
+        entry: SourceText,
+        load: &ModuleLoader,
+        options: CompileOptions,
+        diagnostics_colletion: DiagnosticsColletionCell,
+    ) -> Result<CompilationUnit, ()> {
+        let (modules, sources) = Self::load_modules(name, entry, load, &diagnostics_colletion);
+        Self::check_diagstics(&sources, &diagnostics_colletion, options)?;
 
         let mut symbol_checker =
             symbol_checker::SymbolChecker::new(Rc::clone(&diagnostics_colletion));
-        ast.visit(&mut symbol_checker);
-        println!(
-            "Indentifier Errors: {}",
-            diagnostics_colletion.borrow_mut().diagnostics.len()
-        );
-        Self::check_diagstics(&source_text, &diagnostics_colletion)?;
+        for module in modules.iter() {
+            diagnostics_colletion.borrow_mut().set_module(&module.name);
+            symbol_checker.check_module(&module.name, &module.ast);
+        }
+        Self::check_diagstics(&sources, &diagnostics_colletion, options)?;
+
+        let mut type_checker = type_checker::TypeChecker::new(Rc::clone(&diagnostics_colletion));
+        type_checker.set_script_mode(options.script_mode);
+        for module in modules.iter() {
+            diagnostics_colletion.borrow_mut().set_module(&module.name);
+            type_checker.check_module(&module.name, &module.ast);
+        }
+        if !options.script_mode {
+            type_checker.check_entry_point(&modules.last().unwrap().ast);
+        }
+        Self::check_diagstics(&sources, &diagnostics_colletion, options)?;
 
         Ok(Self {
-            ast,
+            modules,
+            sources,
             diagnostics_colletion,
+            max_loop_iterations: ASTSolver::DEFAULT_MAX_LOOP_ITERATIONS,
+            max_call_depth: ASTSolver::DEFAULT_MAX_CALL_DEPTH,
+            options,
         })
     }
 
-    pub fn run(&self) {
-        let mut solver = ASTSolver::new();
-        self.ast.visit(&mut solver);
-        solver.print_result();
+    // Diagnostics of the parser and the symbol checker for the entry module and
+    // its imports, for tools that show them instead of printing them
+    pub fn check_symbols(name: &str, entry: SourceText, load: &ModuleLoader) -> Vec<Diagnostic> {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let (modules, _) = Self::load_modules(name, entry, load, &diagnostics_colletion);
+        if diagnostics_colletion.borrow().diagnostics.is_empty() {
+            let mut symbol_checker =
+                symbol_checker::SymbolChecker::new(Rc::clone(&diagnostics_colletion));
+            for module in modules.iter() {
+                diagnostics_colletion.borrow_mut().set_module(&module.name);
+                symbol_checker.check_module(&module.name, &module.ast);
+            }
+        }
+        let diagnostics = &mut diagnostics_colletion.borrow_mut().diagnostics;
+        std::mem::take(diagnostics)
+    }
+
+    fn load_modules(
+        name: &str,
+        entry: SourceText,
+        load: &ModuleLoader,
+        diagnostics_colletion: &DiagnosticsColletionCell,
+    ) -> (Vec<Module>, HashMap<String, SourceText>) {
+        let mut graph = ModuleGraph {
+            load,
+            modules: Vec::new(),
+            sources: HashMap::new(),
+            loading: Vec::new(),
+            diagnostics_colletion: Rc::clone(diagnostics_colletion),
+        };
+        graph.add_module(name, entry);
+        (graph.modules, graph.sources)
+    }
+
+    pub fn entry_module(&self) -> &Module {
+        self.modules.last().unwrap()
+    }
+
+    pub fn set_max_loop_iterations(&mut self, max_loop_iterations: usize) {
+        self.max_loop_iterations = max_loop_iterations;
+    }
+
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    // Runs the imported modules first. The result is the one of `main`, or of
+    // the entry module in script mode.
+    pub fn run(&self) -> Result<Value, ()> {
+        let mut solver = ASTSolver::new(Rc::clone(&self.diagnostics_colletion));
+        solver.set_max_loop_iterations(self.max_loop_iterations);
+        solver.set_max_call_depth(self.max_call_depth);
+        for module in self.modules.iter() {
+            solver.run_module(&module.name, &module.ast);
+            if !self.diagnostics_colletion.borrow().diagnostics.is_empty() {
+                break;
+            }
+        }
+        if !self.options.script_mode && self.diagnostics_colletion.borrow().diagnostics.is_empty() {
+            solver.run_entry_point(&self.entry_module().name);
+        }
+        Self::check_diagstics(&self.sources, &self.diagnostics_colletion, self.options)?;
+        Ok(solver.result().clone())
     }
 
     fn check_diagstics(
-        source_text: &SourceText,
+        sources: &HashMap<String, SourceText>,
         diagnostics_colletion: &DiagnosticsColletionCell,
+        options: CompileOptions,
     ) -> Result<(), ()> {
         let diagnostics_messages = &diagnostics_colletion.borrow().diagnostics;
-        if diagnostics_messages.len() > 0 {
-            let diagnostics_printer = DiagnosticsPrinter::new(&source_text, &diagnostics_messages);
+        if !diagnostics_messages.is_empty() {
+            let mut diagnostics_printer = DiagnosticsPrinter::new(sources, diagnostics_messages);
+            diagnostics_printer.set_colored(options.colored);
+            diagnostics_printer.set_format(options.diagnostics_format);
             diagnostics_printer.print();
             return Err(());
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::ast::lexer::TextSpan;
+    use crate::ast::value::Value;
+    use crate::diagnostics::printer::DiagnosticsPrinter;
+    use crate::diagnostics::DiagnosticsColletion;
+    use crate::source_text::SourceText;
+    use crate::terminal::strip_colors;
+
+    use super::{CompilationUnit, CompileOptions, DiagnosticsFormat, ModuleGraph};
+
+    fn source(files: &[(&str, &str)], name: &str) -> Result<SourceText, String> {
+        let path = format!("{}.zn", name);
+        files
+            .iter()
+            .find(|(file, _)| *file == path)
+            .map(|(_, input)| SourceText::new(path.clone(), input.to_string()))
+            .ok_or(path)
+    }
+
+    // Loads the modules reachable from the first file and returns their names
+    // in dependency order together with the diagnostics as `path: message`
+    fn load(files: &[(&str, &str)]) -> (Vec<String>, Vec<String>) {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let load = |name: &str| source(files, name);
+        let mut graph = ModuleGraph {
+            load: &load,
+            modules: Vec::new(),
+            sources: HashMap::new(),
+            loading: Vec::new(),
+            diagnostics_colletion: Rc::clone(&diagnostics_colletion),
+        };
+        graph.add_module("main", source(files, "main").unwrap());
+
+        let diagnostics = diagnostics_colletion.borrow();
+        let messages = diagnostics
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let path = graph.sources[&diagnostic.module].path();
+                format!("{}: {}", path, diagnostic.message)
+            })
+            .collect();
+        let modules = graph.modules.iter().map(|module| module.name.clone());
+        (modules.collect(), messages)
+    }
+
+    fn compile(files: &[(&str, &str)], script_mode: bool) -> Result<CompilationUnit, ()> {
+        let options = CompileOptions {
+            script_mode,
+            colored: false,
+            diagnostics_format: DiagnosticsFormat::Human,
+        };
+        let entry = source(files, "main").unwrap();
+        CompilationUnit::compile_modules("main", entry, &|name| source(files, name), options)
+    }
+
+    #[test]
+    fn should_load_imported_modules_before_the_importing_ones() {
+        let files = [
+            ("main.zn", "module main; import geometry; import math;"),
+            ("geometry.zn", "module geometry; import math;"),
+            ("math.zn", "module math;"),
+        ];
+        let (modules, messages) = load(&files);
+        assert!(messages.is_empty());
+        assert_eq!(modules, vec!["math", "geometry", "main"]);
+    }
+
+    #[test]
+    fn should_report_missing_modules_and_import_cycles() {
+        let files = [
+            ("main.zn", "import a;\nimport missing;"),
+            ("a.zn", "module a; import b;"),
+            ("b.zn", "module c;\nimport main;"),
+        ];
+        let (_, messages) = load(&files);
+        assert_eq!(
+            messages,
+            vec![
+                "b.zn: Expected module <b>, but the file declares <c>",
+                "b.zn: Import cycle: main -> a -> b -> main",
+                "main.zn: Module <missing> not found, expected it in missing.zn",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_name_the_file_of_a_diagnostic() {
+        let sources = HashMap::from([
+            (
+                "main".to_string(),
+                SourceText::new("main.zn".to_string(), "import math;".to_string()),
+            ),
+            (
+                "math".to_string(),
+                SourceText::new("lib/math.zn".to_string(), "\nlet a: i32 = b;".to_string()),
+            ),
+        ]);
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        diagnostics_colletion.borrow_mut().set_module("math");
+        diagnostics_colletion
+            .borrow_mut()
+            .report_undefined_variable(TextSpan::new(14, 15, "b".to_string()), None);
+        diagnostics_colletion.borrow_mut().diagnostics[0].with_label_in(
+            Some("main"),
+            TextSpan::new(7, 11, "math".to_string()),
+            "imported here".to_string(),
+        );
+
+        let diagnostics = &diagnostics_colletion.borrow().diagnostics;
+        let printer = DiagnosticsPrinter::new(&sources, diagnostics);
+        let text = strip_colors(&printer.stringify_diagnostic(&diagnostics[0]));
+        assert!(text.starts_with("error[Z0003]: Not found in this scope\n --> lib/math.zn:2:14\n"));
+        assert!(text.contains(" ::: main.zn:1:8\n"));
+    }
+
+    #[test]
+    fn should_call_functions_of_imported_modules() {
+        let files = [
+            (
+                "main.zn",
+                "\
+                import math;
+                func add(a: i32) -> i32 { return a; }
+                let offset: i32 = 1;
+                let c: Counter = math.counter(5);
+                return math.max(add(offset), math.add(2, 3)) * 100 + c.next();
+                ",
+            ),
+            (
+                "math.zn",
+                "\
+                module math;
+                let offset: i32 = 10;
+                func add(a: i32, b: i32) -> i32 { return a + b + offset; }
+                struct Counter {
+                    count: i32,
+                    func next(self) -> i32 { return add(self.count, 1); }
+                }
+                func counter(start: i32) -> Counter { return Counter { count: start }; }
+                func max(a: i32, b: i32) -> i32 {
+                    if a > b {
+                        return a;
+                    }
+                    return b;
+                }
+                ",
+            ),
+        ];
+        let compilation_unit = compile(&files, true).unwrap();
+        assert_eq!(compilation_unit.run(), Ok(Value::I32(1516)));
+    }
+
+    #[test]
+    fn should_report_structs_declared_in_two_modules() {
+        let files = [
+            ("main.zn", "import shapes;\nstruct Point { x: i32 }\n"),
+            (
+                "shapes.zn",
+                "module shapes;\nstruct Point { x: f64, y: f64 }\n",
+            ),
+        ];
+        let entry = source(&files, "main").unwrap();
+        let diagnostics =
+            CompilationUnit::check_symbols("main", entry, &|name| source(&files, name));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Struct <Point> is already declared");
+        assert_eq!(diagnostics[0].module, "main");
+        assert_eq!(diagnostics[0].labels[0].module.as_deref(), Some("shapes"));
+        assert_eq!(diagnostics[0].labels[0].span.start, 22);
+    }
+
+    #[test]
+    fn should_run_main_after_the_top_level_statements() {
+        let files = [
+            (
+                "main.zn",
+                "\
+                import math;
+                var calls: i32 = math.square(2);
+                func main() -> i32 {
+                    calls += 1;
+                    return math.square(calls);
+                }
+                ",
+            ),
+            ("math.zn", "func square(a: i32) -> i32 { return a * a; }"),
+        ];
+        let compilation_unit = compile(&files, false).unwrap();
+        assert_eq!(compilation_unit.run(), Ok(Value::I32(25)));
+    }
+}
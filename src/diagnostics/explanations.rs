@@ -0,0 +1,1005 @@
+use super::DiagnosticCode;
+
+// Long-form explanation of a code as `--explain` prints it: a summary line,
+// the cause, and an erroneous example followed by its corrected version
+pub fn explanation(code: DiagnosticCode) -> &'static str {
+    match code {
+        DiagnosticCode::UnexpectedToken => {
+            r#"The parser found a token where the grammar expects another one.
+
+Statements follow a fixed shape, like `let <name>: <type> = <value>;`. The
+message names the expected token and the one that was found instead.
+
+Erroneous example:
+
+    let a: i32 = 1
+    let b: i32 = 2;
+
+Corrected example:
+
+    let a: i32 = 1;
+    let b: i32 = 2;"#
+        }
+        DiagnosticCode::ExpectedExpression => {
+            r#"The parser expected an expression, like a value or a variable.
+
+Operators, initializers and arguments need an expression. This usually means
+an operand is missing.
+
+Erroneous example:
+
+    let a: i32 = 1 + ;
+
+Corrected example:
+
+    let a: i32 = 1 + 2;"#
+        }
+        DiagnosticCode::UndefinedVariable => {
+            r#"A name is used that is not declared in any enclosing scope.
+
+Variables and functions have to be declared before they are used. Variables
+are only visible in the block that declares them and the blocks inside of it.
+If a declared name is spelled similarly, the diagnostic points at it.
+
+Erroneous example:
+
+    if true {
+      let a: i32 = 1;
+    }
+    let b: i32 = a;
+
+Corrected example:
+
+    let a: i32 = 1;
+    let b: i32 = a;"#
+        }
+        DiagnosticCode::NumberOfFunctionArgumentsMismatch => {
+            r#"A function or method is called with the wrong number of arguments.
+
+A call passes one argument for every parameter of the function, there are no
+default values.
+
+Erroneous example:
+
+    func add(a: i32, b: i32) -> i32 {
+      return a + b;
+    }
+    let c: i32 = add(1);
+
+Corrected example:
+
+    func add(a: i32, b: i32) -> i32 {
+      return a + b;
+    }
+    let c: i32 = add(1, 2);"#
+        }
+        DiagnosticCode::UnknownType => {
+            r#"A type annotation names something that is not a type.
+
+Types are the primitive types like `i32` and `str`, declared structs, and
+pointers, optionals and arrays of them.
+
+Erroneous example:
+
+    let a: integer = 1;
+
+Corrected example:
+
+    let a: i32 = 1;"#
+        }
+        DiagnosticCode::InvalidArrayLength => {
+            r#"The length of an array type is not an integer literal.
+
+Array types are written `[T; n]` and their length is known when the program is
+compiled.
+
+Erroneous example:
+
+    let a: [i32; n] = [1, 2];
+
+Corrected example:
+
+    let a: [i32; 2] = [1, 2];"#
+        }
+        DiagnosticCode::TypeMismatch => {
+            r#"A value has a different type than its context expects.
+
+Initializers, assignments, arguments, return values and conditions have to
+match the declared type. Numbers are not converted implicitly.
+
+Erroneous example:
+
+    let a: i32 = 1.5;
+
+Corrected example:
+
+    let a: f64 = 1.5;"#
+        }
+        DiagnosticCode::IncompatibleOperands => {
+            r#"A binary operator is applied to operands it does not support.
+
+Arithmetic needs two numbers of the same type, `+` also concatenates two
+strings, and `&&` and `||` need booleans.
+
+Erroneous example:
+
+    let a: i32 = 1;
+    let b: f32 = 2.5;
+    let c: f32 = a + b;
+
+Corrected example:
+
+    let a: f32 = 1.0;
+    let b: f32 = 2.5;
+    let c: f32 = a + b;"#
+        }
+        DiagnosticCode::InvalidUnaryOperand => {
+            r#"A unary operator is applied to an operand it does not support.
+
+`-` negates signed numbers, `!` negates booleans and `~` inverts the bits of
+integers.
+
+Erroneous example:
+
+    let a: bool = -true;
+
+Corrected example:
+
+    let a: bool = !true;"#
+        }
+        DiagnosticCode::InvalidRange => {
+            r#"The bounds of a range are not integers.
+
+`for` loops over a range count from the start up to the end, both have to be
+integers.
+
+Erroneous example:
+
+    let end: f64 = 10.0;
+    var sum: i32 = 0;
+    for i in 0..end {
+      sum += i;
+    }
+
+Corrected example:
+
+    let end: i32 = 10;
+    var sum: i32 = 0;
+    for i in 0..end {
+      sum += i;
+    }"#
+        }
+        DiagnosticCode::DivisionByZero => {
+            r#"A number was divided by zero while the program ran.
+
+Division by zero has no result and stops the program. Check the divisor when
+it can be zero.
+
+Erroneous example:
+
+    let a: i32 = 0;
+    let b: i32 = 10 / a;
+
+Corrected example:
+
+    let a: i32 = 0;
+    var b: i32 = 0;
+    if a != 0 {
+      b = 10 / a;
+    }"#
+        }
+        DiagnosticCode::LoopLimitExceeded => {
+            r#"A loop ran more iterations than allowed.
+
+Loops stop the program once they run more iterations than the limit, which
+catches loops that never end. The limit is set with `--max-loop-iterations`.
+
+Erroneous example:
+
+    var i: i32 = 0;
+    var sum: i32 = 0;
+    while i < 10 {
+      sum += i;
+    }
+
+Corrected example:
+
+    var i: i32 = 0;
+    var sum: i32 = 0;
+    while i < 10 {
+      sum += i;
+      i += 1;
+    }"#
+        }
+        DiagnosticCode::UnterminatedString => {
+            r#"A string literal is missing its closing quote.
+
+String literals end with `"` on the same line, a quote inside of a string is
+escaped as `\"`.
+
+Erroneous example:
+
+    let a: str = "hello;
+
+Corrected example:
+
+    let a: str = "hello";"#
+        }
+        DiagnosticCode::UnterminatedCharacter => {
+            r#"A character literal is missing its closing quote.
+
+Character literals hold one character between single quotes.
+
+Erroneous example:
+
+    let a: char = 'a;
+
+Corrected example:
+
+    let a: char = 'a';"#
+        }
+        DiagnosticCode::EmptyCharacter => {
+            r#"A character literal holds no character.
+
+A character literal holds exactly one character, an empty text is a string.
+
+Erroneous example:
+
+    let a: char = '';
+
+Corrected example:
+
+    let a: char = ' ';"#
+        }
+        DiagnosticCode::MultipleCharacters => {
+            r#"A character literal holds more than one character.
+
+Character literals hold a single character, text is written as a string
+between double quotes.
+
+Erroneous example:
+
+    let a: char = 'ab';
+
+Corrected example:
+
+    let a: str = "ab";"#
+        }
+        DiagnosticCode::InvalidEscapeSequence => {
+            r#"A string or character literal contains an unknown escape sequence.
+
+The escape sequences are `\n`, `\t`, `\r`, `\0`, `\\`, `\'` and `\"`.
+
+Erroneous example:
+
+    let a: str = "C:\files";
+
+Corrected example:
+
+    let a: str = "C:\\files";"#
+        }
+        DiagnosticCode::DuplicateField => {
+            r#"A field is declared or initialized twice.
+
+The fields of a struct have distinct names, and a struct literal initializes
+each of them once.
+
+Erroneous example:
+
+    struct Point {
+      x: i32,
+      x: i32,
+    }
+
+Corrected example:
+
+    struct Point {
+      x: i32,
+      y: i32,
+    }"#
+        }
+        DiagnosticCode::UnknownField => {
+            r#"A struct has no field of the given name.
+
+Fields are accessed and initialized by the names the struct declares.
+
+Erroneous example:
+
+    struct Point {
+      x: i32,
+      y: i32,
+    }
+    let p: Point = Point { x: 1, y: 2 };
+    let z: i32 = p.z;
+
+Corrected example:
+
+    struct Point {
+      x: i32,
+      y: i32,
+    }
+    let p: Point = Point { x: 1, y: 2 };
+    let y: i32 = p.y;"#
+        }
+        DiagnosticCode::DuplicateMethod => {
+            r#"A struct declares two methods with the same name.
+
+Methods are looked up by their name, there is no overloading by parameters.
+
+Erroneous example:
+
+    struct Counter {
+      count: i32,
+      func get(self) -> i32 {
+        return self.count;
+      }
+      func get(self, offset: i32) -> i32 {
+        return self.count + offset;
+      }
+    }
+
+Corrected example:
+
+    struct Counter {
+      count: i32,
+      func get(self) -> i32 {
+        return self.count;
+      }
+      func get_with_offset(self, offset: i32) -> i32 {
+        return self.count + offset;
+      }
+    }"#
+        }
+        DiagnosticCode::UnknownMethod => {
+            r#"A struct has no method of the given name.
+
+Methods are called on values of the struct that declares them.
+
+Erroneous example:
+
+    struct Counter {
+      count: i32,
+      func get(self) -> i32 {
+        return self.count;
+      }
+    }
+    let c: Counter = Counter { count: 1 };
+    let n: i32 = c.value();
+
+Corrected example:
+
+    struct Counter {
+      count: i32,
+      func get(self) -> i32 {
+        return self.count;
+      }
+    }
+    let c: Counter = Counter { count: 1 };
+    let n: i32 = c.get();"#
+        }
+        DiagnosticCode::MissingFields => {
+            r#"A struct literal leaves fields uninitialized.
+
+Every field of a struct gets a value when the struct is created.
+
+Erroneous example:
+
+    struct Point {
+      x: i32,
+      y: i32,
+    }
+    let p: Point = Point { x: 1 };
+
+Corrected example:
+
+    struct Point {
+      x: i32,
+      y: i32,
+    }
+    let p: Point = Point { x: 1, y: 0 };"#
+        }
+        DiagnosticCode::InvalidAssignmentTarget => {
+            r#"A value is assigned to something that cannot be assigned to.
+
+Assignments change variables declared with `var`, and fields and elements of
+them.
+
+Erroneous example:
+
+    struct Point {
+      x: i32,
+    }
+    func origin() -> Point {
+      return Point { x: 0 };
+    }
+    origin().x = 1;
+
+Corrected example:
+
+    struct Point {
+      x: i32,
+    }
+    func origin() -> Point {
+      return Point { x: 0 };
+    }
+    var p: Point = origin();
+    p.x = 1;"#
+        }
+        DiagnosticCode::InvalidAddressOf => {
+            r#"The address of a value that is not stored anywhere is taken.
+
+`&` takes the address of a variable or of a field or element of one.
+Temporary values like literals and results of calls have no address.
+
+Erroneous example:
+
+    let p: *i32 = &1;
+
+Corrected example:
+
+    let a: i32 = 1;
+    let p: *i32 = &a;"#
+        }
+        DiagnosticCode::UncheckedOptional => {
+            r#"An optional value is used without checking it for null.
+
+Values of an optional type `T?` may be null. Inside of
+`if value != null { ... }` the value can be used as a `T`.
+
+Erroneous example:
+
+    func find() -> i32? {
+      return null;
+    }
+    let a: i32? = find();
+    let b: i32 = a + 1;
+
+Corrected example:
+
+    func find() -> i32? {
+      return null;
+    }
+    let a: i32? = find();
+    var b: i32 = 0;
+    if a != null {
+      b = a + 1;
+    }"#
+        }
+        DiagnosticCode::NotIndexable => {
+            r#"A value that is not an array is indexed.
+
+Only arrays have elements that are accessed with `[index]`.
+
+Erroneous example:
+
+    let a: i32 = 1;
+    let b: i32 = a[0];
+
+Corrected example:
+
+    let a: [i32; 1] = [1];
+    let b: i32 = a[0];"#
+        }
+        DiagnosticCode::InvalidIndex => {
+            r#"An array is indexed with a value that is not an integer.
+
+The elements of an array are numbered by integers starting at 0.
+
+Erroneous example:
+
+    let a: [i32; 2] = [1, 2];
+    let b: i32 = a[true];
+
+Corrected example:
+
+    let a: [i32; 2] = [1, 2];
+    let b: i32 = a[1];"#
+        }
+        DiagnosticCode::NotIterable => {
+            r#"A `for` loop iterates over a value that is not an array.
+
+`for` loops iterate over a range like `0..10` or over the elements of an
+array.
+
+Erroneous example:
+
+    let n: i32 = 3;
+    var sum: i32 = 0;
+    for i in n {
+      sum += i;
+    }
+
+Corrected example:
+
+    let n: i32 = 3;
+    var sum: i32 = 0;
+    for i in 0..n {
+      sum += i;
+    }"#
+        }
+        DiagnosticCode::IndexOutOfBounds => {
+            r#"An index is not within the bounds of the array.
+
+An array of length `n` has the elements `0` to `n - 1`. Constant indices are
+checked when the program is compiled, other ones when it runs.
+
+Erroneous example:
+
+    let a: [i32; 2] = [1, 2];
+    let b: i32 = a[2];
+
+Corrected example:
+
+    let a: [i32; 2] = [1, 2];
+    let b: i32 = a[1];"#
+        }
+        DiagnosticCode::NullDereference => {
+            r#"A null pointer was dereferenced while the program ran.
+
+Pointers may be null, which points to no value. Check a pointer for null
+before dereferencing it.
+
+Erroneous example:
+
+    let p: *i32 = null;
+    let a: i32 = *p;
+
+Corrected example:
+
+    let p: *i32 = null;
+    var a: i32 = 0;
+    if p != null {
+      a = *p;
+    }"#
+        }
+        DiagnosticCode::DanglingPointer => {
+            r#"A pointer was dereferenced after the value it points to was freed.
+
+Variables are freed when the block that declares them ends. A pointer to one
+of them must not be used afterwards. A pointer to a field of an optional struct
+dangles as well once the optional is set to `null`.
+
+Erroneous example:
+
+    var p: *i32 = null;
+    {
+      let a: i32 = 1;
+      p = &a;
+    }
+    let b: i32 = *p;
+
+Corrected example:
+
+    var p: *i32 = null;
+    let a: i32 = 1;
+    {
+      p = &a;
+    }
+    let b: i32 = *p;"#
+        }
+        DiagnosticCode::OutsideOfLoop => {
+            r#"`break` or `continue` is used outside of a loop.
+
+`break` leaves the innermost loop and `continue` starts its next iteration, so
+both need a loop around them.
+
+Erroneous example:
+
+    var i: i32 = 0;
+    if i < 10 {
+      break;
+    }
+
+Corrected example:
+
+    var i: i32 = 0;
+    while true {
+      if i >= 10 {
+        break;
+      }
+      i += 1;
+    }"#
+        }
+        DiagnosticCode::ReturnOutsideOfFunction => {
+            r#"`return` is used outside of a function in a program.
+
+Programs start at `func main()`, their top-level statements only declare and
+initialize. Scripts, run with `--script`, may return a result from the top
+level.
+
+Erroneous example:
+
+    module main;
+
+    return 0;
+
+Corrected example:
+
+    module main;
+
+    func main() -> i32 {
+      return 0;
+    }"#
+        }
+        DiagnosticCode::MissingEntryPoint => {
+            r#"A program has no `func main()`.
+
+Programs start by calling `main` of the entry module. Files without one can
+be run as scripts with `--script`.
+
+Erroneous example:
+
+    module main;
+
+    func start() -> i32 {
+      return 0;
+    }
+
+Corrected example:
+
+    module main;
+
+    func main() -> i32 {
+      return 0;
+    }"#
+        }
+        DiagnosticCode::DuplicateEntryPoint => {
+            r#"`main` is declared more than once.
+
+A program has exactly one entry point.
+
+Erroneous example:
+
+    func main() -> i32 {
+      return 0;
+    }
+    func main() -> i32 {
+      return 1;
+    }
+
+Corrected example:
+
+    func main() -> i32 {
+      return 0;
+    }"#
+        }
+        DiagnosticCode::InvalidEntryPoint => {
+            r#"`main` has parameters or returns something else than `i32` or `void`.
+
+The entry point is called without arguments. The `i32` it returns is the exit
+code of the program.
+
+Erroneous example:
+
+    func main(argument: i32) -> str {
+      return "done";
+    }
+
+Corrected example:
+
+    func main() -> i32 {
+      return 0;
+    }"#
+        }
+        DiagnosticCode::MisplacedModuleStatement => {
+            r#"`module` or `import` comes after other statements.
+
+A file starts with its optional `module` declaration, followed by its
+imports, before any other statement.
+
+Erroneous example:
+
+    // main.zn
+    let a: i32 = 1;
+    import shapes;
+    // shapes.zn
+    module shapes;
+
+Corrected example:
+
+    // main.zn
+    import shapes;
+    let a: i32 = 1;
+    // shapes.zn
+    module shapes;"#
+        }
+        DiagnosticCode::ModuleNotFound => {
+            r#"An imported module has no file.
+
+`import name;` loads `name.zn` from the directory of the importing file.
+
+Erroneous example:
+
+    // main.zn
+    import geometry;
+    // shapes.zn
+    module shapes;
+
+Corrected example:
+
+    // main.zn
+    import shapes;
+    // shapes.zn
+    module shapes;"#
+        }
+        DiagnosticCode::ModuleNameMismatch => {
+            r#"A file declares another module than its file name.
+
+The module of a file is named after the file, `module` repeats that name.
+
+Erroneous example:
+
+    // main.zn
+    import shapes;
+    // shapes.zn
+    module geometry;
+
+Corrected example:
+
+    // main.zn
+    import shapes;
+    // shapes.zn
+    module shapes;"#
+        }
+        DiagnosticCode::ImportCycle => {
+            r#"Modules import each other.
+
+Imported modules are loaded before the modules importing them, which is
+impossible for modules that import each other. Move what both need into a
+third module.
+
+Erroneous example:
+
+    // main.zn
+    import a;
+    // a.zn
+    module a;
+    import b;
+    // b.zn
+    module b;
+    import a;
+
+Corrected example:
+
+    // main.zn
+    import a;
+    // a.zn
+    module a;
+    import b;
+    // b.zn
+    module b;"#
+        }
+        DiagnosticCode::AssignmentToImmutable => {
+            r#"A variable declared with `let` is assigned to.
+
+`let` declares an immutable variable: neither the variable nor its fields and
+elements can be assigned after the initialization. Loop variables are
+immutable as well. Declare the variable with `var` to change it.
+
+Erroneous example:
+
+    let sum: i32 = 0;
+    sum += 1;
+
+Corrected example:
+
+    var sum: i32 = 0;
+    sum += 1;"#
+        }
+        DiagnosticCode::DuplicateVariable => {
+            r#"A variable is declared twice in the same scope.
+
+A name can only be declared once per block. A block inside of it can declare
+the name again, which hides the outer variable until the block ends.
+
+Erroneous example:
+
+    let a: i32 = 1;
+    let a: i32 = 2;
+
+Corrected example:
+
+    let a: i32 = 1;
+    let b: i32 = 2;"#
+        }
+        DiagnosticCode::IntegerLiteralOutOfRange => {
+            r#"An integer literal does not fit into its type.
+
+Integer literals can be at most 18446744073709551615, the largest `u64`. A
+literal that initializes a variable, is passed as an argument or is returned
+also has to fit into the declared type instead of wrapping around. The same
+holds for an expression made only of literals, whose value is computed first.
+
+Erroneous example:
+
+    let a: u8 = 256;
+    let b: u8 = 200 + 100;
+
+Corrected example:
+
+    let a: u16 = 256;
+    let b: u16 = 200 + 100;"#
+        }
+        DiagnosticCode::CallDepthExceeded => {
+            r#"Function calls nested deeper than allowed.
+
+Every call that has not returned yet counts towards the limit, which stops
+recursion that never ends before it overflows the stack. The limit is set with
+`--max-call-depth`.
+
+Erroneous example:
+
+    func count(n: i32) -> i32 {
+      return count(n + 1);
+    }
+    count(0);
+
+Corrected example:
+
+    func count(n: i32) -> i32 {
+      if n == 10 {
+        return n;
+      }
+      return count(n + 1);
+    }
+    count(0);"#
+        }
+        DiagnosticCode::MissingReturn => {
+            r#"A function with a return type can end without returning a value.
+
+Every path through the body of a function that does not return `void` has to
+end with a `return`. An `if` only returns on every path when it has an `else`
+and both branches return, a loop may not run at all.
+
+Erroneous example:
+
+    func sign(n: i32) -> i32 {
+      if n < 0 {
+        return -1;
+      }
+    }
+    sign(2);
+
+Corrected example:
+
+    func sign(n: i32) -> i32 {
+      if n < 0 {
+        return -1;
+      }
+      return 1;
+    }
+    sign(2);"#
+        }
+        DiagnosticCode::DuplicateStruct => {
+            r#"A struct name is declared twice.
+
+Struct names are shared by all modules of a program, so two modules cannot
+declare a struct of the same name either.
+
+Erroneous example:
+
+    // main.zn
+    import shapes;
+    struct Point { x: i32 }
+    // shapes.zn
+    module shapes;
+    struct Point { x: f64, y: f64 }
+
+Corrected example:
+
+    // main.zn
+    import shapes;
+    struct Point { x: i32 }
+    // shapes.zn
+    module shapes;
+    struct Vector { x: f64, y: f64 }"#
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::explanation;
+    use crate::compilation_unit::{CompilationUnit, CompileOptions};
+    use crate::diagnostics::printer::DiagnosticsFormat;
+    use crate::diagnostics::{DiagnosticCode, DiagnosticsColletion};
+    use crate::source_text::SourceText;
+
+    // Erroneous and corrected example of the explanation
+    fn examples(code: DiagnosticCode) -> (String, String) {
+        let (_, examples) = explanation(code)
+            .split_once("\n\nErroneous example:\n\n")
+            .unwrap();
+        let (erroneous, corrected) = examples.split_once("\n\nCorrected example:\n\n").unwrap();
+        let unindent = |example: &str| {
+            let lines = example
+                .lines()
+                .map(|line| line.strip_prefix("    ").unwrap_or(line));
+            lines.collect::<Vec<&str>>().join("\n")
+        };
+        (unindent(erroneous), unindent(corrected))
+    }
+
+    // Codes reported for an example when it is compiled and run. Examples of
+    // several files start each file with a `// name.zn` line, the first one
+    // is `main.zn`. Examples with `func main` or `module main` are programs,
+    // others scripts.
+    fn diagnose(example: &str) -> Vec<DiagnosticCode> {
+        let mut files: HashMap<String, String> = HashMap::new();
+        let mut file = "main.zn".to_string();
+        for line in example.lines() {
+            match line.strip_prefix("// ") {
+                Some(name) if name.ends_with(".zn") => file = name.to_string(),
+                _ => files
+                    .entry(file.clone())
+                    .or_default()
+                    .push_str(&format!("{}\n", line)),
+            }
+        }
+        let load = |name: &str| {
+            let path = format!("{}.zn", name);
+            let input = files.get(&path).ok_or(path.clone())?;
+            Ok(SourceText::new(path, input.clone()))
+        };
+        let entry = load("main").unwrap();
+        let options = CompileOptions {
+            script_mode: !entry.input().contains("func main")
+                && !entry.input().contains("module main"),
+            colored: false,
+            diagnostics_format: DiagnosticsFormat::Json,
+        };
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let compiled = CompilationUnit::compile_into(
+            "main",
+            entry,
+            &load,
+            options,
+            Rc::clone(&diagnostics_colletion),
+        );
+        if let Ok(mut compilation_unit) = compiled {
+            compilation_unit.set_max_loop_iterations(100);
+            compilation_unit.set_max_call_depth(100);
+            let _ = compilation_unit.run();
+        }
+        let diagnostics = &diagnostics_colletion.borrow().diagnostics;
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect()
+    }
+
+    #[test]
+    fn should_explain_every_code() {
+        for (index, code) in DiagnosticCode::ALL.into_iter().enumerate() {
+            // codes are numbered without gaps, so none is missing in `ALL`
+            assert_eq!(code as usize, index + 1);
+            assert_eq!(code.to_string().parse(), Ok(code));
+            let (summary, _) = explanation(code).split_once("\n\n").unwrap();
+            assert!(summary.ends_with('.'), "{} has no summary", code);
+            examples(code);
+        }
+        assert_eq!(DiagnosticCode::UndefinedVariable.to_string(), "Z0003");
+    }
+
+    #[test]
+    fn should_report_the_code_of_the_erroneous_example() {
+        for code in DiagnosticCode::ALL {
+            let (erroneous, corrected) = examples(code);
+            assert!(
+                diagnose(&erroneous).contains(&code),
+                "erroneous example of {} reports {:?}",
+                code,
+                diagnose(&erroneous)
+            );
+            assert_eq!(
+                diagnose(&corrected),
+                vec![],
+                "corrected example of {}",
+                code
+            );
+        }
+    }
+}
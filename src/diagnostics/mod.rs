@@ -1,31 +1,236 @@
+pub mod explanations;
 pub mod printer;
 
 use crate::ast::lexer::{TextSpan, Token, TokenKind};
-use std::{cell::RefCell, rc::Rc};
+use crate::ast::type_checker::Type;
+use std::str::FromStr;
+use std::{cell::RefCell, fmt, rc::Rc};
 
 pub enum DiagnosticKind {
     Error,
+    #[allow(dead_code)]
     Warning,
 }
 
+// Stable identifier of a kind of diagnostic, printed as `Z0001`. Codes are
+// never reused, new kinds get the next free number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagnosticCode {
+    UnexpectedToken = 1,
+    ExpectedExpression = 2,
+    UndefinedVariable = 3,
+    NumberOfFunctionArgumentsMismatch = 4,
+    UnknownType = 5,
+    InvalidArrayLength = 6,
+    TypeMismatch = 7,
+    IncompatibleOperands = 8,
+    InvalidUnaryOperand = 9,
+    InvalidRange = 10,
+    DivisionByZero = 11,
+    LoopLimitExceeded = 12,
+    UnterminatedString = 13,
+    UnterminatedCharacter = 14,
+    EmptyCharacter = 15,
+    MultipleCharacters = 16,
+    InvalidEscapeSequence = 17,
+    DuplicateField = 18,
+    UnknownField = 19,
+    DuplicateMethod = 20,
+    UnknownMethod = 21,
+    MissingFields = 22,
+    InvalidAssignmentTarget = 23,
+    InvalidAddressOf = 24,
+    UncheckedOptional = 25,
+    NotIndexable = 26,
+    InvalidIndex = 27,
+    NotIterable = 28,
+    IndexOutOfBounds = 29,
+    NullDereference = 30,
+    DanglingPointer = 31,
+    OutsideOfLoop = 32,
+    ReturnOutsideOfFunction = 33,
+    MissingEntryPoint = 34,
+    DuplicateEntryPoint = 35,
+    InvalidEntryPoint = 36,
+    MisplacedModuleStatement = 37,
+    ModuleNotFound = 38,
+    ModuleNameMismatch = 39,
+    ImportCycle = 40,
+    AssignmentToImmutable = 41,
+    DuplicateVariable = 42,
+    IntegerLiteralOutOfRange = 43,
+    CallDepthExceeded = 44,
+    MissingReturn = 45,
+    DuplicateStruct = 46,
+}
+
+impl DiagnosticCode {
+    pub const ALL: [DiagnosticCode; 46] = [
+        DiagnosticCode::UnexpectedToken,
+        DiagnosticCode::ExpectedExpression,
+        DiagnosticCode::UndefinedVariable,
+        DiagnosticCode::NumberOfFunctionArgumentsMismatch,
+        DiagnosticCode::UnknownType,
+        DiagnosticCode::InvalidArrayLength,
+        DiagnosticCode::TypeMismatch,
+        DiagnosticCode::IncompatibleOperands,
+        DiagnosticCode::InvalidUnaryOperand,
+        DiagnosticCode::InvalidRange,
+        DiagnosticCode::DivisionByZero,
+        DiagnosticCode::LoopLimitExceeded,
+        DiagnosticCode::UnterminatedString,
+        DiagnosticCode::UnterminatedCharacter,
+        DiagnosticCode::EmptyCharacter,
+        DiagnosticCode::MultipleCharacters,
+        DiagnosticCode::InvalidEscapeSequence,
+        DiagnosticCode::DuplicateField,
+        DiagnosticCode::UnknownField,
+        DiagnosticCode::DuplicateMethod,
+        DiagnosticCode::UnknownMethod,
+        DiagnosticCode::MissingFields,
+        DiagnosticCode::InvalidAssignmentTarget,
+        DiagnosticCode::InvalidAddressOf,
+        DiagnosticCode::UncheckedOptional,
+        DiagnosticCode::NotIndexable,
+        DiagnosticCode::InvalidIndex,
+        DiagnosticCode::NotIterable,
+        DiagnosticCode::IndexOutOfBounds,
+        DiagnosticCode::NullDereference,
+        DiagnosticCode::DanglingPointer,
+        DiagnosticCode::OutsideOfLoop,
+        DiagnosticCode::ReturnOutsideOfFunction,
+        DiagnosticCode::MissingEntryPoint,
+        DiagnosticCode::DuplicateEntryPoint,
+        DiagnosticCode::InvalidEntryPoint,
+        DiagnosticCode::MisplacedModuleStatement,
+        DiagnosticCode::ModuleNotFound,
+        DiagnosticCode::ModuleNameMismatch,
+        DiagnosticCode::ImportCycle,
+        DiagnosticCode::AssignmentToImmutable,
+        DiagnosticCode::DuplicateVariable,
+        DiagnosticCode::IntegerLiteralOutOfRange,
+        DiagnosticCode::CallDepthExceeded,
+        DiagnosticCode::MissingReturn,
+        DiagnosticCode::DuplicateStruct,
+    ];
+}
+
+impl FromStr for DiagnosticCode {
+    type Err = ();
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.to_string().eq_ignore_ascii_case(code))
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Z{:04}", *self as u16)
+    }
+}
+
+// Secondary span of a diagnostic, like the declaration the error refers to
+pub struct Label {
+    pub(crate) span: TextSpan,
+    pub(crate) message: String,
+    // module of the span if it is another one than the one of the diagnostic
+    pub(crate) module: Option<String>,
+}
+
 pub struct Diagnostic {
     pub(crate) message: String,
     pub(crate) kind: DiagnosticKind,
+    pub(crate) code: DiagnosticCode,
+    // primary span
     pub(crate) span: TextSpan,
+    // module whose source the span points into
+    pub(crate) module: String,
+    pub(crate) labels: Vec<Label>,
+    // `note:` lines telling why the code is wrong
+    pub(crate) notes: Vec<String>,
+    // `help:` lines telling how to fix it
+    pub(crate) help: Vec<String>,
 }
 
 impl Diagnostic {
-    pub fn new(message: String, kind: DiagnosticKind, span: TextSpan) -> Self {
+    pub fn new(
+        message: String,
+        kind: DiagnosticKind,
+        code: DiagnosticCode,
+        span: TextSpan,
+        module: String,
+    ) -> Self {
         Self {
             message,
             kind,
+            code,
             span,
+            module,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
         }
     }
+
+    pub fn with_label(&mut self, span: TextSpan, message: String) -> &mut Self {
+        self.labels.push(Label {
+            span,
+            message,
+            module: None,
+        });
+        self
+    }
+
+    // Label of a span in the given module, `None` is the module of the diagnostic
+    pub fn with_label_in(
+        &mut self,
+        module: Option<&str>,
+        span: TextSpan,
+        message: String,
+    ) -> &mut Self {
+        self.labels.push(Label {
+            span,
+            message,
+            module: module
+                .filter(|module| *module != self.module)
+                .map(str::to_string),
+        });
+        self
+    }
+
+    pub fn with_note(&mut self, note: String) -> &mut Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(&mut self, help: String) -> &mut Self {
+        self.help.push(help);
+        self
+    }
+
+    // Message followed by the `note:` and `help:` lines, for outputs that
+    // only show text
+    pub fn message_with_notes(&self) -> String {
+        let notes = self.notes.iter().map(|note| format!("\nnote: {}", note));
+        let help = self.help.iter().map(|help| format!("\nhelp: {}", help));
+        notes
+            .chain(help)
+            .fold(self.message.clone(), |message, line| message + &line)
+    }
+
+    // Module of the label's span
+    pub fn module_of<'a>(&'a self, label: &'a Label) -> &'a str {
+        label.module.as_deref().unwrap_or(&self.module)
+    }
 }
 
 pub struct DiagnosticsColletion {
     pub diagnostics: Vec<Diagnostic>,
+    // module that is currently parsed, checked or run
+    module: String,
 }
 
 pub type DiagnosticsColletionCell = Rc<RefCell<DiagnosticsColletion>>;
@@ -34,25 +239,60 @@ impl DiagnosticsColletion {
     pub fn new() -> Self {
         Self {
             diagnostics: vec![],
+            module: String::new(),
         }
     }
 
+    // Diagnostics reported from now on belong to the given module
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    pub fn set_module(&mut self, module: &str) {
+        self.module = module.to_string();
+    }
+
     pub fn clear(&mut self) {
         self.diagnostics.clear();
     }
 
-    pub fn report_error(&mut self, message: String, span: TextSpan) {
-        self.diagnostics
-            .push(Diagnostic::new(message, DiagnosticKind::Error, span));
+    // The diagnostic can be extended by labels, notes and help
+    pub fn report_error(
+        &mut self,
+        code: DiagnosticCode,
+        message: String,
+        span: TextSpan,
+    ) -> &mut Diagnostic {
+        self.diagnostics.push(Diagnostic::new(
+            message,
+            DiagnosticKind::Error,
+            code,
+            span,
+            self.module.clone(),
+        ));
+        self.diagnostics.last_mut().unwrap()
     }
 
-    pub fn report_warning(&mut self, message: String, span: TextSpan) {
-        self.diagnostics
-            .push(Diagnostic::new(message, DiagnosticKind::Warning, span));
+    #[allow(dead_code)]
+    pub fn report_warning(
+        &mut self,
+        code: DiagnosticCode,
+        message: String,
+        span: TextSpan,
+    ) -> &mut Diagnostic {
+        self.diagnostics.push(Diagnostic::new(
+            message,
+            DiagnosticKind::Warning,
+            code,
+            span,
+            self.module.clone(),
+        ));
+        self.diagnostics.last_mut().unwrap()
     }
 
     pub fn report_unexpected_token(&mut self, expected_tokenkind: &TokenKind, found_token: &Token) {
         self.report_error(
+            DiagnosticCode::UnexpectedToken,
             format!(
                 "Expected <{}>, but found <{}>",
                 expected_tokenkind, found_token.kind
@@ -62,27 +302,539 @@ impl DiagnosticsColletion {
     }
     pub fn report_expected_expression(&mut self, found_token: &Token) {
         self.report_error(
+            DiagnosticCode::ExpectedExpression,
             format!("Expected expression, but found <{}>", found_token.kind),
             found_token.span.clone(),
         );
     }
 
-    pub fn report_undefined_variable(&mut self, span: TextSpan) {
-        self.report_error(format!("Not found in this scope"), span);
+    // `similar` is the declaration of a name close to the unknown one, in the
+    // given module, `None` is the current one
+    pub fn report_undefined_variable(
+        &mut self,
+        span: TextSpan,
+        similar: Option<(&Token, Option<&str>)>,
+    ) {
+        let diagnostic = self.report_error(
+            DiagnosticCode::UndefinedVariable,
+            "Not found in this scope".to_string(),
+            span,
+        );
+        if let Some((declaration, module)) = similar {
+            let name = &declaration.span.literal;
+            diagnostic
+                .with_label_in(
+                    module,
+                    declaration.span.clone(),
+                    format!("<{}> is declared here", name),
+                )
+                .with_help(format!("did you mean <{}>?", name));
+        }
     }
 
+    // The declaration is in the given module, `None` is the current one
     pub fn report_number_of_function_arguments_mismatch(
         &mut self,
         span: TextSpan,
         expected: usize,
         found: usize,
+        declaration: &Token,
+        module: Option<&str>,
     ) {
         self.report_error(
+            DiagnosticCode::NumberOfFunctionArgumentsMismatch,
             format!(
                 "Function {} expects {} arguments but {} were given",
                 span.literal, expected, found
             ),
             span,
+        )
+        .with_label_in(
+            module,
+            declaration.span.clone(),
+            format!("declared with {} parameters here", expected),
+        );
+    }
+
+    pub fn report_unknown_type(&mut self, found_token: &Token) {
+        self.report_error(
+            DiagnosticCode::UnknownType,
+            format!("Expected type, but found <{}>", found_token.kind),
+            found_token.span.clone(),
+        );
+    }
+
+    pub fn report_invalid_array_length(&mut self, found_token: &Token) {
+        self.report_error(
+            DiagnosticCode::InvalidArrayLength,
+            format!("Expected array length, but found <{}>", found_token.kind),
+            found_token.span.clone(),
+        );
+    }
+
+    pub fn report_type_mismatch(&mut self, span: TextSpan, expected: &Type, found: &Type) {
+        self.report_error(
+            DiagnosticCode::TypeMismatch,
+            format!("Expected type <{}>, but found <{}>", expected, found),
+            span,
+        );
+    }
+
+    pub fn report_incompatible_operands(
+        &mut self,
+        span: TextSpan,
+        operator: &TokenKind,
+        left: &Type,
+        right: &Type,
+    ) {
+        self.report_error(
+            DiagnosticCode::IncompatibleOperands,
+            format!(
+                "Operator <{}> cannot be applied to <{}> and <{}>",
+                operator, left, right
+            ),
+            span,
+        );
+    }
+
+    pub fn report_invalid_unary_operand(
+        &mut self,
+        span: TextSpan,
+        operator: &TokenKind,
+        operand: &Type,
+    ) {
+        self.report_error(
+            DiagnosticCode::InvalidUnaryOperand,
+            format!("Operator <{}> cannot be applied to <{}>", operator, operand),
+            span,
+        );
+    }
+
+    pub fn report_invalid_range(&mut self, span: TextSpan, start: &Type, end: &Type) {
+        self.report_error(
+            DiagnosticCode::InvalidRange,
+            format!(
+                "Range bounds must be integers, but found <{}> and <{}>",
+                start, end
+            ),
+            span,
+        );
+    }
+
+    pub fn report_division_by_zero(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::DivisionByZero,
+            "Division by zero".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_loop_limit_exceeded(&mut self, span: TextSpan, max_iterations: usize) {
+        self.report_error(
+            DiagnosticCode::LoopLimitExceeded,
+            format!("Loop exceeded the limit of {} iterations", max_iterations),
+            span,
+        );
+    }
+
+    pub fn report_unterminated_string(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::UnterminatedString,
+            "Unterminated string literal".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_unterminated_character(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::UnterminatedCharacter,
+            "Unterminated character literal".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_empty_character(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::EmptyCharacter,
+            "Empty character literal".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_multiple_characters(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::MultipleCharacters,
+            "Character literal must contain exactly one character".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_invalid_escape_sequence(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::InvalidEscapeSequence,
+            format!("Invalid escape sequence <{}>", span.literal),
+            span,
+        );
+    }
+
+    pub fn report_duplicate_field(&mut self, field: &Token, struct_name: &str, first: &Token) {
+        self.report_error(
+            DiagnosticCode::DuplicateField,
+            format!(
+                "Field <{}> is already declared in <{}>",
+                field.span.literal, struct_name
+            ),
+            field.span.clone(),
+        )
+        .with_label(first.span.clone(), "first declared here".to_string());
+    }
+
+    // `module` is the one of the first declaration, `None` is the current one
+    pub fn report_duplicate_struct(
+        &mut self,
+        identifier: &Token,
+        first: &Token,
+        module: Option<&str>,
+    ) {
+        self.report_error(
+            DiagnosticCode::DuplicateStruct,
+            format!("Struct <{}> is already declared", identifier.span.literal),
+            identifier.span.clone(),
+        )
+        .with_label_in(
+            module,
+            first.span.clone(),
+            "first declared here".to_string(),
+        )
+        .with_note("struct names are shared by all modules".to_string());
+    }
+
+    pub fn report_unknown_field(&mut self, field: &Token, data_type: &Type) {
+        self.report_error(
+            DiagnosticCode::UnknownField,
+            format!("Type <{}> has no field <{}>", data_type, field.span.literal),
+            field.span.clone(),
+        );
+    }
+
+    pub fn report_duplicate_method(&mut self, method: &Token, struct_name: &str, first: &Token) {
+        self.report_error(
+            DiagnosticCode::DuplicateMethod,
+            format!(
+                "Method <{}> is already declared in <{}>",
+                method.span.literal, struct_name
+            ),
+            method.span.clone(),
+        )
+        .with_label(first.span.clone(), "first declared here".to_string())
+        .with_note("methods are looked up by their name alone".to_string());
+    }
+
+    pub fn report_unknown_method(&mut self, method: &Token, data_type: &Type) {
+        self.report_error(
+            DiagnosticCode::UnknownMethod,
+            format!(
+                "Type <{}> has no method <{}>",
+                data_type, method.span.literal
+            ),
+            method.span.clone(),
+        );
+    }
+
+    pub fn report_missing_fields(&mut self, identifier: &Token, missing: &[&str]) {
+        self.report_error(
+            DiagnosticCode::MissingFields,
+            format!(
+                "Missing fields <{}> in initializer of <{}>",
+                missing.join(", "),
+                identifier.span.literal
+            ),
+            identifier.span.clone(),
+        );
+    }
+
+    pub fn report_invalid_assignment_target(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::InvalidAssignmentTarget,
+            "Cannot assign to this expression".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_invalid_address_of(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::InvalidAddressOf,
+            "Cannot take the address of this expression".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_unchecked_optional(&mut self, span: TextSpan, data_type: &Type) {
+        self.report_error(
+            DiagnosticCode::UncheckedOptional,
+            format!(
+                "Value of optional type <{}> is used without checking it for null",
+                data_type
+            ),
+            span,
+        )
+        .with_help(
+            "check it first, it can be used inside of `if value != null { ... }`".to_string(),
+        );
+    }
+
+    pub fn report_not_indexable(&mut self, span: TextSpan, data_type: &Type) {
+        self.report_error(
+            DiagnosticCode::NotIndexable,
+            format!("Type <{}> cannot be indexed", data_type),
+            span,
+        );
+    }
+
+    pub fn report_invalid_index(&mut self, span: TextSpan, data_type: &Type) {
+        self.report_error(
+            DiagnosticCode::InvalidIndex,
+            format!("Expected integer index, but found <{}>", data_type),
+            span,
+        );
+    }
+
+    pub fn report_not_iterable(&mut self, span: TextSpan, data_type: &Type) {
+        self.report_error(
+            DiagnosticCode::NotIterable,
+            format!("Type <{}> cannot be iterated over", data_type),
+            span,
+        );
+    }
+
+    pub fn report_index_out_of_bounds(&mut self, span: TextSpan, index: i128, length: usize) {
+        self.report_error(
+            DiagnosticCode::IndexOutOfBounds,
+            format!(
+                "Index {} is out of bounds for an array of length {}",
+                index, length
+            ),
+            span,
+        );
+    }
+
+    pub fn report_null_dereference(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::NullDereference,
+            "Null pointer dereferenced".to_string(),
+            span,
+        );
+    }
+
+    pub fn report_dangling_pointer(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::DanglingPointer,
+            "Pointer dereferenced after the value it points to was freed or set to null"
+                .to_string(),
+            span,
+        );
+    }
+
+    pub fn report_outside_of_loop(&mut self, keyword: &Token) {
+        self.report_error(
+            DiagnosticCode::OutsideOfLoop,
+            format!("<{}> can only be used inside of a loop", keyword.kind),
+            keyword.span.clone(),
+        );
+    }
+
+    pub fn report_return_outside_of_function(&mut self, keyword: &Token) {
+        self.report_error(
+            DiagnosticCode::ReturnOutsideOfFunction,
+            format!(
+                "<{}> outside of a function is only allowed in script mode",
+                keyword.kind
+            ),
+            keyword.span.clone(),
+        );
+    }
+
+    pub fn report_missing_entry_point(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::MissingEntryPoint,
+            "Missing entry point, expected <func main() -> i32>".to_string(),
+            span,
+        )
+        .with_help(
+            "declare `func main() -> i32`, or run the file as a script with `--script`".to_string(),
+        );
+    }
+
+    pub fn report_duplicate_entry_point(&mut self, identifier: &Token, first: &Token) {
+        self.report_error(
+            DiagnosticCode::DuplicateEntryPoint,
+            format!(
+                "Entry point <{}> is already declared",
+                identifier.span.literal
+            ),
+            identifier.span.clone(),
+        )
+        .with_label(first.span.clone(), "first declared here".to_string());
+    }
+
+    pub fn report_invalid_entry_point(&mut self, identifier: &Token) {
+        self.report_error(
+            DiagnosticCode::InvalidEntryPoint,
+            format!(
+                "Entry point <{}> takes no arguments and returns <i32> or <void>",
+                identifier.span.literal
+            ),
+            identifier.span.clone(),
+        );
+    }
+
+    pub fn report_misplaced_module_statement(&mut self, keyword: &Token) {
+        self.report_error(
+            DiagnosticCode::MisplacedModuleStatement,
+            format!(
+                "<{}> is only allowed at the start of a file, before any other statement",
+                keyword.kind
+            ),
+            keyword.span.clone(),
+        );
+    }
+
+    pub fn report_module_not_found(&mut self, module: &Token, path: &str) {
+        self.report_error(
+            DiagnosticCode::ModuleNotFound,
+            format!(
+                "Module <{}> not found, expected it in {}",
+                module.span.literal, path
+            ),
+            module.span.clone(),
+        );
+    }
+
+    pub fn report_module_name_mismatch(&mut self, declared: &Token, expected: &str) {
+        self.report_error(
+            DiagnosticCode::ModuleNameMismatch,
+            format!(
+                "Expected module <{}>, but the file declares <{}>",
+                expected, declared.span.literal
+            ),
+            declared.span.clone(),
+        )
+        .with_help(format!(
+            "rename the module to <{}> or the file to {}.zn",
+            expected, declared.span.literal
+        ));
+    }
+
+    pub fn report_import_cycle(&mut self, module: &Token, cycle: &[String]) {
+        self.report_error(
+            DiagnosticCode::ImportCycle,
+            format!("Import cycle: {}", cycle.join(" -> ")),
+            module.span.clone(),
+        );
+    }
+
+    pub fn report_assignment_to_immutable(
+        &mut self,
+        span: TextSpan,
+        declaration: &Token,
+        module: Option<&str>,
+    ) {
+        self.report_error(
+            DiagnosticCode::AssignmentToImmutable,
+            format!(
+                "Cannot assign to immutable variable <{}>",
+                declaration.span.literal
+            ),
+            span,
+        )
+        .with_label_in(
+            module,
+            declaration.span.clone(),
+            "declared here".to_string(),
+        )
+        .with_help("only variables declared with `var` can be assigned to".to_string());
+    }
+
+    pub fn report_duplicate_variable(
+        &mut self,
+        identifier: &Token,
+        first: &Token,
+        module: Option<&str>,
+    ) {
+        self.report_error(
+            DiagnosticCode::DuplicateVariable,
+            format!(
+                "Variable <{}> is already declared in this scope",
+                identifier.span.literal
+            ),
+            identifier.span.clone(),
+        )
+        .with_label_in(
+            module,
+            first.span.clone(),
+            "first declared here".to_string(),
+        );
+    }
+
+    pub fn report_integer_literal_too_large(&mut self, span: TextSpan) {
+        self.report_error(
+            DiagnosticCode::IntegerLiteralOutOfRange,
+            format!("Integer literal <{}> is too large", span.literal),
+            span,
+        )
+        .with_note(format!("the largest integer literal is {}", u64::MAX));
+    }
+
+    pub fn report_integer_literal_out_of_range(
+        &mut self,
+        span: TextSpan,
+        value: i128,
+        data_type: &Type,
+    ) {
+        self.report_error(
+            DiagnosticCode::IntegerLiteralOutOfRange,
+            format!(
+                "Integer literal <{}> does not fit into <{}>",
+                value, data_type
+            ),
+            span,
+        );
+    }
+
+    pub fn report_constant_out_of_range(&mut self, span: TextSpan, value: i128, data_type: &Type) {
+        self.report_error(
+            DiagnosticCode::IntegerLiteralOutOfRange,
+            format!(
+                "Constant expression evaluates to <{}>, which does not fit into <{}>",
+                value, data_type
+            ),
+            span,
+        );
+    }
+
+    pub fn report_call_depth_exceeded(&mut self, span: TextSpan, max_depth: usize) {
+        self.report_error(
+            DiagnosticCode::CallDepthExceeded,
+            format!("Calls exceeded the limit of {} nested calls", max_depth),
+            span,
+        )
+        .with_note(
+            "a function that calls itself needs a case that returns without a call".to_string(),
+        );
+    }
+
+    pub fn report_missing_return(&mut self, identifier: &Token, end: TextSpan, return_type: &Type) {
+        self.report_error(
+            DiagnosticCode::MissingReturn,
+            format!(
+                "Function <{}> does not return <{}> on every path",
+                identifier.span.literal, return_type
+            ),
+            identifier.span.clone(),
+        )
+        .with_label(
+            end,
+            "the function can end here without a return".to_string(),
         );
     }
 }
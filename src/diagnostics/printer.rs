@@ -1,57 +1,783 @@
-use super::Diagnostic;
+use super::explanations::explanation;
+use super::{Diagnostic, DiagnosticCode, DiagnosticKind};
+use crate::ast::lexer::TextSpan;
 use crate::source_text::SourceText;
+use crate::terminal::strip_colors;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
 use termion::color;
 
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// How the diagnostics are written to stderr
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagnosticsFormat {
+    // the source line with the span marked, for people
+    Human,
+    // one JSON object per line and diagnostic
+    Json,
+    // one SARIF 2.1.0 log with all diagnostics
+    Sarif,
+}
+
+impl FromStr for DiagnosticsFormat {
+    type Err = ();
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "human" => Ok(DiagnosticsFormat::Human),
+            "json" => Ok(DiagnosticsFormat::Json),
+            "sarif" => Ok(DiagnosticsFormat::Sarif),
+            _ => Err(()),
+        }
+    }
+}
+
 pub struct DiagnosticsPrinter<'a> {
-    source_text: &'a SourceText,
+    // source texts by the name of their module
+    sources: &'a HashMap<String, SourceText>,
     diagnostics: &'a [Diagnostic],
+    colored: bool,
+    format: DiagnosticsFormat,
 }
 
 impl<'a> DiagnosticsPrinter<'a> {
-    pub fn new(source_text: &'a SourceText, diagnostics: &'a [Diagnostic]) -> Self {
+    pub fn new(sources: &'a HashMap<String, SourceText>, diagnostics: &'a [Diagnostic]) -> Self {
         Self {
-            source_text,
+            sources,
             diagnostics,
+            colored: true,
+            format: DiagnosticsFormat::Human,
         }
     }
 
+    pub fn set_format(&mut self, format: DiagnosticsFormat) {
+        self.format = format;
+    }
+
+    pub fn set_colored(&mut self, colored: bool) {
+        self.colored = colored;
+    }
+
+    // A SARIF log is printed even without diagnostics
     pub fn print(&self) {
+        match self.format {
+            DiagnosticsFormat::Human => {
+                for diagnostic in self.diagnostics {
+                    // diagnostics are separated by an empty line
+                    let text = self.stringify_diagnostic(diagnostic);
+                    if self.colored {
+                        eprintln!("{}\n", text);
+                    } else {
+                        eprintln!("{}\n", strip_colors(&text));
+                    }
+                }
+            }
+            DiagnosticsFormat::Json => {
+                for diagnostic in self.diagnostics {
+                    eprintln!("{}", self.json_diagnostic(diagnostic));
+                }
+            }
+            DiagnosticsFormat::Sarif => eprintln!("{:#}", self.sarif_log()),
+        }
+    }
+
+    // {"severity":"error","code":"Z0003","message":"Not found in this scope",
+    //  "file":"main.zn","span":{"start":14,"end":15},
+    //  "start":{"line":2,"column":14},"end":{"line":2,"column":15},
+    //  "labels":[],"notes":[],"help":[]}
+    pub fn json_diagnostic(&self, diagnostic: &Diagnostic) -> Value {
+        let mut value = self.json_location(&diagnostic.module, &diagnostic.span);
+        value["severity"] = json!(severity(diagnostic));
+        value["code"] = json!(diagnostic.code.to_string());
+        value["message"] = json!(diagnostic.message);
+        value["labels"] = diagnostic
+            .labels
+            .iter()
+            .map(|label| {
+                let mut value = self.json_location(diagnostic.module_of(label), &label.span);
+                value["message"] = json!(label.message);
+                value
+            })
+            .collect();
+        value["notes"] = json!(diagnostic.notes);
+        value["help"] = json!(diagnostic.help);
+        value
+    }
+
+    fn json_location(&self, module: &str, span: &TextSpan) -> Value {
+        let source_text = &self.sources[module];
+        let (start_line, start_column) = source_text.get_position(span.start);
+        let (end_line, end_column) = source_text.get_position(span.end);
+        json!({
+            "file": source_text.path(),
+            "span": { "start": span.start, "end": span.end },
+            "start": { "line": start_line, "column": start_column },
+            "end": { "line": end_line, "column": end_column },
+        })
+    }
+
+    // Log of a single run, the codes of the diagnostics are its rules
+    pub fn sarif_log(&self) -> Value {
+        let mut rules: Vec<DiagnosticCode> = Vec::new();
+        let mut results = Vec::new();
         for diagnostic in self.diagnostics {
-            println!("{}", self.stringify_diagnostic(diagnostic));
+            let rule_index = match rules.iter().position(|code| *code == diagnostic.code) {
+                Some(index) => index,
+                None => {
+                    rules.push(diagnostic.code);
+                    rules.len() - 1
+                }
+            };
+            let related: Vec<Value> = diagnostic
+                .labels
+                .iter()
+                .enumerate()
+                .map(|(index, label)| {
+                    let mut location =
+                        self.sarif_location(diagnostic.module_of(label), &label.span);
+                    location["id"] = json!(index);
+                    location["message"] = json!({ "text": label.message });
+                    location
+                })
+                .collect();
+            results.push(json!({
+                "ruleId": diagnostic.code.to_string(),
+                "ruleIndex": rule_index,
+                "level": severity(diagnostic),
+                "message": { "text": diagnostic.message_with_notes() },
+                "locations": [self.sarif_location(&diagnostic.module, &diagnostic.span)],
+                "relatedLocations": related,
+            }));
         }
+        let rules: Vec<Value> = rules
+            .iter()
+            .map(|code| {
+                let summary = explanation(*code).lines().next().unwrap_or_default();
+                json!({
+                    "id": code.to_string(),
+                    "name": format!("{:?}", code),
+                    "shortDescription": { "text": summary },
+                })
+            })
+            .collect();
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    },
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": results,
+            }],
+        })
     }
 
-    // let b = 7 - elepant + aligator;
-    //             ^^^^^^^ Not found in this scope
-    pub fn stringify_diagnostic(&self, diagnostic: &Diagnostic) -> String {
-        let (line, col) = self.source_text.get_location(diagnostic.span.start);
-        let line_number = self.source_text.get_linenumber(diagnostic.span.start) - 1;
-        let symbol_len = diagnostic.span.literal.len();
-        let symbol_end_col = col + diagnostic.span.literal.len();
-        let prefix = line[..col].to_string();
-        let error_symbol = line[col..symbol_end_col].to_string();
-        let suffix = line[symbol_end_col..].to_string();
+    fn sarif_location(&self, module: &str, span: &TextSpan) -> Value {
+        let source_text = &self.sources[module];
+        let (start_line, start_column) = source_text.get_position(span.start);
+        let (end_line, end_column) = source_text.get_position(span.end);
+        json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": source_text.path() },
+                "region": {
+                    "startLine": start_line,
+                    "startColumn": start_column,
+                    "endLine": end_line,
+                    "endColumn": end_column,
+                    "byteOffset": span.start,
+                    "byteLength": span.end - span.start,
+                },
+            },
+        })
+    }
 
+    // error[Z0004]: Function add expects 2 arguments but 1 were given
+    //  --> src/main.zn:5:13
+    //   |
+    // 1 | func add(a: i32, b: i32) -> i32 {
+    //   |      --- declared with 2 parameters here
+    // ...
+    // 5 |     let x = add(1);
+    //   |             ^^^
+    pub fn stringify_diagnostic(&self, diagnostic: &Diagnostic) -> String {
         let message_color: Box<dyn color::Color> = match diagnostic.kind {
-            super::DiagnosticKind::Error => Box::new(color::Red),
-            super::DiagnosticKind::Warning => Box::new(color::Yellow),
+            DiagnosticKind::Error => Box::new(color::Red),
+            DiagnosticKind::Warning => Box::new(color::Yellow),
         };
 
-        let line_number_str = format!("{:2} | ", line_number);
-        let whitespace = " ".repeat(col + line_number_str.len());
-
-        format!(
-            // "{}{line_number_str}{}{prefix}{error_symbol}{suffix}\n{whitespace}{}{}\n{whitespace}|\n{whitespace}+-- {}{}",
-            "{}{line_number_str}{}{prefix}{error_symbol}{suffix}\n{whitespace}{}{} {}({}:{}){}",
+        // spans by module, the module of the primary span first
+        let primary = Annotation::new(
+            &self.sources[&diagnostic.module],
+            &diagnostic.span,
+            true,
+            "",
+        );
+        let mut sections: Vec<(&str, Vec<Annotation>)> =
+            vec![(diagnostic.module.as_str(), vec![primary])];
+        for label in diagnostic.labels.iter() {
+            let module = diagnostic.module_of(label);
+            let annotation =
+                Annotation::new(&self.sources[module], &label.span, false, &label.message);
+            match sections.iter_mut().find(|(name, _)| *name == module) {
+                Some((_, annotations)) => annotations.push(annotation),
+                None => sections.push((module, vec![annotation])),
+            }
+        }
+        let width = sections
+            .iter()
+            .flat_map(|(_, annotations)| shown_lines(annotations))
+            .max()
+            .map_or(1, |line| (line + 1).to_string().len());
+        let empty_gutter = format!(
+            "{}{} |{}\n",
             color::Fg(color::Blue),
-            color::Fg(color::Reset),
-            color::Fg(message_color.as_ref()),
-            "^".repeat(symbol_len),
-            diagnostic.message,
-            line_number,
-            col,
+            " ".repeat(width),
             color::Fg(color::Reset)
-        )
-        .to_string()
+        );
+
+        let mut text = format!(
+            "{}{}[{}]{}: {}\n",
+            color::Fg(message_color.as_ref()),
+            severity(diagnostic),
+            diagnostic.code,
+            color::Fg(color::Reset),
+            diagnostic.message
+        );
+        for (index, (module, annotations)) in sections.iter().enumerate() {
+            let source_text = &self.sources[*module];
+            if index > 0 {
+                text.push_str(&empty_gutter);
+            }
+            text.push_str(&format!(
+                "{}{}{}{} {}:{}:{}\n",
+                " ".repeat(width),
+                color::Fg(color::Blue),
+                if index == 0 { "-->" } else { ":::" },
+                color::Fg(color::Reset),
+                source_text.path(),
+                annotations[0].start_line + 1,
+                annotations[0].start_column + 1
+            ));
+            text.push_str(&empty_gutter);
+            self.render_section(
+                &mut text,
+                source_text.input(),
+                annotations,
+                width,
+                message_color.as_ref(),
+            );
+        }
+
+        let notes = diagnostic.notes.iter().map(|note| ("note", note));
+        let help = diagnostic.help.iter().map(|help| ("help", help));
+        for (index, (kind, line)) in notes.chain(help).enumerate() {
+            if index == 0 {
+                text.push_str(&empty_gutter);
+            }
+            text.push_str(&format!(
+                "{} {}={} {}: {}\n",
+                " ".repeat(width),
+                color::Fg(color::Blue),
+                color::Fg(color::Reset),
+                kind,
+                line
+            ));
+        }
+        text.pop();
+        text
+    }
+
+    // Source lines of the spans of one module with the markers below them
+    fn render_section(
+        &self,
+        text: &mut String,
+        input: &str,
+        annotations: &[Annotation],
+        width: usize,
+        message_color: &dyn color::Color,
+    ) {
+        let lines: Vec<&str> = input
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        // multi-line spans are drawn in columns left of the source, spans on
+        // separate lines share one
+        let mut multiline: Vec<(usize, &Annotation)> = Vec::new();
+        let mut spans: Vec<&Annotation> = annotations
+            .iter()
+            .filter(|annotation| annotation.is_multiline())
+            .collect();
+        spans.sort_by_key(|annotation| annotation.start_line);
+        for annotation in spans {
+            let column = (0..)
+                .map(|column| column * 2)
+                .find(|column| {
+                    multiline.iter().all(|(other_column, other)| {
+                        other_column != column || other.end_line < annotation.start_line
+                    })
+                })
+                .unwrap();
+            multiline.push((column, annotation));
+        }
+        let margin = multiline
+            .iter()
+            .map(|(column, _)| column + 2)
+            .max()
+            .unwrap_or_default();
+
+        let mut previous: Option<usize> = None;
+        for line in shown_lines(annotations) {
+            if previous.is_some_and(|previous| line > previous + 1) {
+                text.push_str("...\n");
+            }
+            previous = Some(line);
+
+            let mut row = Row::default();
+            for &(column, annotation) in multiline.iter() {
+                if annotation.start_line == line && annotation.starts_line {
+                    row.put(column, '/', annotation.style());
+                } else if annotation.start_line < line && line <= annotation.end_line {
+                    row.put(column, '|', annotation.style());
+                }
+            }
+            row.write(margin, lines.get(line).unwrap_or(&""), Style::Plain);
+            let mut rows = vec![(Some(line + 1), row)];
+
+            // spans on this line alone, the label of the rightmost goes beside
+            // the markers and the others hang below on lines of their own
+            let mut singles: Vec<&Annotation> = annotations
+                .iter()
+                .filter(|annotation| !annotation.is_multiline() && annotation.start_line == line)
+                .collect();
+            singles.sort_by_key(|annotation| annotation.start_column);
+            if !singles.is_empty() {
+                let mut row = bars(&multiline, line, 0, 0);
+                // the primary span is marked last so it wins overlaps
+                let secondary = singles.iter().filter(|annotation| !annotation.primary);
+                let primary = singles.iter().filter(|annotation| annotation.primary);
+                for annotation in secondary.chain(primary) {
+                    for column in annotation.start_column..annotation.end_column {
+                        row.put(margin + column, annotation.marker(), annotation.style());
+                    }
+                }
+                let mut labelled: Vec<&Annotation> = singles
+                    .iter()
+                    .filter(|annotation| !annotation.label.is_empty())
+                    .copied()
+                    .collect();
+                let rightmost = singles.last().unwrap();
+                if !rightmost.label.is_empty() {
+                    labelled.pop();
+                    let end = singles.iter().map(|annotation| annotation.end_column).max();
+                    row.write(
+                        margin + end.unwrap_or_default() + 1,
+                        rightmost.label,
+                        rightmost.style(),
+                    );
+                }
+                rows.push((None, row));
+                if !labelled.is_empty() {
+                    let mut row = bars(&multiline, line, 0, 0);
+                    for annotation in labelled.iter() {
+                        row.put(margin + annotation.start_column, '|', annotation.style());
+                    }
+                    rows.push((None, row));
+                }
+                for (index, annotation) in labelled.iter().enumerate().rev() {
+                    let mut row = bars(&multiline, line, 0, 0);
+                    for left in labelled[..index].iter() {
+                        row.put(margin + left.start_column, '|', left.style());
+                    }
+                    row.write(
+                        margin + annotation.start_column,
+                        annotation.label,
+                        annotation.style(),
+                    );
+                    rows.push((None, row));
+                }
+            }
+
+            // multi-line spans starting after other code on the line are
+            // connected to their column, the ones ending here are closed
+            for (index, &(column, annotation)) in multiline.iter().enumerate() {
+                if annotation.start_line == line && !annotation.starts_line {
+                    let mut row = bars(&multiline, line, index, 0);
+                    for column in column + 1..margin + annotation.start_column {
+                        row.put(column, '_', annotation.style());
+                    }
+                    row.put(
+                        margin + annotation.start_column,
+                        annotation.marker(),
+                        annotation.style(),
+                    );
+                    rows.push((None, row));
+                }
+            }
+            for (index, &(column, annotation)) in multiline.iter().enumerate() {
+                if annotation.end_line == line {
+                    let mut row = bars(&multiline, line, multiline.len(), index);
+                    let end = margin + annotation.end_column - 1;
+                    row.put(column, '|', annotation.style());
+                    for column in column + 1..end {
+                        row.put(column, '_', annotation.style());
+                    }
+                    row.put(end, annotation.marker(), annotation.style());
+                    row.write(end + 2, annotation.label, annotation.style());
+                    rows.push((None, row));
+                }
+            }
+
+            for (number, row) in rows {
+                let number = number.map_or(String::new(), |number| number.to_string());
+                text.push_str(&format!(
+                    "{}{:>width$} |{} {}\n",
+                    color::Fg(color::Blue),
+                    number,
+                    color::Fg(color::Reset),
+                    self.paint(&row, message_color)
+                ));
+            }
+        }
+    }
+
+    fn paint(&self, row: &Row, message_color: &dyn color::Color) -> String {
+        let mut text = String::new();
+        let mut current = Style::Plain;
+        for &(c, style) in row.cells.iter() {
+            if style != current {
+                let color = match style {
+                    Style::Plain => color::Fg(color::Reset).to_string(),
+                    Style::Primary => color::Fg(message_color).to_string(),
+                    Style::Secondary => color::Fg(color::Blue).to_string(),
+                };
+                text.push_str(&color);
+                current = style;
+            }
+            text.push(c);
+        }
+        if current != Style::Plain {
+            text.push_str(&color::Fg(color::Reset).to_string());
+        }
+        text
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Plain,
+    Primary,
+    Secondary,
+}
+
+// Characters of a line of the rendered diagnostic, right of the gutter
+#[derive(Default)]
+struct Row {
+    cells: Vec<(char, Style)>,
+}
+
+impl Row {
+    fn put(&mut self, column: usize, c: char, style: Style) {
+        if self.cells.len() <= column {
+            self.cells.resize(column + 1, (' ', Style::Plain));
+        }
+        self.cells[column] = (c, style);
+    }
+
+    fn write(&mut self, column: usize, text: &str, style: Style) {
+        for (index, c) in text.chars().enumerate() {
+            self.put(column + index, c, style);
+        }
+    }
+}
+
+// Span marked below the source, the primary one with `^` and labels with `-`.
+// Lines count from 0, columns count characters and the end column is
+// exclusive.
+struct Annotation<'a> {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    // only whitespace is before the start on its line
+    starts_line: bool,
+    primary: bool,
+    label: &'a str,
+}
+
+impl<'a> Annotation<'a> {
+    fn new(source_text: &SourceText, span: &TextSpan, primary: bool, label: &'a str) -> Self {
+        let input = source_text.input();
+        let start = span.start.min(input.len());
+        // empty spans mark the character at their start
+        let last = input[..span.end.min(input.len())]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+            .max(start);
+        let (start_line, start_column) = source_text.get_position(start);
+        let (end_line, end_column) = source_text.get_position(last);
+        let line_start = input[..start].rfind('\n').map_or(0, |index| index + 1);
+        Self {
+            start_line: start_line - 1,
+            start_column: start_column - 1,
+            end_line: end_line - 1,
+            end_column,
+            starts_line: input[line_start..start].trim().is_empty(),
+            primary,
+            label,
+        }
+    }
+
+    fn is_multiline(&self) -> bool {
+        self.start_line != self.end_line
+    }
+
+    fn marker(&self) -> char {
+        if self.primary {
+            '^'
+        } else {
+            '-'
+        }
+    }
+
+    fn style(&self) -> Style {
+        if self.primary {
+            Style::Primary
+        } else {
+            Style::Secondary
+        }
+    }
+}
+
+// Lines shown for the spans, long multi-line spans only with their first and
+// last two lines. A single line between two shown ones is shown instead of
+// `...`.
+fn shown_lines(annotations: &[Annotation]) -> Vec<usize> {
+    let mut lines: Vec<usize> = Vec::new();
+    for annotation in annotations {
+        if annotation.end_line - annotation.start_line < 6 {
+            lines.extend(annotation.start_line..=annotation.end_line);
+        } else {
+            lines.extend([
+                annotation.start_line,
+                annotation.start_line + 1,
+                annotation.end_line - 1,
+                annotation.end_line,
+            ]);
+        }
+    }
+    lines.sort();
+    lines.dedup();
+    let mut shown: Vec<usize> = Vec::new();
+    for line in lines {
+        if shown.last().is_some_and(|previous| line == previous + 2) {
+            shown.push(line - 1);
+        }
+        shown.push(line);
+    }
+    shown
+}
+
+// Vertical lines of the multi-line spans, by their column, that pass the rows
+// below a line. The spans before `started` have drawn their start on the line,
+// the ones before `ended` their end.
+fn bars(multiline: &[(usize, &Annotation)], line: usize, started: usize, ended: usize) -> Row {
+    let mut row = Row::default();
+    for (index, &(column, annotation)) in multiline.iter().enumerate() {
+        let open = annotation.start_line < line
+            || (annotation.start_line == line && (annotation.starts_line || index < started));
+        let closed = annotation.end_line < line || (annotation.end_line == line && index < ended);
+        if open && !closed {
+            row.put(column, '|', annotation.style());
+        }
+    }
+    row
+}
+
+// Severity as JSON and SARIF name it
+fn severity(diagnostic: &Diagnostic) -> &'static str {
+    match diagnostic.kind {
+        DiagnosticKind::Error => "error",
+        DiagnosticKind::Warning => "warning",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::{cell::RefCell, rc::Rc};
+
+    use serde_json::{json, Value};
+
+    use super::DiagnosticsPrinter;
+    use crate::ast::lexer::TextSpan;
+    use crate::diagnostics::{Diagnostic, DiagnosticCode, DiagnosticsColletion};
+    use crate::source_text::SourceText;
+    use crate::terminal::strip_colors;
+
+    // Diagnostics of `b` and `ö` on the second line of the source of `main`
+    fn diagnostics() -> (HashMap<String, SourceText>, Vec<Diagnostic>) {
+        let input = "let a: i32 = 1;\nlet s: str = \"ö\" + b;";
+        let sources = HashMap::from([(
+            "main".to_string(),
+            SourceText::new("src/main.zn".to_string(), input.to_string()),
+        )]);
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        diagnostics_colletion.borrow_mut().set_module("main");
+        let b = input.rfind('b').unwrap();
+        diagnostics_colletion
+            .borrow_mut()
+            .report_undefined_variable(TextSpan::new(b, b + 1, "b".to_string()), None);
+        let string = input.find('"').unwrap();
+        diagnostics_colletion
+            .borrow_mut()
+            .report_unterminated_string(TextSpan::new(string, b, "\"ö\" + ".to_string()));
+        diagnostics_colletion.borrow_mut().diagnostics[1]
+            .with_label(TextSpan::new(0, 3, "let".to_string()), "in".to_string())
+            .with_note("strings end on their line".to_string());
+        let diagnostics = std::mem::take(&mut diagnostics_colletion.borrow_mut().diagnostics);
+        (sources, diagnostics)
+    }
+
+    #[test]
+    fn should_write_diagnostics_as_json() {
+        let (sources, diagnostics) = diagnostics();
+        let printer = DiagnosticsPrinter::new(&sources, &diagnostics);
+        assert_eq!(
+            printer.json_diagnostic(&diagnostics[0]),
+            json!({
+                "severity": "error",
+                "code": "Z0003",
+                "message": "Not found in this scope",
+                "file": "src/main.zn",
+                "span": { "start": 36, "end": 37 },
+                "start": { "line": 2, "column": 20 },
+                "end": { "line": 2, "column": 21 },
+                "labels": [],
+                "notes": [],
+                "help": [],
+            })
+        );
+        assert_eq!(
+            printer.json_diagnostic(&diagnostics[1])["end"]["column"],
+            20
+        );
+    }
+
+    #[test]
+    fn should_render_labels_and_notes() {
+        let input = "func add(a: i32, b: i32) -> i32 {\n    return a + b;\n}\nlet c: i32 = add(1);\nlet d: i32 = add(1,\n    2,\n    3);";
+        let sources = HashMap::from([(
+            "main".to_string(),
+            SourceText::new("main.zn".to_string(), input.to_string()),
+        )]);
+        let span = |literal: &str, index: usize| {
+            let start = input.match_indices(literal).nth(index).unwrap().0;
+            TextSpan::new(start, start + literal.len(), literal.to_string())
+        };
+        let mut diagnostics_colletion = DiagnosticsColletion::new();
+        diagnostics_colletion.set_module("main");
+        diagnostics_colletion
+            .report_error(
+                DiagnosticCode::NumberOfFunctionArgumentsMismatch,
+                "Function add expects 2 arguments but 1 were given".to_string(),
+                span("add", 1),
+            )
+            .with_label(span("add", 0), "declared here".to_string())
+            .with_label(span("c", 1), "assigned to".to_string())
+            .with_label(span("i32", 3), "of type".to_string())
+            .with_note("the arguments are not defaulted".to_string())
+            .with_help("pass <b>".to_string());
+        let body = span("{\n    return a + b;\n}", 0);
+        let call = span("(1,\n    2,\n    3)", 0);
+        diagnostics_colletion
+            .report_warning(
+                DiagnosticCode::NumberOfFunctionArgumentsMismatch,
+                "Too many".to_string(),
+                call,
+            )
+            .with_label(body, "body".to_string());
+        let diagnostics = diagnostics_colletion.diagnostics;
+        let printer = DiagnosticsPrinter::new(&sources, &diagnostics);
+        let render = |diagnostic| strip_colors(&printer.stringify_diagnostic(diagnostic));
+
+        assert_eq!(
+            render(&diagnostics[0]),
+            [
+                "error[Z0004]: Function add expects 2 arguments but 1 were given",
+                " --> main.zn:4:14",
+                "  |",
+                "1 | func add(a: i32, b: i32) -> i32 {",
+                "  |      --- declared here",
+                "...",
+                "4 | let c: i32 = add(1);",
+                "  |     -  ---   ^^^",
+                "  |     |  |",
+                "  |     |  of type",
+                "  |     assigned to",
+                "  |",
+                "  = note: the arguments are not defaulted",
+                "  = help: pass <b>",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            render(&diagnostics[1]),
+            [
+                "warning[Z0004]: Too many",
+                " --> main.zn:5:17",
+                "  |",
+                "1 |   func add(a: i32, b: i32) -> i32 {",
+                "  |  _________________________________-",
+                "2 | |     return a + b;",
+                "3 | | }",
+                "  | |_- body",
+                "4 |   let c: i32 = add(1);",
+                "5 |   let d: i32 = add(1,",
+                "  |  _________________^",
+                "6 | |     2,",
+                "7 | |     3);",
+                "  | |______^",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn should_write_diagnostics_as_valid_sarif() {
+        let schema: Value = serde_json::from_str(include_str!("sarif-2.1.0.schema.json")).unwrap();
+        let validator = jsonschema::draft7::new(&schema).unwrap();
+
+        let (sources, diagnostics) = diagnostics();
+        let log = DiagnosticsPrinter::new(&sources, &diagnostics).sarif_log();
+        let errors: Vec<String> = validator.iter_errors(&log).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "{:?}", errors);
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][1]["id"], "Z0013");
+        assert_eq!(run["results"][1]["ruleIndex"], 1);
+        let region = &run["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 2);
+        assert_eq!(region["byteOffset"], 36);
+        let related = &run["results"][1]["relatedLocations"][0];
+        assert_eq!(related["message"]["text"], "in");
+        assert_eq!(related["physicalLocation"]["region"]["startLine"], 1);
+        assert_eq!(
+            run["results"][1]["message"]["text"],
+            "Unterminated string literal\nnote: strings end on their line"
+        );
+
+        let empty = DiagnosticsPrinter::new(&sources, &[]).sarif_log();
+        assert!(validator.is_valid(&empty));
+        // the validator rejects what the standard forbids
+        let mut invalid = log.clone();
+        invalid["runs"][0]["results"][0]["level"] = json!("fatal");
+        assert!(!validator.is_valid(&invalid));
     }
 }
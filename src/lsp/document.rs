@@ -0,0 +1,264 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde_json::{json, Value};
+
+use crate::ast::lexer::{Lexer, TextSpan, Token};
+use crate::ast::parser::Parser;
+use crate::ast::printer::Highlight;
+use crate::ast::symbol_index::SymbolIndex;
+use crate::ast::Ast;
+use crate::diagnostics::DiagnosticsColletion;
+
+// Semantic token types of the server, the legend sent on `initialize`
+pub const TOKEN_TYPES: [&str; 7] = [
+    "keyword", "type", "function", "variable", "number", "string", "comment",
+];
+
+// File opened in the editor. Positions of the protocol count lines and UTF-16
+// code units, spans of the lexer count bytes.
+pub struct Document {
+    pub text: String,
+    pub index: SymbolIndex,
+    tokens: Vec<Token>,
+    // byte offsets of the line starts
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        // the diagnostics are published from a check of all modules instead
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        let mut lexer = Lexer::new(text.clone(), Rc::clone(&diagnostics_colletion));
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        let mut parser = Parser::new(tokens.clone(), diagnostics_colletion);
+        let mut ast = Ast::new();
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(index, _)| index + 1));
+        Self {
+            index: SymbolIndex::new(&ast),
+            text,
+            tokens,
+            line_starts,
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> Value {
+        let (line, character) = self.line_and_character(offset);
+        json!({ "line": line, "character": character })
+    }
+
+    pub fn range(&self, span: &TextSpan) -> Value {
+        json!({ "start": self.position(span.start), "end": self.position(span.end) })
+    }
+
+    // Byte offset of a protocol position, characters past the end of the line
+    // stay on the line
+    pub fn offset(&self, position: &Value) -> usize {
+        let line = position["line"].as_u64().unwrap_or(0) as usize;
+        let character = position["character"].as_u64().unwrap_or(0) as usize;
+        let Some(start) = self.line_starts.get(line).copied() else {
+            return self.text.len();
+        };
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        let mut units = 0;
+        for (index, c) in self.text[start..end].char_indices() {
+            if units >= character {
+                return start + index;
+            }
+            units += c.len_utf16();
+        }
+        end
+    }
+
+    // Semantic tokens as the protocol encodes them, five numbers per token with
+    // its line and start relative to the token before it. Identifiers are
+    // classified by the declaration they refer to, like the highlight printer
+    // does, unresolved ones are left out.
+    pub fn semantic_tokens(&self) -> Vec<u32> {
+        let mut data = Vec::new();
+        let (mut previous_line, mut previous_character) = (0, 0);
+        for token in self.tokens.iter() {
+            let highlight = match Highlight::of_token(&token.kind) {
+                Some(highlight) => Some(highlight),
+                None => self
+                    .index
+                    .symbol_at(token.span.start)
+                    .filter(|(span, _)| span.start == token.span.start)
+                    .map(|(_, id)| self.index.symbol(id).highlight()),
+            };
+            let Some(token_type) = highlight.and_then(token_type) else {
+                continue;
+            };
+            // a token may not span lines, multi-line comments are split
+            let mut start = token.span.start;
+            for part in self.text[token.span.start..token.span.end].split('\n') {
+                let length = part.trim_end_matches('\r').encode_utf16().count();
+                if length > 0 {
+                    let (line, character) = self.line_and_character(start);
+                    if line != previous_line {
+                        previous_character = 0;
+                    }
+                    data.extend([
+                        (line - previous_line) as u32,
+                        (character - previous_character) as u32,
+                        length as u32,
+                        token_type,
+                        0,
+                    ]);
+                    (previous_line, previous_character) = (line, character);
+                }
+                start += part.len() + 1;
+            }
+        }
+        data
+    }
+
+    fn line_and_character(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+        (line, character)
+    }
+}
+
+// Index of the highlight in the legend, punctuation is not a semantic token
+fn token_type(highlight: Highlight) -> Option<u32> {
+    let token_type = match highlight {
+        Highlight::Keyword | Highlight::Constant => 0,
+        Highlight::Type => 1,
+        Highlight::Function => 2,
+        Highlight::Variable => 3,
+        Highlight::Number => 4,
+        Highlight::String => 5,
+        Highlight::Comment => 6,
+        Highlight::Text => return None,
+    };
+    Some(token_type)
+}
+
+// Path of a `file://` URI, other URIs are kept as they are
+pub fn uri_to_path(uri: &str) -> String {
+    let Some(path) = uri.strip_prefix("file://") else {
+        return uri.to_string();
+    };
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) if bytes[index] == b'%' => {
+                decoded.push(byte);
+                index += 3;
+            }
+            _ => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{uri_to_path, Document};
+
+    #[test]
+    fn should_convert_positions_in_utf16() {
+        let document = Document::new("let s: str = \"é😀\";\nlet a: i32 = 1;".to_string());
+        let semicolon = document.text.find(';').unwrap();
+        assert_eq!(
+            document.position(semicolon),
+            json!({ "line": 0, "character": 18 })
+        );
+        assert_eq!(
+            document.offset(&json!({ "line": 0, "character": 18 })),
+            semicolon
+        );
+        assert_eq!(
+            document.offset(&json!({ "line": 0, "character": 99 })),
+            semicolon + 1
+        );
+        assert_eq!(
+            document.offset(&json!({ "line": 1, "character": 4 })),
+            document.text.rfind('a').unwrap()
+        );
+        assert_eq!(
+            uri_to_path("file:///home/me/my%20project/main.zn"),
+            "/home/me/my project/main.zn"
+        );
+    }
+
+    // Declaration of the identifier at the protocol position
+    fn declaration_at(document: &Document, line: u64, character: u64) -> Option<String> {
+        let offset = document.offset(&json!({ "line": line, "character": character }));
+        let (_, id) = document.index.symbol_at(offset)?;
+        Some(document.index.symbol(id).detail.clone())
+    }
+
+    #[test]
+    fn should_find_symbols_after_an_edit_inserts_multi_byte_text() {
+        let before = Document::new("let s: str = \"\";\nlet t: str = s;".to_string());
+        assert_eq!(
+            declaration_at(&before, 1, 13),
+            Some("let s: str".to_string())
+        );
+
+        // the editor inserts `é😀` into the string and sends the whole text
+        let after = Document::new("let s: str = \"é😀\"; let t: str = s;".to_string());
+        assert_eq!(
+            declaration_at(&after, 0, 33),
+            Some("let s: str".to_string())
+        );
+        assert_eq!(
+            declaration_at(&after, 0, 24),
+            Some("let t: str".to_string())
+        );
+        let reference = after.text.rfind('s').unwrap();
+        assert_eq!(
+            after.position(reference),
+            json!({ "line": 0, "character": 33 })
+        );
+        // a position inside of the surrogate pair of `😀` moves to the next character
+        assert_eq!(
+            after.offset(&json!({ "line": 0, "character": 16 })),
+            after.text.find('"').unwrap() + 1 + 'é'.len_utf8() + '😀'.len_utf8()
+        );
+    }
+
+    #[test]
+    fn should_count_semantic_tokens_in_utf16() {
+        let document = Document::new("/* ü\n😀 */ let u: str = \"ö\";".to_string());
+        let data = document.semantic_tokens();
+        let tokens: Vec<&[u32]> = data.chunks(5).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                // the comment is split at the line break
+                &[0, 0, 4, 6, 0][..],
+                &[1, 0, 5, 6, 0],
+                &[0, 6, 3, 0, 0],
+                &[0, 4, 1, 3, 0],
+                &[0, 3, 3, 1, 0],
+                &[0, 6, 3, 5, 0],
+            ]
+        );
+    }
+}
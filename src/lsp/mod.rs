@@ -0,0 +1,585 @@
+mod document;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use serde_json::{json, Value};
+
+use crate::ast::symbol_index::{Symbol, SymbolKind};
+use crate::cli::module_name;
+use crate::compilation_unit::CompilationUnit;
+use crate::diagnostics::DiagnosticKind;
+use crate::source_text::SourceText;
+use document::{uri_to_path, Document, TOKEN_TYPES};
+
+// Error codes of JSON-RPC
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// Symbol kinds of the protocol
+const FUNCTION_SYMBOL: u32 = 12;
+const STRUCT_SYMBOL: u32 = 23;
+const METHOD_SYMBOL: u32 = 6;
+
+// Serves the language server protocol on stdin and stdout
+pub fn main() -> ExitCode {
+    let mut server = Server::new(io::stdout().lock());
+    server.run(&mut io::stdin().lock())
+}
+
+pub struct Server<W: Write> {
+    writer: W,
+    // open documents by their URI, the editor sends their whole text on a change
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+impl<W: Write> Server<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            documents: HashMap::new(),
+            shutdown: false,
+        }
+    }
+
+    // Answers the messages until `exit`, which succeeds after a `shutdown`
+    pub fn run(&mut self, reader: &mut impl BufRead) -> ExitCode {
+        loop {
+            let body = match read_message(reader) {
+                Ok(Some(body)) => body,
+                Ok(None) => return ExitCode::FAILURE,
+                // a malformed message is answered, the next one can be read again
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    let error = json!({ "code": PARSE_ERROR, "message": error.to_string() });
+                    self.send(json!({ "jsonrpc": "2.0", "id": null, "error": error }));
+                    continue;
+                }
+                Err(error) => {
+                    eprintln!("error: could not read a message: {}", error);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let message: Value = match serde_json::from_str(&body) {
+                Ok(message) => message,
+                Err(error) => {
+                    let error = json!({ "code": PARSE_ERROR, "message": error.to_string() });
+                    self.send(json!({ "jsonrpc": "2.0", "id": null, "error": error }));
+                    continue;
+                }
+            };
+            if message["method"] == "exit" {
+                return match self.shutdown {
+                    true => ExitCode::SUCCESS,
+                    false => ExitCode::FAILURE,
+                };
+            }
+            self.handle(&message);
+        }
+    }
+
+    fn handle(&mut self, message: &Value) {
+        // the server sends no requests, so there are no responses to handle
+        let Some(method) = message["method"].as_str() else {
+            return;
+        };
+        let params = &message["params"];
+        let Some(id) = message.get("id") else {
+            self.notify(method, params);
+            return;
+        };
+        let result = match self.shutdown {
+            true => Err((INVALID_REQUEST, "the server is shut down".to_string())),
+            false => self.request(method, params),
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        self.send(response);
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    // full text on every change
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "documentSymbolProvider": true,
+                    "semanticTokensProvider": {
+                        "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                        "full": true,
+                    },
+                },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            }),
+            "shutdown" => {
+                self.shutdown = true;
+                Value::Null
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/references" => self.references(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            "textDocument/semanticTokens/full" => match self.document(params) {
+                Some(document) => json!({ "data": document.semantic_tokens() }),
+                None => Value::Null,
+            },
+            _ => return Err((METHOD_NOT_FOUND, format!("unknown method `{}`", method))),
+        };
+        Ok(result)
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents
+                    .insert(uri.to_string(), Document::new(text.to_string()));
+            }
+            "textDocument/didChange" => {
+                let Some(change) = params["contentChanges"].as_array().and_then(|c| c.last())
+                else {
+                    return;
+                };
+                let text = change["text"].as_str().unwrap_or_default();
+                self.documents
+                    .insert(uri.to_string(), Document::new(text.to_string()));
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+            }
+            _ => return,
+        }
+        self.publish_diagnostics(uri);
+    }
+
+    fn document(&self, params: &Value) -> Option<&Document> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        self.documents.get(uri)
+    }
+
+    // Symbol at the position of a request with the span naming it there
+    fn located(&self, params: &Value) -> Option<(&Document, &Symbol, Value)> {
+        let document = self.document(params)?;
+        let (span, id) = document
+            .index
+            .symbol_at(document.offset(&params["position"]))?;
+        Some((document, document.index.symbol(id), document.range(span)))
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((_, symbol, range)) = self.located(params) else {
+            return Value::Null;
+        };
+        json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!("```zeno\n{}\n```", symbol.detail),
+            },
+            "range": range,
+        })
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let Some((document, symbol, _)) = self.located(params) else {
+            return Value::Null;
+        };
+        json!({
+            "uri": params["textDocument"]["uri"],
+            "range": document.range(&symbol.span),
+        })
+    }
+
+    fn references(&self, params: &Value) -> Value {
+        let Some(document) = self.document(params) else {
+            return Value::Null;
+        };
+        let Some((_, id)) = document
+            .index
+            .symbol_at(document.offset(&params["position"]))
+        else {
+            return Value::Null;
+        };
+        let mut spans = Vec::new();
+        if params["context"]["includeDeclaration"] == true {
+            spans.push(&document.index.symbol(id).span);
+        }
+        spans.extend(document.index.references(id));
+        let uri = &params["textDocument"]["uri"];
+        spans
+            .into_iter()
+            .map(|span| json!({ "uri": uri, "range": document.range(span) }))
+            .collect()
+    }
+
+    // Functions and structs with their methods
+    fn document_symbols(&self, params: &Value) -> Value {
+        let Some(document) = self.document(params) else {
+            return Value::Null;
+        };
+        let symbols = document.index.symbols();
+        let outline = |id: usize, kind: u32, children: Vec<Value>| {
+            let symbol = &symbols[id];
+            json!({
+                "name": symbol.name,
+                "detail": symbol.detail,
+                "kind": kind,
+                "range": document.range(&symbol.range),
+                "selectionRange": document.range(&symbol.span),
+                "children": children,
+            })
+        };
+        symbols
+            .iter()
+            .enumerate()
+            .filter_map(|(id, symbol)| match symbol.kind {
+                SymbolKind::Function => Some(outline(id, FUNCTION_SYMBOL, Vec::new())),
+                SymbolKind::Struct => {
+                    let methods = symbols
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, method)| {
+                            method.kind == SymbolKind::Method && method.parent == Some(id)
+                        })
+                        .map(|(method, _)| outline(method, METHOD_SYMBOL, Vec::new()))
+                        .collect();
+                    Some(outline(id, STRUCT_SYMBOL, methods))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Publishes the parser and symbol checker diagnostics of the document, a
+    // closed document has none
+    fn publish_diagnostics(&mut self, uri: &str) {
+        let diagnostics = match self.documents.get(uri) {
+            Some(document) => self.diagnostics(uri, document),
+            None => Vec::new(),
+        };
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }));
+    }
+
+    fn diagnostics(&self, uri: &str, document: &Document) -> Vec<Value> {
+        let path = uri_to_path(uri);
+        let module = module_name(&path);
+        let directory = Path::new(&path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        // imports are looked up next to the file, open documents before the disk
+        let load = |name: &str| {
+            let module_path = directory.join(format!("{}.zn", name));
+            let module_path_str = module_path.display().to_string();
+            let open = self
+                .documents
+                .iter()
+                .find(|(uri, _)| uri_to_path(uri) == module_path_str);
+            if let Some((_, document)) = open {
+                return Ok(SourceText::new(module_path_str, document.text.clone()));
+            }
+            std::fs::read_to_string(&module_path)
+                .map(|input| SourceText::new(module_path_str.clone(), input))
+                .map_err(|_| module_path_str)
+        };
+        let entry = SourceText::new(path.clone(), document.text.clone());
+        CompilationUnit::check_symbols(&module, entry, &load)
+            .iter()
+            .filter(|diagnostic| diagnostic.module == module)
+            .map(|diagnostic| {
+                let severity = match diagnostic.kind {
+                    DiagnosticKind::Error => 1,
+                    DiagnosticKind::Warning => 2,
+                };
+                // only labels in the document have a range the editor knows
+                let related: Vec<Value> = diagnostic
+                    .labels
+                    .iter()
+                    .filter(|label| label.module.is_none())
+                    .map(|label| {
+                        json!({
+                            "location": { "uri": uri, "range": document.range(&label.span) },
+                            "message": label.message,
+                        })
+                    })
+                    .collect();
+                json!({
+                    "range": document.range(&diagnostic.span),
+                    "severity": severity,
+                    "code": diagnostic.code.to_string(),
+                    "source": "zeno",
+                    "message": diagnostic.message_with_notes(),
+                    "relatedInformation": related,
+                })
+            })
+            .collect()
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        // the editor is gone if the output is closed, `exit` never comes then
+        let _ = write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = self.writer.flush();
+    }
+}
+
+// Body of the next message, `None` at the end of the input
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(content_length) = content_length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing Content-Length header",
+        ));
+    };
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+    use std::process::ExitCode;
+
+    use serde_json::{json, Value};
+
+    use super::{read_message, Server};
+
+    const URI: &str = "file:///project/main.zn";
+
+    const SOURCE: &str = "\
+func twice(a: i32) -> i32 {
+  return a * 2;
+}
+struct P {
+  x: i32,
+  func get(self) -> i32 {
+    return self.x;
+  }
+}
+let b: i32 = twice(1);
+let c: i32 = twice(b);
+";
+
+    // Scripted client, sends the messages and returns the exit code with the
+    // messages of the server
+    fn exchange(messages: &[Value]) -> (ExitCode, Vec<Value>) {
+        exchange_after("", messages)
+    }
+
+    // Like `exchange`, with raw input sent before the messages
+    fn exchange_after(raw: &str, messages: &[Value]) -> (ExitCode, Vec<Value>) {
+        let mut input = raw.as_bytes().to_vec();
+        for message in messages {
+            let body = message.to_string();
+            input.extend(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).bytes());
+        }
+        let mut server = Server::new(Vec::new());
+        let exit_code = server.run(&mut Cursor::new(input));
+        let mut output = Cursor::new(server.writer);
+        let mut replies = Vec::new();
+        while let Some(body) = read_message(&mut output).unwrap() {
+            replies.push(serde_json::from_str(&body).unwrap());
+        }
+        (exit_code, replies)
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn open(text: &str) -> Value {
+        notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "languageId": "zeno", "version": 1, "text": text } }),
+        )
+    }
+
+    fn at(line: u64, character: u64) -> Value {
+        json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } })
+    }
+
+    // Result of the request with the id
+    fn result(replies: &[Value], id: u64) -> &Value {
+        &replies.iter().find(|reply| reply["id"] == id).unwrap()["result"]
+    }
+
+    fn range(line: u64, start: u64, end: u64) -> Value {
+        json!({
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        })
+    }
+
+    #[test]
+    fn should_publish_diagnostics_on_open_and_change() {
+        let (exit_code, replies) = exchange(&[
+            request(1, "initialize", json!({})),
+            notification("initialized", json!({})),
+            open("let a: i32 = 1;\nlet b: i32 = c;\n"),
+            notification(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": URI, "version": 2 },
+                    "contentChanges": [{ "text": "let a: i32 = 1;\n" }],
+                }),
+            ),
+            request(2, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ]);
+        assert_eq!(exit_code, ExitCode::SUCCESS);
+        let capabilities = &result(&replies, 1)["capabilities"];
+        assert_eq!(capabilities["textDocumentSync"], 1);
+        assert_eq!(
+            capabilities["semanticTokensProvider"]["legend"]["tokenTypes"][2],
+            "function"
+        );
+
+        let published: Vec<&Value> = replies
+            .iter()
+            .filter(|reply| reply["method"] == "textDocument/publishDiagnostics")
+            .map(|reply| &reply["params"]["diagnostics"])
+            .collect();
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[0].as_array().unwrap().len(), 1);
+        assert_eq!(published[0][0]["range"], range(1, 13, 14));
+        assert_eq!(published[0][0]["severity"], 1);
+        assert_eq!(published[1], &json!([]));
+        assert_eq!(result(&replies, 2), &Value::Null);
+    }
+
+    #[test]
+    fn should_answer_hover_definition_and_references() {
+        let references = |id: u64, line: u64, character: u64, declaration: bool| {
+            let mut params = at(line, character);
+            params["context"] = json!({ "includeDeclaration": declaration });
+            request(id, "textDocument/references", params)
+        };
+        let (_, replies) = exchange(&[
+            open(SOURCE),
+            request(1, "textDocument/hover", at(10, 14)),
+            request(2, "textDocument/definition", at(10, 19)),
+            references(3, 0, 6, false),
+            references(4, 0, 6, true),
+            request(5, "textDocument/hover", at(1, 11)),
+            request(6, "textDocument/unknown", at(0, 0)),
+        ]);
+        assert_eq!(
+            result(&replies, 1)["contents"]["value"],
+            "```zeno\nfunc twice(a: i32) -> i32\n```"
+        );
+        assert_eq!(result(&replies, 1)["range"], range(10, 13, 18));
+        assert_eq!(result(&replies, 2)["uri"], URI);
+        assert_eq!(result(&replies, 2)["range"], range(9, 4, 5));
+        let lines = |id: u64| -> Vec<Value> {
+            result(&replies, id)
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|location| location["range"]["start"]["line"].clone())
+                .collect()
+        };
+        assert_eq!(lines(3), vec![json!(9), json!(10)]);
+        assert_eq!(lines(4), vec![json!(0), json!(9), json!(10)]);
+        assert_eq!(result(&replies, 5), &Value::Null);
+        let error = &replies.iter().find(|reply| reply["id"] == 6).unwrap()["error"];
+        assert_eq!(error["code"], -32601);
+    }
+
+    #[test]
+    fn should_answer_malformed_messages_and_keep_serving() {
+        let (exit_code, replies) = exchange_after(
+            "Content-Type: application/vscode-jsonrpc\r\n\r\n",
+            &[
+                request(1, "shutdown", Value::Null),
+                notification("exit", Value::Null),
+            ],
+        );
+        assert_eq!(exit_code, ExitCode::SUCCESS);
+        assert_eq!(replies[0]["id"], Value::Null);
+        assert_eq!(replies[0]["error"]["code"], -32700);
+        assert_eq!(result(&replies, 1), &Value::Null);
+    }
+
+    #[test]
+    fn should_outline_and_classify_the_document() {
+        let (exit_code, replies) = exchange(&[
+            open(SOURCE),
+            request(1, "textDocument/documentSymbol", at(0, 0)),
+            request(2, "textDocument/semanticTokens/full", at(0, 0)),
+        ]);
+        // the input ended without `exit`
+        assert_eq!(exit_code, ExitCode::FAILURE);
+
+        let symbols = result(&replies, 1).as_array().unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0]["name"], "twice");
+        assert_eq!(symbols[0]["kind"], 12);
+        assert_eq!(symbols[0]["range"]["end"]["line"], 2);
+        assert_eq!(symbols[1]["name"], "P");
+        assert_eq!(symbols[1]["children"][0]["name"], "get");
+        assert_eq!(symbols[1]["children"][0]["selectionRange"], range(5, 7, 10));
+
+        // `func twice(a: i32) -> i32 {` and `return a * 2;`
+        let data = result(&replies, 2)["data"].as_array().unwrap();
+        let expected = [
+            [0, 0, 4, 0],
+            [0, 5, 5, 2],
+            [0, 6, 1, 3],
+            [0, 3, 3, 1],
+            [0, 8, 3, 1],
+            [1, 2, 6, 0],
+            [0, 7, 1, 3],
+            [0, 4, 1, 4],
+        ];
+        for (token, expected) in data.chunks(5).zip(expected) {
+            assert_eq!(token[..4], expected.map(|number| json!(number)));
+        }
+    }
+}
@@ -1,29 +1,25 @@
 mod ast;
+mod cli;
 mod compilation_unit;
 mod diagnostics;
+mod lsp;
+mod repl;
 mod source_text;
+mod terminal;
 
-use std::env;
-use std::fs;
+use std::process::ExitCode;
+use std::{env, thread};
 
-use crate::compilation_unit::CompilationUnit;
+// The interpreter recurses on the native stack for every call, so it runs on a
+// thread whose stack holds `ASTSolver::DEFAULT_MAX_CALL_DEPTH` calls
+const STACK_SIZE: usize = 512 * 1024 * 1024;
 
-fn main() -> Result<(), ()> {
-    // func a() { return 10; }
-    let input = "\
-func a(arg1, arg2) {
-  let c = arg2 / 071
-  return arg1 * c
-}
-
-return a(0x02, 7.67)
-";
-    let args: Vec<String> = env::args().collect();
-
-    let content = fs::read_to_string(args.get(1).unwrap()).unwrap();
-    // let content = fs::read_to_string("examples/demo.zn").unwrap();
-
-    let compilation_unit = CompilationUnit::compile(content.as_str())?;
-    compilation_unit.run();
-    Ok(())
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || cli::main(&args))
+        .expect("failed to spawn the main thread")
+        .join()
+        .unwrap_or(ExitCode::FAILURE)
 }
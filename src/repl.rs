@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::{cell::RefCell, rc::Rc};
+
+use crate::ast::lexer::{Lexer, TokenKind};
+use crate::ast::parser::Parser;
+use crate::ast::solver::ASTSolver;
+use crate::ast::symbol_checker::SymbolChecker;
+use crate::ast::type_checker::TypeChecker;
+use crate::ast::value::Value;
+use crate::ast::Ast;
+use crate::cli::stringify_tokens;
+use crate::diagnostics::printer::DiagnosticsPrinter;
+use crate::diagnostics::{DiagnosticsColletion, DiagnosticsColletionCell};
+use crate::source_text::SourceText;
+use crate::terminal::strip_colors;
+
+const HELP: &str = "\
+Inputs are evaluated as top-level statements, the value of a bare expression
+is printed. Input continues on the next line while braces are unbalanced.
+
+Commands:
+  :ast [input]      Print the syntax tree of the input, or of the last one
+  :tokens [input]   Print the tokens of the input, or of the last one
+  :reset            Forget all declared variables, functions and structs
+  :help             Print this help
+  :quit             Leave the REPL, like the end of the input";
+
+// Every input is a snippet of the same module
+const MODULE: &str = "repl";
+
+pub struct Repl {
+    // state of the inputs that ran so far, an input with compile errors leaves
+    // the checkers untouched and one that stops early only keeps the
+    // declarations of the statements that ran
+    symbol_checker: SymbolChecker,
+    type_checker: TypeChecker,
+    solver: ASTSolver,
+    diagnostics_colletion: DiagnosticsColletionCell,
+    // inputs that ran so far by their module, diagnostics can point at their
+    // declarations
+    inputs: HashMap<String, SourceText>,
+    last_input: String,
+    colored: bool,
+    max_loop_iterations: usize,
+    max_call_depth: usize,
+}
+
+impl Repl {
+    pub fn new(colored: bool) -> Self {
+        let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+        diagnostics_colletion.borrow_mut().set_module(MODULE);
+        Self {
+            symbol_checker: SymbolChecker::new(Rc::clone(&diagnostics_colletion)),
+            type_checker: TypeChecker::new(Rc::clone(&diagnostics_colletion)),
+            solver: ASTSolver::new(Rc::clone(&diagnostics_colletion)),
+            diagnostics_colletion,
+            inputs: HashMap::new(),
+            last_input: String::new(),
+            colored,
+            max_loop_iterations: ASTSolver::DEFAULT_MAX_LOOP_ITERATIONS,
+            max_call_depth: ASTSolver::DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+
+    pub fn set_max_loop_iterations(&mut self, max_loop_iterations: usize) {
+        self.max_loop_iterations = max_loop_iterations;
+        self.solver.set_max_loop_iterations(max_loop_iterations);
+    }
+
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+        self.solver.set_max_call_depth(max_call_depth);
+    }
+
+    // Evaluates the inputs read from stdin until its end or `:quit`
+    pub fn run(&mut self) -> ExitCode {
+        let mut lines = io::stdin().lock().lines();
+        loop {
+            let mut input = String::new();
+            let mut prompt = "> ";
+            loop {
+                print!("{}", prompt);
+                let _ = io::stdout().flush();
+                match lines.next() {
+                    Some(Ok(line)) => input.push_str(&line),
+                    _ => {
+                        println!();
+                        return ExitCode::SUCCESS;
+                    }
+                }
+                input.push('\n');
+                if is_complete(&input) {
+                    break;
+                }
+                prompt = "... ";
+            }
+            if !self.execute(input.trim()) {
+                return ExitCode::SUCCESS;
+            }
+        }
+    }
+
+    // Runs a meta-command or evaluates the input, tells whether to go on
+    fn execute(&mut self, input: &str) -> bool {
+        let (command, argument) = match input.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (input, ""),
+        };
+        // without an argument, `:ast` and `:tokens` show the last input
+        let argument = match argument {
+            "" => self.last_input.clone(),
+            _ => terminated(argument),
+        };
+        match command {
+            "" => {}
+            ":quit" => return false,
+            ":help" => println!("{}", HELP),
+            ":reset" => self.reset(),
+            ":ast" => {
+                let source_text = self.source_text(&argument);
+                if let Ok(ast) = self.parse(&source_text) {
+                    self.print(&ast.visualize());
+                }
+            }
+            ":tokens" => {
+                let source_text = self.source_text(&argument);
+                let tokens = stringify_tokens(&source_text, &self.diagnostics_colletion);
+                if self.report(&source_text).is_ok() {
+                    print!("{}", tokens);
+                }
+            }
+            _ if command.starts_with(':') => {
+                eprintln!("error: unknown command `{}`, `:help` lists them", command)
+            }
+            _ => {
+                if let Ok(Some(value)) = self.evaluate(input) {
+                    let value = value.cast(&value.get_type().concrete());
+                    println!("{} ({})", value, value.get_type());
+                }
+            }
+        }
+        true
+    }
+
+    fn reset(&mut self) {
+        let colored = self.colored;
+        let max_loop_iterations = self.max_loop_iterations;
+        let max_call_depth = self.max_call_depth;
+        *self = Self::new(colored);
+        self.set_max_loop_iterations(max_loop_iterations);
+        self.set_max_call_depth(max_call_depth);
+    }
+
+    // Value of the input if it ends with a bare expression
+    fn evaluate(&mut self, input: &str) -> Result<Option<Value>, ()> {
+        self.last_input = terminated(input);
+        // every input is a module of its own for the diagnostics
+        let number = self.inputs.len() + 1;
+        let module = format!("{}:{}", MODULE, number);
+        let source_text = SourceText::new(format!("<input {}>", number), self.last_input.clone());
+        self.diagnostics_colletion.borrow_mut().set_module(&module);
+        let ast = self.parse(&source_text)?;
+
+        let mut symbol_checker = self.symbol_checker.clone();
+        symbol_checker.set_module(&module);
+        ast.visit(&mut symbol_checker);
+        self.report(&source_text)?;
+        let mut type_checker = self.type_checker.clone();
+        ast.visit(&mut type_checker);
+        self.report(&source_text)?;
+        self.inputs.insert(module.clone(), source_text.clone());
+
+        match self.solver.run_snippet(MODULE, &ast) {
+            None => {
+                self.symbol_checker = symbol_checker;
+                self.type_checker = type_checker;
+            }
+            // declarations before a runtime error or a `return` are kept
+            Some(finished) => {
+                let ran = ast.prefix(finished);
+                self.symbol_checker.set_module(&module);
+                ran.visit(&mut self.symbol_checker);
+                ran.visit(&mut self.type_checker);
+            }
+        }
+        self.report(&source_text)?;
+        let value = self.solver.result().clone();
+        Ok((ast.ends_with_expression() && value != Value::Void).then_some(value))
+    }
+
+    fn source_text(&self, input: &str) -> SourceText {
+        SourceText::new("<repl>".to_string(), input.to_string())
+    }
+
+    fn parse(&self, source_text: &SourceText) -> Result<Ast, ()> {
+        let mut parser = Parser::from_input(
+            source_text.input().to_string(),
+            Rc::clone(&self.diagnostics_colletion),
+        );
+        let mut ast = Ast::new();
+        while let Some(statement) = parser.next_statement() {
+            ast.add_statement(statement);
+        }
+        self.report(source_text)?;
+        Ok(ast)
+    }
+
+    // Prints the diagnostics of the current input and forgets them
+    fn report(&self, source_text: &SourceText) -> Result<(), ()> {
+        let mut diagnostics_colletion = self.diagnostics_colletion.borrow_mut();
+        if diagnostics_colletion.diagnostics.is_empty() {
+            return Ok(());
+        }
+        let mut sources = self.inputs.clone();
+        sources.insert(
+            diagnostics_colletion.module().to_string(),
+            source_text.clone(),
+        );
+        let mut printer = DiagnosticsPrinter::new(&sources, &diagnostics_colletion.diagnostics);
+        printer.set_colored(self.colored);
+        printer.print();
+        diagnostics_colletion.clear();
+        Err(())
+    }
+
+    fn print(&self, text: &str) {
+        if self.colored {
+            print!("{}", text);
+        } else {
+            print!("{}", strip_colors(text));
+        }
+    }
+}
+
+// An input is complete once all of its braces are closed
+fn is_complete(input: &str) -> bool {
+    let diagnostics_colletion = Rc::new(RefCell::new(DiagnosticsColletion::new()));
+    let mut lexer = Lexer::new(input.to_string(), diagnostics_colletion);
+    let mut depth = 0;
+    while let Some(token) = lexer.next_token() {
+        match token.kind {
+            TokenKind::LeftBrace => depth += 1,
+            TokenKind::RightBrace => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+// The semicolon after the last statement may be left out
+fn terminated(input: &str) -> String {
+    let input = input.trim_end();
+    if input.is_empty() || input.ends_with(';') || input.ends_with('}') {
+        return input.to_string();
+    }
+    format!("{};", input)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::value::Value;
+
+    use super::{is_complete, terminated, Repl};
+
+    #[test]
+    fn should_keep_declarations_between_inputs() {
+        let mut repl = Repl::new(false);
+        assert_eq!(repl.evaluate("var a: i32 = 2"), Ok(None));
+        assert_eq!(
+            repl.evaluate("func double(x: i32) -> i32 {\n  return x * 2;\n}"),
+            Ok(None)
+        );
+        assert_eq!(repl.evaluate("a += 1;"), Ok(Some(Value::I32(3))));
+        assert_eq!(repl.evaluate("double(a) + 1"), Ok(Some(Value::I32(7))));
+
+        repl.reset();
+        assert_eq!(repl.evaluate("a"), Err(()));
+    }
+
+    #[test]
+    fn should_forget_inputs_with_errors() {
+        let mut repl = Repl::new(false);
+        assert_eq!(
+            repl.evaluate("let b: i32 = 1; let c: i32 = missing;"),
+            Err(())
+        );
+        assert_eq!(repl.evaluate("b"), Err(()));
+        assert!(repl.diagnostics_colletion.borrow().diagnostics.is_empty());
+        assert_eq!(
+            repl.evaluate("let b: bool = true; b"),
+            Ok(Some(Value::Bool(true)))
+        );
+    }
+
+    #[test]
+    fn should_only_keep_the_declarations_that_ran() {
+        let mut repl = Repl::new(false);
+        assert_eq!(
+            repl.evaluate("let z: i32 = 1 / 0; let w: i32 = 2;"),
+            Err(())
+        );
+        assert!(repl.diagnostics_colletion.borrow().diagnostics.is_empty());
+        assert_eq!(repl.evaluate("w + 1"), Err(()));
+        assert_eq!(repl.evaluate("z"), Err(()));
+
+        assert_eq!(
+            repl.evaluate("let a: i32 = 1; let b: str = \"b\"; let c: i32 = a / 0;"),
+            Err(())
+        );
+        assert_eq!(repl.evaluate("a + 1"), Ok(Some(Value::I32(2))));
+        assert_eq!(repl.evaluate("b"), Ok(Some(Value::Str("b".to_string()))));
+        assert_eq!(repl.evaluate("c"), Err(()));
+
+        assert_eq!(repl.evaluate("return 1; let d: i32 = 2;"), Ok(None));
+        assert_eq!(repl.evaluate("d + 1"), Err(()));
+    }
+
+    #[test]
+    fn should_shadow_declarations_of_previous_inputs() {
+        let mut repl = Repl::new(false);
+        assert_eq!(repl.evaluate("let a: i32 = 1;"), Ok(None));
+        assert_eq!(
+            repl.evaluate("let a: str = \"a\"; a"),
+            Ok(Some(Value::Str("a".to_string())))
+        );
+        assert_eq!(repl.evaluate("let a: i32 = 2; let a: i32 = 3;"), Err(()));
+        assert_eq!(
+            repl.evaluate("a + \"!\""),
+            Ok(Some(Value::Str("a!".to_string())))
+        );
+        assert_eq!(repl.evaluate("let a: i32 = 2 / 0; a + \"?\""), Err(()));
+        assert_eq!(
+            repl.evaluate("var a: i32 = 4; a += 1;"),
+            Ok(Some(Value::I32(5)))
+        );
+    }
+
+    #[test]
+    fn should_wait_for_balanced_braces() {
+        assert!(!is_complete("func f() -> i32 {\n  if true {\n"));
+        assert!(is_complete("func f() -> i32 {\n  return 1;\n}\n"));
+        assert!(is_complete("let s: str = \"{\";"));
+        assert_eq!(terminated("1 + 2 "), "1 + 2;");
+        assert_eq!(terminated("while false {}"), "while false {}");
+    }
+}
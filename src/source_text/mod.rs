@@ -1,46 +1,40 @@
+#[derive(Clone)]
 pub struct SourceText {
+    // file the text was read from, shown in diagnostics
+    path: String,
     input: String,
 }
 
 impl SourceText {
-    pub fn new(input: String) -> Self {
-        Self { input }
+    pub fn new(path: String, input: String) -> Self {
+        Self { path, input }
     }
 
-    pub fn from_file(filename: &str) -> Self {
-        match std::fs::read_to_string(filename) {
-            Ok(input) => Self { input },
-            _ => Self {
-                input: "".to_string(),
-            },
-        }
+    pub fn path(&self) -> &str {
+        &self.path
     }
 
-    pub fn get_location(&self, index: usize) -> (String, usize) {
-        let line_number = self.get_linenumber(index);
-        (self.get_line(line_number - 1), self.get_column(index))
+    pub fn input(&self) -> &str {
+        &self.input
     }
 
     pub fn get_column(&self, index: usize) -> usize {
-        if self.input[0..index].lines().count() > 0 {
-            self.input[0..index].lines().last().unwrap().len()
-        } else {
-            index
+        match self.input[0..index].rfind('\n') {
+            Some(line_break) => index - line_break - 1,
+            None => index,
         }
     }
 
-    pub fn get_linenumber(&self, index: usize) -> usize {
-        if index == 0 {
-            return 1;
-        }
-        self.input[0..index].lines().count()
+    // 1-based line and column of the index, the column counts characters
+    pub fn get_position(&self, index: usize) -> (usize, usize) {
+        let line_start = self.input[0..index]
+            .rfind('\n')
+            .map_or(0, |line_break| line_break + 1);
+        let column = self.input[line_start..index].chars().count() + 1;
+        (self.get_linenumber(index), column)
     }
 
-    pub fn get_line(&self, row: usize) -> String {
-        if self.input.lines().count() > 0 {
-            self.input.lines().nth(row).unwrap().to_string()
-        } else {
-            self.input.clone()
-        }
+    pub fn get_linenumber(&self, index: usize) -> usize {
+        self.input[0..index].matches('\n').count() + 1
     }
 }
@@ -0,0 +1,73 @@
+use std::io;
+use std::str::FromStr;
+
+// When output is colored with ANSI escape codes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    // only when writing to a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = ();
+
+    fn from_str(choice: &str) -> Result<Self, Self::Err> {
+        match choice {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ColorChoice {
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorChoice::Auto => termion::is_tty(&io::stdout()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+// Removes the ANSI escape sequences the printers color their output with
+pub fn strip_colors(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            result.push(c);
+            continue;
+        }
+        // `ESC [ parameters final-byte`, the final byte is a letter
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use termion::color;
+
+    use super::strip_colors;
+
+    #[test]
+    fn should_strip_colors() {
+        let text = format!(
+            "{}let{} a = {}1;",
+            color::Fg(color::Green),
+            color::Fg(color::Reset),
+            color::Fg(color::AnsiValue(4))
+        );
+        assert_eq!(strip_colors(&text), "let a = 1;");
+    }
+}